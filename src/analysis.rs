@@ -0,0 +1,92 @@
+use crate::lexer::{tokenize, TokenKind};
+
+/// The first keyword of a statement in upper case, e.g. `SELECT`, or `OTHER`.
+pub fn statement_kind(sql: &str) -> String {
+    tokenize(sql)
+        .into_iter()
+        .find(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .filter(|token| token.kind == TokenKind::Keyword)
+        .map(|token| sql[token.start..token.end].to_uppercase())
+        .unwrap_or_else(|| String::from("OTHER"))
+}
+
+/// The first table a statement reads or writes, i.e. the name after FROM, INTO, UPDATE or JOIN.
+pub fn main_table(sql: &str) -> Option<String> {
+    tables(sql).into_iter().next()
+}
+
+/// Every table named after FROM, INTO, UPDATE or JOIN, in the order they appear.
+pub fn tables(sql: &str) -> Vec<String> {
+    let mut tables = Vec::new();
+    let tokens: Vec<_> = tokenize(sql)
+        .into_iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Keyword {
+            continue;
+        }
+        let keyword = &sql[token.start..token.end];
+        if !["FROM", "INTO", "UPDATE", "JOIN"]
+            .iter()
+            .any(|k| k.eq_ignore_ascii_case(keyword))
+        {
+            continue;
+        }
+        // schema.table is several tokens, take them all
+        let mut name = String::new();
+        let mut expect_part = true;
+        for token in &tokens[i + 1..] {
+            let text = &sql[token.start..token.end];
+            match token.kind {
+                TokenKind::Identifier | TokenKind::QuotedIdentifier if expect_part => {
+                    name.push_str(text.trim_matches(['"', '`']));
+                    expect_part = false;
+                }
+                TokenKind::Punctuation if text == "." && !expect_part => {
+                    name.push('.');
+                    expect_part = true;
+                }
+                _ => break,
+            }
+        }
+        if !name.is_empty() && !tables.contains(&name) {
+            tables.push(name);
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod test {
+    use crate::analysis::{main_table, statement_kind, tables};
+
+    #[test]
+    fn test_statement_kind_and_table() {
+        assert_eq!(
+            "SELECT",
+            statement_kind("select * from `order` o join user u")
+        );
+        assert_eq!(
+            Some(String::from("order")),
+            main_table("select * from `order` o join user u")
+        );
+        assert_eq!(
+            "INSERT",
+            statement_kind("/* batch */ INSERT INTO app.order_item VALUES (?)")
+        );
+        assert_eq!(
+            Some(String::from("app.order_item")),
+            main_table("/* batch */ INSERT INTO app.order_item VALUES (?)")
+        );
+        assert_eq!(
+            Some(String::from("user")),
+            main_table("UPDATE user SET name = ?")
+        );
+        assert_eq!(None, main_table("SELECT (SELECT 1)"));
+        assert_eq!(
+            vec!["order", "user"],
+            tables("select * from `order` o join user u join `order` p")
+        );
+    }
+}
@@ -1,54 +1,390 @@
-use crate::core::replace_placeholder;
-use crate::log_parser::LogParser;
-use arboard::Clipboard;
+use crate::analysis::{statement_kind, tables};
+use crate::clipboard::{Clipboard, ClipboardBackend};
+use crate::config::Config;
+use crate::core::{bind, BindOptions, Binding, Dialect, Value};
+use crate::diagnostic::{check, Diagnostic, Location};
+use crate::export::{ExportDialog, ExportKind};
+use crate::filter::{Fields, Filter};
+use crate::formatter::format_sql;
+use crate::highlight::LineCache;
+use crate::history::{History, HistoryEntry};
+use crate::keymap::{Action, Command, Keymap, Mode, Profile};
+use crate::layout::{LayoutPreset, SplitHandle};
+use crate::log_parser::Statement;
+use crate::output::{OutputFormat, Record, RecordWriter};
+use crate::palette::Palette;
+use crate::param_table::{format_params, params, Param, ParamTable};
+use crate::result_view::{line_ranges, ResultView};
+use crate::search::Search;
+use crate::session::Session;
+use crate::theme::Theme;
+use crate::worker::{bind_inputs, parse_log, Bound, Parsed, Progress, Worker};
+use clap::ValueEnum;
 use ratatui::layout::Rect;
+use ratatui::widgets::ListState;
 use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::ops::Range;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
+
+/// Inputs larger than this are bound once typing pauses instead of on every key.
+const LIVE_LIMIT: usize = 20_000;
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// Logs larger than this are parsed on the worker.
+const BACKGROUND_LIMIT: usize = 100_000;
+/// How often the progress of a background task is drawn.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 
 pub struct App {
     pub sql_input: TextArea<'static>,
     pub value_input: TextArea<'static>,
     pub result: String,
+    /// Byte ranges of the values written into `result`
+    pub result_literals: Vec<Range<usize>>,
+    pub result_view: ResultView,
+    /// The binding of the current inputs, `None` while a recalculation is pending
+    pub binding: Option<Binding>,
+    /// When the result of large inputs is recalculated
+    pub recalculate_at: Option<Instant>,
+    /// Whether the result bound on the worker is copied once it is back
+    pub copy_when_bound: bool,
+    /// Generation of the inputs and the result last copied, leaving an input copies them only
+    /// when they changed since
+    pub copied_key: Option<u64>,
+    /// Moves whenever the inputs or the result change, the styled lines and the rows of the
+    /// panes are kept while it stays
+    pub generation: u64,
+    /// Row and column offsets of the Sql and Value panes, they follow the cursor
+    pub sql_scroll: (u16, u16),
+    pub value_scroll: (u16, u16),
+    /// Styled lines of the Sql, Value and Result panes, styled again when they change
+    pub sql_lines: LineCache,
+    pub value_lines: LineCache,
+    pub result_lines: LineCache,
     pub current_area: AreaEnum,
     pub should_exit: bool,
     pub area_coordinates: HashMap<AreaEnum, Rect>,
+    /// Borders between the panes drawn last, they are dragged to resize the panes
+    pub split_handles: Vec<SplitHandle>,
+    /// The border being dragged
+    pub dragging: Option<SplitHandle>,
+    /// Whether the focused pane is shown alone
+    pub zoomed: bool,
+    pub config: Config,
+    /// Styles of the TUI, from the `[theme]` of the config
+    pub theme: Theme,
+    pub bind_options: BindOptions,
+    /// Statements of the log read from the clipboard, listed when there is more than one
+    pub statements: Vec<StatementItem>,
+    /// Index in `statements` of the statement shown in the Sql and Value inputs
+    pub selected: usize,
+    /// Only statements containing this text are listed
+    pub filter: String,
+    pub list_state: ListState,
+    pub clipboard: Clipboard,
+    /// Text last copied to or read from the clipboard, it is not parsed again
+    pub copied: String,
+    /// Where converted statements are kept
+    pub history: History,
+    /// The history shown over the panes, `None` when it is closed
+    pub history_view: Option<HistoryView>,
+    /// Scroll of the help shown over the panes, `None` when it is closed
+    pub help: Option<u16>,
+    /// The command palette shown over the panes, `None` when it is closed
+    pub palette: Option<Palette>,
+    /// The search of the Sql, Value or Result pane, kept after its prompt closes to mark the
+    /// matches
+    pub search: Option<Search>,
+    /// The export dialog shown over the panes, `None` when it is closed
+    pub export: Option<ExportDialog>,
+    /// The pane to edit in the external editor, the main loop hands the terminal over to it
+    pub external_edit: Option<AreaEnum>,
+    /// Editing keys of the Sql and Value panes
+    pub keymap: Keymap,
+    pub mode: Mode,
+    /// Whether the moves extend the selection, set by the emacs mark and vim's visual mode
+    pub mark: bool,
+    /// First key of a two key vim command like `dd` or `gg`
+    pub pending: Option<char>,
+    /// Outcome of the last command, shown in the footer until the next key
+    pub message: Option<String>,
+    /// The Value pane shown as a table of its parameters, `None` when it shows the text
+    pub param_table: Option<ParamTable>,
+    /// What looks wrong with the statement in the inputs
+    pub diagnostics: Vec<Diagnostic>,
+    /// What went wrong reading the statements of the clipboard
+    pub log_diagnostics: Vec<Diagnostic>,
+    /// When the clipboard is checked for a new log, `None` when it is not watched
+    pub watch_at: Option<Instant>,
+    /// When the clipboard was last read for the empty inputs
+    pub read_at: Option<Instant>,
+    pub show_diagnostics: bool,
+    /// Position of the selected diagnostic in the panel
+    pub diagnostic_selected: usize,
+    pub diagnostic_state: ListState,
+    /// Parses logs, binds large inputs and reads the clipboard in the background
+    pub worker: Worker,
+}
+
+/// A diagnostic of the panel and the statement of the list it is about.
+pub struct DiagnosticItem {
+    /// Index in `statements`, `None` for the inputs when there is no list or for the log
+    pub statement: Option<usize>,
+    pub diagnostic: Diagnostic,
+}
+
+/// The browsable history, newest entries first.
+pub struct HistoryView {
+    pub entries: Vec<HistoryEntry>,
+    /// Only entries containing every word of the query are listed
+    pub query: String,
+    /// Index in `entries` of the selected entry
+    pub selected: usize,
+    pub list_state: ListState,
 }
 
-#[derive(EnumIter, PartialEq, Clone, Copy, Hash, Eq)]
+impl HistoryView {
+    /// Indices of the entries matching the query.
+    pub fn visible(&self) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|&index| self.entries[index].matches(&self.query))
+            .collect()
+    }
+
+    /// Moves the selection by `delta` listed entries.
+    pub fn select_by(&mut self, delta: isize) {
+        let visible = self.visible();
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let position = match visible.iter().position(|&index| index == self.selected) {
+            Some(position) => position.saturating_add_signed(delta).min(last),
+            None => 0,
+        };
+        self.selected = visible[position];
+    }
+
+    pub fn query_char(&mut self, char: char) {
+        self.query.push(char);
+        self.select_by(0);
+    }
+
+    pub fn query_backspace(&mut self) {
+        self.query.pop();
+        self.select_by(0);
+    }
+}
+
+/// A statement of the list with what is shown about it.
+pub struct StatementItem {
+    pub statement: Statement,
+    pub kind: String,
+    pub table: Option<String>,
+    /// Every table the statement reads or writes, the first one is `table`
+    pub tables: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The statement with its placeholders replaced, empty without sql or parameters
+    pub bound: String,
+}
+
+impl StatementItem {
+    pub fn new(statement: Statement, options: &BindOptions) -> StatementItem {
+        let binding = bind(&statement.sql, &statement.value, options);
+        let tables = tables(&statement.sql);
+        StatementItem {
+            kind: statement_kind(&statement.sql),
+            table: tables.first().cloned(),
+            tables,
+            diagnostics: check(&statement.sql, &statement.value, &binding, options),
+            bound: if statement.sql.is_empty() || statement.value.is_empty() {
+                String::new()
+            } else {
+                binding.result
+            },
+            statement,
+        }
+    }
+}
+
+/// The placeholder under the cursor and the value it takes, or the value under the cursor and
+/// the placeholders taking it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Link {
+    /// Byte ranges of the placeholders in the sql text, a named placeholder may repeat
+    pub placeholders: Vec<Range<usize>>,
+    /// Position of the value in the parameter list, starting at 0
+    pub index: usize,
+    /// The value, `None` when there are fewer values than placeholders
+    pub value: Option<Value>,
+    pub value_count: usize,
+}
+
+impl Link {
+    /// Where the value is and what it is, for the status line.
+    pub fn status(&self) -> String {
+        let position = format!("Value {}/{}", self.index + 1, self.value_count);
+        match &self.value {
+            None => format!("{}: missing", position),
+            Some(value) if value.field_type.is_empty() => {
+                format!("{}: {}", position, value.field_value)
+            }
+            Some(value) => format!("{} ({}): {}", position, value.field_type, value.field_value),
+        }
+    }
+}
+
+#[derive(Debug, EnumIter, PartialEq, Clone, Copy, Hash, Eq)]
 pub enum AreaEnum {
+    List,
     Sql,
     Value,
     Result,
+    Diagnostics,
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(config: Config) -> App {
         App {
             sql_input: TextArea::default(),
             value_input: TextArea::default(),
             result: String::new(),
+            result_literals: Vec::new(),
+            result_view: ResultView::default(),
+            binding: None,
+            recalculate_at: None,
+            copy_when_bound: false,
+            copied_key: None,
+            generation: 0,
+            sql_scroll: (0, 0),
+            value_scroll: (0, 0),
+            sql_lines: LineCache::default(),
+            value_lines: LineCache::default(),
+            result_lines: LineCache::default(),
             current_area: AreaEnum::Sql,
             should_exit: false,
             area_coordinates: HashMap::new(),
+            split_handles: Vec::new(),
+            dragging: None,
+            zoomed: false,
+            theme: config.theme.theme(),
+            bind_options: config.bind_options(None),
+            history: History::new(&config.history),
+            clipboard: Clipboard::new(&config.clipboard),
+            keymap: config.keybindings.editor_keymap(),
+            mode: input_mode(config.keybindings.profile),
+            watch_at: config.clipboard.watch.then(Instant::now),
+            read_at: None,
+            config,
+            statements: Vec::new(),
+            selected: 0,
+            filter: String::new(),
+            list_state: ListState::default(),
+            copied: String::new(),
+            history_view: None,
+            help: None,
+            palette: None,
+            search: None,
+            external_edit: None,
+            export: None,
+            mark: false,
+            pending: None,
+            message: None,
+            param_table: None,
+            diagnostics: Vec::new(),
+            log_diagnostics: Vec::new(),
+            show_diagnostics: false,
+            diagnostic_selected: 0,
+            diagnostic_state: ListState::default(),
+            worker: Worker::default(),
         }
     }
 
+    /// The areas focus moves between, the statement list only when it is shown.
+    fn areas(&self) -> Vec<AreaEnum> {
+        let inputs = self.config.layout.preset != LayoutPreset::ResultOnly;
+        AreaEnum::iter()
+            .filter(|area| *area != AreaEnum::List || self.has_list())
+            .filter(|area| *area != AreaEnum::Diagnostics || self.show_diagnostics)
+            .filter(|area| !matches!(area, AreaEnum::Sql | AreaEnum::Value) || inputs)
+            .collect()
+    }
+
+    pub fn has_list(&self) -> bool {
+        self.statements.len() > 1
+    }
+
     pub fn next_area(&mut self) -> AreaEnum {
-        let all: Vec<_> = AreaEnum::iter().collect();
-        let index = all.iter().position(|x| x == &self.current_area).unwrap();
+        let all = self.areas();
+        let index = all
+            .iter()
+            .position(|x| x == &self.current_area)
+            .unwrap_or(0);
         let next_index = (index + 1) % all.len();
         all[next_index]
     }
 
     pub fn prev_area(&mut self) -> AreaEnum {
-        let all: Vec<_> = AreaEnum::iter().collect();
-        let index = all.iter().position(|x| x == &self.current_area).unwrap();
+        let all = self.areas();
+        let index = all
+            .iter()
+            .position(|x| x == &self.current_area)
+            .unwrap_or(0);
         let prev_index = if index == 0 { all.len() - 1 } else { index - 1 };
         all[prev_index]
     }
 
+    /// Shows the focused pane alone, or all panes again.
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed = !self.zoomed;
+    }
+
+    /// Switches to the layout `preset`, the focus leaves the panes it hides.
+    pub fn set_layout(&mut self, preset: LayoutPreset) {
+        self.config.layout.preset = preset;
+        if !self.areas().contains(&self.current_area) {
+            self.focus(AreaEnum::Result);
+        }
+        self.message = Some(format!("layout: {}", preset.name()));
+        self.save_layout();
+    }
+
+    /// Starts dragging the border between panes under the mouse, if there is one.
+    pub fn start_drag(&mut self, column: u16, row: u16) -> bool {
+        self.dragging = self
+            .split_handles
+            .iter()
+            .find(|handle| handle.contains(column, row))
+            .copied();
+        self.dragging.is_some()
+    }
+
+    pub fn drag(&mut self, column: u16, row: u16) {
+        if let Some(handle) = self.dragging {
+            self.config.layout.drag(&handle, column, row);
+        }
+    }
+
+    /// Stops dragging, the new sizes are kept for the next session.
+    pub fn stop_drag(&mut self) {
+        if self.dragging.take().is_some() {
+            self.save_layout();
+        }
+    }
+
+    fn save_layout(&mut self) {
+        if !self.config.layout.remember {
+            return;
+        }
+        if let Err(e) = self.config.layout.save() {
+            self.message = Some(format!("cannot keep the layout: {}", e));
+        }
+    }
+
     pub fn set_area_coordinate(&mut self, area: AreaEnum, rect: Rect) {
         self.area_coordinates.insert(area, rect);
     }
@@ -70,29 +406,860 @@ impl App {
             AreaEnum::Value => {
                 self.value_input = TextArea::default();
             }
+            AreaEnum::List => {
+                self.filter.clear();
+                return;
+            }
+            AreaEnum::Diagnostics => {
+                self.log_diagnostics.clear();
+                return;
+            }
             AreaEnum::Result => {
                 self.sql_input = TextArea::default();
                 self.value_input = TextArea::default();
-                self.result = String::new();
+                self.statements.clear();
+                self.filter.clear();
             }
         }
+        self.input_changed();
     }
 
     pub fn input_char(&mut self, char: char) {
         if let Some(input) = self.get_current_input() {
             input.insert_char(char);
+            self.mark = false;
+            self.input_changed();
         }
     }
 
-    pub fn input_backspace(&mut self) {
-        if let Some(input) = self.get_current_input() {
-            input.delete_char();
+    /// Applies an editing action to the focused input.
+    pub fn edit(&mut self, action: Action) {
+        let page = self.page_height();
+        // pasting takes the system clipboard, or what was copied or deleted in the inputs
+        let pasted = (action == Action::Paste)
+            .then(|| self.clipboard.get_text())
+            .and_then(Result::ok)
+            .map(|text| text.replace("\r\n", "\n"));
+        let mark = self.mark;
+        let Some(input) = self.get_current_input() else {
+            return;
+        };
+        let selecting = input.is_selecting();
+        let mut moved = false;
+        let changed = match action {
+            Action::SelectAll => {
+                input.select_all();
+                false
+            }
+            Action::Newline => {
+                input.insert_newline();
+                true
+            }
+            Action::Backspace => input.delete_char(),
+            Action::Delete => input.delete_next_char(),
+            Action::DeleteWordLeft => input.delete_word(),
+            Action::DeleteWordRight => input.delete_next_word(),
+            Action::DeleteLineEnd => input.delete_line_by_end(),
+            Action::Undo => input.undo(),
+            Action::Redo => input.redo(),
+            Action::Copy => {
+                input.copy();
+                false
+            }
+            Action::Cut => input.cut(),
+            Action::Paste => {
+                if let Some(text) = pasted {
+                    input.set_yank_text(text);
+                }
+                input.paste()
+            }
+            Action::SetMark => {
+                input.cancel_selection();
+                input.start_selection();
+                false
+            }
+            Action::Cancel => {
+                input.cancel_selection();
+                false
+            }
+            Action::Command => {
+                self.mode = Mode::Command(String::new());
+                return;
+            }
+            Action::Search => {
+                self.start_search();
+                return;
+            }
+            motion => {
+                moved = move_cursor(input, motion, page, mark);
+                false
+            }
+        };
+        if selecting && matches!(action, Action::Copy | Action::Cut) {
+            let text = input.yank_text();
+            self.copy_text(text);
+        }
+        self.mark = match action {
+            Action::SetMark => true,
+            _ => mark && moved,
+        };
+        if changed {
+            self.input_changed();
         }
     }
 
-    pub fn input_delete(&mut self) {
-        if let Some(input) = self.get_current_input() {
-            input.delete_next_char();
+    /// Copies or cuts the line of the cursor with its line break, like vim's `yy` and `dd`.
+    pub fn edit_line(&mut self, action: Action) {
+        let Some(input) = self.get_current_input() else {
+            return;
+        };
+        let cursor = input.cursor();
+        let last = input.lines().len() - 1;
+        input.cancel_selection();
+        if cursor.0 < last {
+            input.move_cursor(CursorMove::Head);
+            input.start_selection();
+            input.move_cursor(CursorMove::Down);
+        } else if cursor.0 > 0 {
+            // the last line takes the line break before it
+            input.move_cursor(CursorMove::Up);
+            input.move_cursor(CursorMove::End);
+            input.start_selection();
+            input.move_cursor(CursorMove::Down);
+            input.move_cursor(CursorMove::End);
+        } else {
+            input.move_cursor(CursorMove::Head);
+            input.start_selection();
+            input.move_cursor(CursorMove::End);
+        }
+        self.edit(action);
+        if action == Action::Copy {
+            if let Some(input) = self.get_current_input() {
+                input.move_cursor(CursorMove::Jump(cursor.0 as u16, cursor.1 as u16));
+            }
+        }
+    }
+
+    /// The mode the Sql and Value panes return to after a command.
+    pub fn input_mode(&self) -> Mode {
+        input_mode(self.config.keybindings.profile)
+    }
+
+    /// Runs `command`, for the keys working in every pane and the command palette.
+    pub fn execute(&mut self, command: Command) {
+        match command {
+            Command::Exit => self.should_exit = true,
+            Command::Clear => self.input_clear(),
+            Command::Copy => {
+                self.calculate_result();
+                self.copy_result();
+            }
+            Command::CopyAll => self.copy_all(),
+            Command::History => self.toggle_history(),
+            Command::Table => self.toggle_param_table(),
+            Command::Diagnostics => self.toggle_diagnostics(),
+            Command::Zoom => self.toggle_zoom(),
+            Command::NextLayout => self.set_layout(self.config.layout.preset.next()),
+            Command::Layout(preset) => self.set_layout(preset),
+            Command::NextArea => {
+                let area = self.next_area();
+                self.focus(area);
+            }
+            Command::PrevArea => {
+                let area = self.prev_area();
+                self.focus(area);
+            }
+            Command::Focus(area) => {
+                if area == AreaEnum::Diagnostics && !self.show_diagnostics {
+                    self.toggle_diagnostics();
+                }
+                if self.areas().contains(&area) {
+                    self.focus(area);
+                } else {
+                    self.message = Some(format!("{} is hidden", area.title()));
+                }
+            }
+            Command::Help => self.toggle_help(),
+            Command::Palette => self.toggle_palette(),
+            Command::Search => self.start_search(),
+            Command::SearchNext => self.search_next(1),
+            Command::SearchPrev => self.search_next(-1),
+            Command::Format => {
+                let sql = self.get_sql_text();
+                if sql.trim().is_empty() {
+                    self.message = Some(String::from("nothing to format"));
+                } else {
+                    // replaced as an edit, so it can be undone
+                    self.sql_input.select_all();
+                    self.sql_input.insert_str(format_sql(&sql));
+                    self.input_changed();
+                }
+            }
+            Command::Watch => {
+                self.toggle_watch();
+                self.message = Some(String::from(if self.watch_at.is_some() {
+                    "watching the clipboard"
+                } else {
+                    "stopped watching the clipboard"
+                }));
+            }
+            Command::Write => self.mode = Mode::Command(String::from("w ")),
+            Command::Export => self.toggle_export(),
+            Command::Editor => {
+                self.external_edit = Some(match self.current_area {
+                    area @ (AreaEnum::Sql | AreaEnum::Value) => area,
+                    AreaEnum::List | AreaEnum::Result | AreaEnum::Diagnostics => AreaEnum::Result,
+                })
+            }
+            Command::Dialect(dialect) => {
+                self.bind_options.dialect = dialect;
+                self.calculate_result();
+            }
+        }
+    }
+
+    /// Opens the list of keys, or closes it.
+    pub fn toggle_help(&mut self) {
+        self.palette = None;
+        self.export = None;
+        self.help = match self.help {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    /// Opens the command palette with the commands that can be run now, or closes it.
+    pub fn toggle_palette(&mut self) {
+        self.help = None;
+        self.export = None;
+        if self.palette.take().is_some() {
+            return;
+        }
+        let mut commands = vec![
+            Command::Copy,
+            Command::Search,
+            Command::Format,
+            Command::Editor,
+            Command::Write,
+            Command::Export,
+            Command::Table,
+            Command::Diagnostics,
+            Command::Zoom,
+            Command::History,
+            Command::Watch,
+            Command::Clear,
+            Command::Help,
+            Command::Exit,
+        ];
+        if self.has_list() {
+            commands.insert(1, Command::CopyAll);
+        }
+        commands.extend(
+            Dialect::value_variants()
+                .iter()
+                .map(|d| Command::Dialect(*d)),
+        );
+        commands.extend(
+            LayoutPreset::value_variants()
+                .iter()
+                .map(|p| Command::Layout(*p)),
+        );
+        commands.extend(
+            AreaEnum::iter()
+                .filter(|area| *area != AreaEnum::List || self.has_list())
+                .map(Command::Focus),
+        );
+        self.palette = Some(Palette::new(commands));
+    }
+
+    /// Closes the command palette and runs the selected command.
+    pub fn run_palette(&mut self) {
+        if let Some(command) = self.palette.take().and_then(|p| p.selected_command()) {
+            self.execute(command);
+        }
+    }
+
+    /// Runs a command line: `w <file>` writes the result, `yank` copies it, `format` formats
+    /// the sql, `dialect <name>` changes the dialect, `layout <preset>` the layout, `history`,
+    /// `watch`, `zoom`, `help`, `palette`, `clear` and `q` quits.
+    pub fn run_command(&mut self, line: &str) {
+        let (name, argument) = match line.trim().split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (line.trim(), ""),
+        };
+        self.message = match name {
+            "" => None,
+            "w" | "write" => Some(self.write_result(argument).unwrap_or_else(|e| e)),
+            "wq" => match self.write_result(argument) {
+                Ok(_) => {
+                    self.should_exit = true;
+                    None
+                }
+                Err(e) => Some(e),
+            },
+            "q" | "quit" => {
+                self.should_exit = true;
+                None
+            }
+            "y" | "yank" => {
+                self.calculate_result();
+                self.copy_result();
+                // a failed copy already says why
+                self.message.take().or(Some(if !self.can_copy() {
+                    String::from("the clipboard is turned off")
+                } else if self.result.is_empty() {
+                    String::from("nothing to copy, the result is empty")
+                } else {
+                    String::from("copied the result")
+                }))
+            }
+            "format" => self.command_message(Command::Format),
+            "dialect" => match Dialect::from_str(argument, true) {
+                Ok(dialect) => self.command_message(Command::Dialect(dialect)),
+                Err(_) => Some(format!("unknown dialect '{}'", argument)),
+            },
+            "history" => self.command_message(Command::History),
+            "watch" => self.command_message(Command::Watch),
+            "layout" if argument.is_empty() => self.command_message(Command::NextLayout),
+            "layout" => match LayoutPreset::from_str(argument, true) {
+                Ok(preset) => self.command_message(Command::Layout(preset)),
+                Err(_) => Some(format!("unknown layout '{}'", argument)),
+            },
+            "zoom" => self.command_message(Command::Zoom),
+            "help" => self.command_message(Command::Help),
+            "palette" => self.command_message(Command::Palette),
+            "e" | "edit" => self.command_message(Command::Editor),
+            "export" => self.command_message(Command::Export),
+            "session" => match argument.split_once(char::is_whitespace) {
+                Some(("save", path)) => Some(
+                    self.session()
+                        .save(Path::new(path.trim()))
+                        .map(|_| format!("saved the session to {}", path.trim()))
+                        .unwrap_or_else(|e| e),
+                ),
+                Some(("open", path)) => Some(
+                    Session::load(Path::new(path.trim()))
+                        .map(|session| {
+                            self.open_session(session);
+                            format!("opened the session {}", path.trim())
+                        })
+                        .unwrap_or_else(|e| e),
+                ),
+                _ => Some(String::from("usage: session save|open <file>")),
+            },
+            "noh" | "nohlsearch" => {
+                self.search = None;
+                None
+            }
+            "clear" => {
+                self.current_area = AreaEnum::Result;
+                self.input_clear();
+                None
+            }
+            _ => Some(format!("unknown command: {}", name)),
+        };
+    }
+
+    /// Opens the export dialog, or closes it.
+    pub fn toggle_export(&mut self) {
+        self.help = None;
+        self.palette = None;
+        self.export = match self.export {
+            Some(_) => None,
+            None => Some(ExportDialog::default()),
+        };
+    }
+
+    /// Writes what the export dialog asks for, the dialog stays open when it fails.
+    pub fn run_export(&mut self) {
+        let Some(dialog) = self.export.as_ref() else {
+            return;
+        };
+        let (kind, path) = (dialog.kind, dialog.path.trim().to_string());
+        match self.export_to(kind, &path) {
+            Ok(message) => {
+                self.export = None;
+                self.message = Some(message);
+            }
+            Err(e) => self.message = Some(e),
+        }
+    }
+
+    fn export_to(&mut self, kind: ExportKind, path: &str) -> Result<String, String> {
+        if path.is_empty() {
+            return Err(String::from("no file to write to"));
+        }
+        let written = |count: usize| match count {
+            1 => format!("written 1 statement to {}", path),
+            count => format!("written {} statements to {}", count, path),
+        };
+        match kind {
+            ExportKind::Result => self.write_result(path),
+            ExportKind::Statements => {
+                let records = self.export_records()?;
+                let bound: Vec<&str> = records.iter().map(|r| r.bound.as_str()).collect();
+                fs::write(path, format!("{}\n", bound.join("\n")))
+                    .map_err(|e| format!("cannot write {}: {}", path, e))?;
+                Ok(written(records.len()))
+            }
+            ExportKind::Json | ExportKind::Csv => {
+                let records = self.export_records()?;
+                let count = records.len();
+                let format = match kind {
+                    ExportKind::Csv => OutputFormat::Csv,
+                    _ => OutputFormat::Json,
+                };
+                let write = || -> std::io::Result<()> {
+                    let mut writer = RecordWriter::new(BufWriter::new(File::create(path)?), format);
+                    for record in records {
+                        writer.write(record)?;
+                    }
+                    writer.finish()
+                };
+                write().map_err(|e| format!("cannot write {}: {}", path, e))?;
+                Ok(written(count))
+            }
+            ExportKind::Session => {
+                self.session().save(Path::new(path))?;
+                Ok(format!("saved the session to {}", path))
+            }
+        }
+    }
+
+    /// The records of the listed statements, or of the one typed into the inputs.
+    fn export_records(&mut self) -> Result<Vec<Record>, String> {
+        self.store_edits();
+        let statements = self.export_statements();
+        if statements.is_empty() {
+            return Err(String::from("nothing to export, there is no statement"));
+        }
+        Ok(statements
+            .iter()
+            .map(|statement| Record::new(statement, &self.bind_options))
+            .collect())
+    }
+
+    fn export_statements(&self) -> Vec<Statement> {
+        if !self.statements.is_empty() {
+            return self
+                .visible_statements()
+                .into_iter()
+                .map(|index| self.statements[index].statement.clone())
+                .collect();
+        }
+        let (sql, value) = (self.get_sql_text(), self.get_value_text());
+        if sql.trim().is_empty() {
+            return Vec::new();
+        }
+        vec![Statement {
+            sql,
+            value,
+            format: String::from("manual"),
+            source: None,
+            wrapper: None,
+            timestamp: None,
+            thread: None,
+            trace_id: None,
+            rows: None,
+        }]
+    }
+
+    /// What the TUI shows, to be opened again with `open_session`.
+    pub fn session(&mut self) -> Session {
+        self.store_edits();
+        Session {
+            dialect: self.bind_options.dialect,
+            placeholder_style: self.bind_options.placeholder_style,
+            sql: self.get_sql_text(),
+            value: self.get_value_text(),
+            result: self.result.clone(),
+            statements: self
+                .statements
+                .iter()
+                .map(|item| item.statement.clone())
+                .collect(),
+            selected: self.selected,
+            filter: self.filter.clone(),
+            ..Session::default()
+        }
+    }
+
+    /// Shows a saved session: its statements, inputs and result, whatever was shown before.
+    pub fn open_session(&mut self, session: Session) {
+        self.bind_options.dialect = session.dialect;
+        self.bind_options.placeholder_style = session.placeholder_style;
+        self.statements = session
+            .statements
+            .into_iter()
+            .map(|statement| StatementItem::new(statement, &self.bind_options))
+            .collect();
+        self.filter = session.filter;
+        self.selected = session
+            .selected
+            .min(self.statements.len().saturating_sub(1));
+        // the inputs may hold edits of the selected statement
+        self.sql_input = TextArea::from(session.sql.lines());
+        self.value_input = TextArea::from(session.value.lines());
+        self.sql_scroll = (0, 0);
+        self.value_scroll = (0, 0);
+        self.search = None;
+        self.calculate_result();
+        // an edited result, or one bound with other types than the configured ones
+        if self.result != session.result {
+            self.result = session.result;
+            self.result_literals.clear();
+            self.text_changed();
+        }
+        self.current_area = if self.has_list() {
+            AreaEnum::List
+        } else {
+            AreaEnum::Result
+        };
+    }
+
+    /// The text of `area` for the external editor, with the extension of its file.
+    pub fn editor_text(&mut self, area: AreaEnum) -> (String, &'static str) {
+        match area {
+            AreaEnum::Sql => (self.get_sql_text(), "sql"),
+            AreaEnum::Value => (self.get_value_text(), "txt"),
+            _ => {
+                self.calculate_result();
+                (self.result.clone(), "sql")
+            }
+        }
+    }
+
+    /// Puts the text saved in the external editor back into `area`. An edited result is kept
+    /// and copied until the inputs change.
+    pub fn edited(&mut self, area: AreaEnum, edited: Result<String, String>) {
+        let text = match edited {
+            Ok(text) => text.replace("\r\n", "\n"),
+            Err(e) => {
+                self.message = Some(e);
+                return;
+            }
+        };
+        let input = match area {
+            AreaEnum::Sql => &mut self.sql_input,
+            AreaEnum::Value => &mut self.value_input,
+            _ => {
+                if text != self.result {
+                    self.result = text;
+                    self.result_literals.clear();
+                    self.text_changed();
+                    self.result_view.selection = None;
+                    self.copy_result();
+                    self.message = Some(String::from(
+                        "kept the edited result until the Sql or Value changes",
+                    ));
+                }
+                return;
+            }
+        };
+        if input.lines().join("\n") != text {
+            // replaced as an edit, so it can be undone
+            input.select_all();
+            input.insert_str(text);
+            self.input_changed();
+        }
+    }
+
+    /// Opens the search prompt for the focused pane, or for the Result when the focused pane
+    /// has no text to search.
+    pub fn start_search(&mut self) {
+        let area = match self.current_area {
+            AreaEnum::Value if self.param_table.is_some() => {
+                self.message = Some(String::from("show the parameters as text to search them"));
+                return;
+            }
+            area @ (AreaEnum::Sql | AreaEnum::Value) => area,
+            AreaEnum::List | AreaEnum::Result | AreaEnum::Diagnostics => AreaEnum::Result,
+        };
+        if !self.areas().contains(&area) {
+            self.message = Some(format!("{} is hidden", area.title()));
+            return;
+        }
+        self.focus(area);
+        let origin = match area {
+            AreaEnum::Sql => cursor_offset(&self.sql_input),
+            AreaEnum::Value => cursor_offset(&self.value_input),
+            _ => line_ranges(&self.result)
+                .get(self.result_view.cursor)
+                .map_or(0, |line| line.start),
+        };
+        self.search = Some(Search::new(area, origin));
+    }
+
+    /// The text the search looks in.
+    fn search_text(&self, area: AreaEnum) -> String {
+        match area {
+            AreaEnum::Sql => self.get_sql_text(),
+            AreaEnum::Value => self.get_value_text(),
+            _ => self.result.clone(),
+        }
+    }
+
+    /// Notes a change of the inputs or the result: the panes are styled again, the search looks
+    /// in the new text and the parameter table shows the new values.
+    fn text_changed(&mut self) {
+        self.generation += 1;
+        self.find_matches();
+        if self.param_table.is_some() {
+            let params = params(&self.get_value_text());
+            if let Some(table) = self.param_table.as_mut() {
+                table.params = params;
+            }
+        }
+    }
+
+    fn find_matches(&mut self) {
+        if let Some(area) = self.search.as_ref().map(|search| search.area) {
+            let text = self.search_text(area);
+            if let Some(search) = self.search.as_mut() {
+                search.find_in(&text);
+            }
+        }
+    }
+
+    pub fn search_char(&mut self, char: char) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.push(char);
+            self.search_changed();
+        }
+    }
+
+    pub fn search_backspace(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.pop();
+            self.search_changed();
+        }
+    }
+
+    /// Moves to the first match after where the search started, or back there without one.
+    fn search_changed(&mut self) {
+        self.find_matches();
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.first_after_origin();
+        let (area, offset) = match search.matches.get(search.current) {
+            Some(range) => (search.area, range.start),
+            None => (search.area, search.origin),
+        };
+        self.show_offset(area, offset);
+    }
+
+    /// Moves to the next match, or to a previous one with a negative `delta`.
+    pub fn search_next(&mut self, delta: isize) {
+        let Some(search) = self.search.as_mut() else {
+            self.message = Some(String::from("nothing searched"));
+            return;
+        };
+        search.move_by(delta);
+        let area = search.area;
+        match search.matches.get(search.current).map(|range| range.start) {
+            Some(start) => {
+                if self.current_area != area {
+                    self.focus(area);
+                }
+                self.show_offset(area, start);
+            }
+            None => self.message = Some(format!("no match for '{}'", search.query)),
+        }
+    }
+
+    /// Closes the search prompt, keeping the matches marked or going back to where the search
+    /// started.
+    pub fn end_search(&mut self, keep: bool) {
+        match self.search.as_mut() {
+            Some(search) if keep && !search.query.is_empty() => search.typing = false,
+            Some(search) => {
+                let (area, origin) = (search.area, search.origin);
+                self.search = None;
+                self.show_offset(area, origin);
+            }
+            None => {}
+        }
+    }
+
+    /// Moves the cursor of `area` to the byte `offset` of its text.
+    fn show_offset(&mut self, area: AreaEnum, offset: usize) {
+        let text = self.search_text(area);
+        let (row, column) = position(&text, offset);
+        match area {
+            AreaEnum::Sql => self.sql_input.move_cursor(CursorMove::Jump(row, column)),
+            AreaEnum::Value => self.value_input.move_cursor(CursorMove::Jump(row, column)),
+            _ => {
+                let view = &mut self.result_view;
+                view.cursor = row as usize;
+                // lines that are not wrapped scroll sideways to the match
+                let width = self
+                    .area_coordinates
+                    .get(&AreaEnum::Result)
+                    .map_or(40, |rect| rect.width.saturating_sub(8) as usize)
+                    .max(1);
+                let column = column as usize;
+                if !view.wrap && (column < view.column || column >= view.column + width) {
+                    view.column = column.saturating_sub(width / 2);
+                }
+            }
+        }
+    }
+
+    /// Runs `command` and takes the message it left.
+    fn command_message(&mut self, command: Command) -> Option<String> {
+        self.execute(command);
+        self.message.take()
+    }
+
+    fn write_result(&mut self, path: &str) -> Result<String, String> {
+        if path.is_empty() {
+            return Err(String::from("usage: w <file>"));
+        }
+        // a result still waiting for its binding is made now, an edited one is kept
+        if self.recalculate_at.is_some() || self.worker.is_binding() {
+            self.calculate_result();
+        }
+        if self.result.is_empty() {
+            return Err(String::from("nothing to write, the result is empty"));
+        }
+        fs::write(path, format!("{}\n", self.result))
+            .map(|_| format!("written {}", path))
+            .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    /// Rows of the focused pane, the distance PageUp and PageDown move.
+    pub fn page_height(&self) -> u16 {
+        self.area_coordinates
+            .get(&self.current_area)
+            .map_or(10, |rect| rect.height.saturating_sub(2).max(1))
+    }
+
+    /// Byte range of the selected text of the Sql or Value input.
+    pub fn selection(&self, area: AreaEnum) -> Option<Range<usize>> {
+        let input = match area {
+            AreaEnum::Sql => &self.sql_input,
+            AreaEnum::Value => &self.value_input,
+            AreaEnum::List | AreaEnum::Result | AreaEnum::Diagnostics => return None,
+        };
+        let (start, end) = input.selection_range()?;
+        Some(offset(input, start)..offset(input, end))
+    }
+
+    /// Recalculates the result after an edit, for large inputs once typing pauses.
+    pub fn input_changed(&mut self) {
+        self.text_changed();
+        self.copy_when_bound = false;
+        if self.input_size() > LIVE_LIMIT {
+            self.binding = None;
+            self.recalculate_at = Some(Instant::now() + DEBOUNCE);
+        } else {
+            self.calculate_result();
+        }
+    }
+
+    fn input_size(&self) -> usize {
+        self.sql_input
+            .lines()
+            .iter()
+            .chain(self.value_input.lines())
+            .map(String::len)
+            .sum()
+    }
+
+    /// Recalculates the result now, for large inputs on the worker.
+    fn recalculate(&mut self) {
+        self.text_changed();
+        if self.input_size() > LIVE_LIMIT {
+            self.bind_in_background();
+        } else {
+            self.calculate_result();
+        }
+    }
+
+    /// Binds the inputs on the worker, the result shown stays until the binding is back.
+    fn bind_in_background(&mut self) {
+        self.binding = None;
+        self.recalculate_at = None;
+        let (sql, value) = (self.get_sql_text(), self.get_value_text());
+        self.worker.bind(sql, value, self.bind_options.clone());
+    }
+
+    /// Shows a binding done on the worker, unless the inputs changed since.
+    pub fn bound(&mut self, id: u64, bound: Bound) {
+        if !self.worker.finish(id) || self.recalculate_at.is_some() {
+            return;
+        }
+        if bound.sql != self.get_sql_text() || bound.value != self.get_value_text() {
+            return;
+        }
+        self.show_binding(bound);
+        if std::mem::take(&mut self.copy_when_bound) {
+            self.copy_changed_result();
+        }
+    }
+
+    /// Stops parsing a log in the background, it is not read again.
+    pub fn cancel_parse(&mut self) -> bool {
+        let cancelled = self.worker.cancel_parse();
+        if cancelled {
+            self.message = Some(String::from("stopped reading the clipboard"));
+        }
+        cancelled
+    }
+
+    /// Runs a pending recalculation and watches the clipboard when they are due.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if self.recalculate_at.is_some_and(|at| at <= now) {
+            self.bind_in_background();
+        }
+        if self.watch_at.is_some_and(|at| at <= now) {
+            self.watch_clipboard();
+            self.watch_at = Some(now + self.watch_interval());
+        }
+    }
+
+    /// When `tick` has something to do or the progress of a task is drawn next, `None` when
+    /// it waits for events only.
+    pub fn next_tick(&self) -> Option<Instant> {
+        let progress = self
+            .worker
+            .task()
+            .map(|_| Instant::now() + PROGRESS_INTERVAL);
+        [self.recalculate_at, self.watch_at, progress]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+
+    fn watch_interval(&self) -> Duration {
+        Duration::from_millis(self.config.clipboard.watch_interval.max(50))
+    }
+
+    /// Starts or stops loading the logs copied while the TUI is open.
+    pub fn toggle_watch(&mut self) {
+        self.watch_at = match self.watch_at {
+            Some(_) => None,
+            None => Some(Instant::now()),
+        };
+    }
+
+    /// Moves the focus, leaving an input copies the result.
+    pub fn focus(&mut self, area: AreaEnum) {
+        let left_input = area != self.current_area
+            && matches!(self.current_area, AreaEnum::Sql | AreaEnum::Value);
+        self.current_area = area;
+        if left_input {
+            if self.recalculate_at.is_some() {
+                self.bind_in_background();
+            }
+            // a binding still running is copied once it is back
+            if self.worker.is_binding() {
+                self.copy_when_bound = true;
+            } else {
+                self.copy_changed_result();
+            }
         }
     }
 
@@ -112,43 +1279,1075 @@ impl App {
         self.value_input.lines().join("\n")
     }
 
+    /// Links the placeholder or value under the cursor of the focused input.
+    pub fn link(&self) -> Option<Link> {
+        let binding = self.binding.as_ref()?;
+        let index = match self.current_area {
+            AreaEnum::Sql => {
+                let offset = cursor_offset(&self.sql_input);
+                // the cursor may also sit right after a placeholder that was just typed
+                let placeholder = binding
+                    .placeholders
+                    .iter()
+                    .find(|p| p.span.contains(&offset))
+                    .or_else(|| binding.placeholders.iter().find(|p| p.span.end == offset))?;
+                placeholder.value
+            }
+            AreaEnum::Value if self.param_table.is_some() => {
+                let index = self.param_table.as_ref()?.selected;
+                Some(index)
+                    .filter(|&index| binding.placeholders.iter().any(|p| p.value == index))?
+            }
+            AreaEnum::Value => {
+                let offset = cursor_offset(&self.value_input);
+                binding
+                    .values
+                    .iter()
+                    .position(|v| v.span.start <= offset && offset <= v.span.end)
+                    .filter(|&index| binding.placeholders.iter().any(|p| p.value == index))?
+            }
+            AreaEnum::List | AreaEnum::Result | AreaEnum::Diagnostics => return None,
+        };
+        Some(Link {
+            placeholders: binding
+                .placeholders
+                .iter()
+                .filter(|p| p.value == index)
+                .map(|p| p.span.clone())
+                .collect(),
+            index,
+            value: binding.values.get(index).cloned(),
+            value_count: binding.values.len(),
+        })
+    }
+
     pub fn calculate_result(&mut self) {
-        self.result =
-            replace_placeholder(self.get_sql_text().as_str(), self.get_value_text().as_str());
+        let bound = bind_inputs(
+            self.get_sql_text(),
+            self.get_value_text(),
+            &self.bind_options,
+        );
+        self.show_binding(bound);
+    }
+
+    fn show_binding(&mut self, bound: Bound) {
+        let Bound {
+            sql,
+            value,
+            binding,
+            diagnostics,
+        } = bound;
+        self.text_changed();
+        let previous = std::mem::take(&mut self.result);
+        if sql.is_empty() || value.is_empty() {
+            self.result = String::new();
+            self.result_literals.clear();
+        } else {
+            self.result = binding.result.clone();
+            self.result_literals = binding
+                .placeholders
+                .iter()
+                .filter_map(|placeholder| placeholder.result.clone())
+                .collect();
+        }
+        if self.result != previous {
+            self.result_view.selection = None;
+        }
+        self.diagnostics = diagnostics;
+        self.binding = Some(binding);
+        self.recalculate_at = None;
+    }
+
+    pub fn copy_result(&mut self) {
+        self.copied_key = Some(self.generation);
+        if !self.result.is_empty() {
+            let format = match self.statements.get(self.selected) {
+                Some(item) => item.statement.format.clone(),
+                None => String::from("manual"),
+            };
+            let (sql, value) = (self.get_sql_text(), self.get_value_text());
+            self.record(vec![HistoryEntry::new(
+                "tui",
+                &format,
+                &sql,
+                &value,
+                &self.result,
+            )]);
+        }
+        self.copy_text(self.result.clone());
+    }
+
+    /// Copies the result unless it was copied with the same inputs, moving between the panes
+    /// neither overwrites the clipboard nor adds to the history.
+    fn copy_changed_result(&mut self) {
+        if self.copied_key != Some(self.generation) {
+            self.copy_result();
+        }
+    }
+
+    /// Copies the selection of the Result pane, or the statement on its cursor line.
+    pub fn copy_result_selection(&mut self) {
+        let Some(text) = self.result_view.selected_text(&self.result) else {
+            return;
+        };
+        let text = text.to_string();
+        self.message = Some(match text.lines().count() {
+            _ if !self.can_copy() => String::from("the clipboard is turned off"),
+            1 => String::from("copied 1 line"),
+            lines => format!("copied {} lines", lines),
+        });
+        self.copy_text(text);
+    }
+
+    /// Copies the listed statements with their placeholders replaced, one per line.
+    pub fn copy_all(&mut self) {
+        self.store_edits();
+        let entries: Vec<HistoryEntry> = self
+            .visible_statements()
+            .into_iter()
+            .map(|index| &self.statements[index])
+            .map(|item| {
+                let statement = &item.statement;
+                HistoryEntry::new(
+                    "tui",
+                    &statement.format,
+                    &statement.sql,
+                    &statement.value,
+                    &item.bound,
+                )
+            })
+            .collect();
+        let all: Vec<&str> = entries.iter().map(|entry| entry.bound.as_str()).collect();
+        let text = all.join("\n");
+        self.record(entries);
+        self.copy_text(text);
+    }
+
+    fn record(&mut self, entries: Vec<HistoryEntry>) {
+        self.history.add(entries);
+    }
+
+    /// Shows the Value pane as a table of its parameters, or as text again.
+    pub fn toggle_param_table(&mut self) {
+        if self.param_table.take().is_none() {
+            self.param_table = Some(ParamTable {
+                params: params(&self.get_value_text()),
+                ..ParamTable::default()
+            });
+            self.focus(AreaEnum::Value);
+        }
+    }
+
+    /// The parameters of the Value pane shown as a table, none when it is shown as text.
+    pub fn params(&self) -> &[Param] {
+        self.param_table
+            .as_ref()
+            .map_or(&[], |table| table.params.as_slice())
+    }
+
+    /// Writes `params` into the Value pane as a parameter list.
+    pub fn set_params(&mut self, params: &[Param]) {
+        self.value_input = TextArea::from([format_params(params)]);
+        self.input_changed();
+    }
 
-        Clipboard::new()
-            .unwrap()
-            .set_text(self.result.as_str())
-            .expect("failed to set clipboard");
+    /// Shows or hides the diagnostics panel.
+    pub fn toggle_diagnostics(&mut self) {
+        self.show_diagnostics = !self.show_diagnostics;
+        if !self.show_diagnostics && self.current_area == AreaEnum::Diagnostics {
+            self.current_area = AreaEnum::Result;
+        }
+    }
+
+    /// The diagnostics of the log, then those of every statement, the one in the inputs as
+    /// it is edited.
+    pub fn diagnostic_items(&self) -> Vec<DiagnosticItem> {
+        let mut items: Vec<DiagnosticItem> = self
+            .log_diagnostics
+            .iter()
+            .map(|diagnostic| DiagnosticItem {
+                statement: None,
+                diagnostic: diagnostic.clone(),
+            })
+            .collect();
+        if self.statements.is_empty() {
+            items.extend(self.diagnostics.iter().map(|diagnostic| DiagnosticItem {
+                statement: None,
+                diagnostic: diagnostic.clone(),
+            }));
+        }
+        for (index, item) in self.statements.iter().enumerate() {
+            let diagnostics = if index == self.selected {
+                &self.diagnostics
+            } else {
+                &item.diagnostics
+            };
+            items.extend(diagnostics.iter().map(|diagnostic| DiagnosticItem {
+                statement: Some(index),
+                diagnostic: diagnostic.clone(),
+            }));
+        }
+        items
+    }
+
+    /// Moves the selection of the diagnostics panel by `delta`.
+    pub fn select_diagnostic_by(&mut self, delta: isize) {
+        let last = self.diagnostic_items().len().saturating_sub(1);
+        self.diagnostic_selected = self
+            .diagnostic_selected
+            .saturating_add_signed(delta)
+            .min(last);
+    }
+
+    /// Shows the statement of the selected diagnostic and puts the cursor where it points to.
+    pub fn jump_to_diagnostic(&mut self) {
+        let Some(item) = self
+            .diagnostic_items()
+            .into_iter()
+            .nth(self.diagnostic_selected)
+        else {
+            return;
+        };
+        if let Some(index) = item.statement {
+            self.select(index);
+        }
+        let (area, offset) = match item.diagnostic.location {
+            Some(Location::Sql(range)) => (AreaEnum::Sql, range.start),
+            Some(Location::Value(range)) => (AreaEnum::Value, range.start),
+            None => return,
+        };
+        let input = if area == AreaEnum::Sql {
+            &mut self.sql_input
+        } else {
+            &mut self.value_input
+        };
+        let text = input.lines().join("\n");
+        let (row, column) = position(&text, offset);
+        input.cancel_selection();
+        input.move_cursor(CursorMove::Jump(row, column));
+        // the table selects the parameter instead
+        if let Some(table) = self
+            .param_table
+            .as_mut()
+            .filter(|_| area == AreaEnum::Value)
+        {
+            let params = params(&text);
+            table.selected = values_before(&text, offset).min(params.len().saturating_sub(1));
+        }
+        self.focus(area);
+    }
+
+    /// Opens the history view with the entries read again, or closes it.
+    pub fn toggle_history(&mut self) {
+        if self.history_view.take().is_some() {
+            return;
+        }
+        let mut entries = self.history.entries().unwrap_or_default();
+        entries.reverse();
+        self.history_view = Some(HistoryView {
+            entries,
+            query: String::new(),
+            selected: 0,
+            list_state: ListState::default(),
+        });
+    }
+
+    /// Closes the history view and puts the selected entry into the inputs.
+    pub fn open_history_entry(&mut self) {
+        let Some(view) = self.history_view.take() else {
+            return;
+        };
+        if !view.visible().contains(&view.selected) {
+            return;
+        }
+        let entry = &view.entries[view.selected];
+        // the inputs no longer show a statement of the list
+        self.store_edits();
+        self.statements.clear();
+        self.filter.clear();
+        self.sql_input = TextArea::from(entry.sql.lines());
+        self.value_input = TextArea::from(entry.value.lines());
+        self.sql_scroll = (0, 0);
+        self.value_scroll = (0, 0);
+        self.recalculate();
+        self.current_area = AreaEnum::Result;
+        if self.worker.is_binding() {
+            self.copy_when_bound = true;
+        } else {
+            self.copy_text(self.result.clone());
+        }
+    }
+
+    /// Whether copying writes to a clipboard.
+    pub fn can_copy(&self) -> bool {
+        self.config.clipboard.write && self.clipboard.backend() != ClipboardBackend::None
     }
 
+    /// Copies `text`, a failure is shown in the footer.
+    fn copy_text(&mut self, text: String) {
+        if !self.can_copy() || text.is_empty() {
+            return;
+        }
+        match self.clipboard.set_text(&text) {
+            Ok(()) => self.copied = text,
+            Err(e) => self.message = Some(format!("cannot copy: {}", e)),
+        }
+        if let Some(fallback) = self.clipboard.take_fallback() {
+            self.message = Some(fallback);
+        }
+    }
+
+    /// Asks the worker for the clipboard while the inputs are empty, at most once per watch
+    /// interval.
     pub fn parse_clipboard(&mut self) {
+        if !self.config.clipboard.read || self.worker.is_parsing() {
+            return;
+        }
         if !self.sql_input.is_empty() || !self.value_input.is_empty() {
             return;
         }
+        let now = Instant::now();
+        if self
+            .read_at
+            .is_some_and(|at| now < at + self.watch_interval())
+        {
+            return;
+        }
+        self.read_at = Some(now);
+        self.worker
+            .read_clipboard(self.config.clipboard.clone(), false);
+    }
 
-        if let Ok(mut clipboard) = Clipboard::new() {
-            if let Ok(content) = clipboard.get_text() {
-                if content == self.result.as_str() {
-                    return;
-                }
-                if let Some(log_parser) = LogParser::parse_lines(content.lines().collect()) {
-                    self.sql_input = TextArea::new(log_parser.sql);
-                    self.value_input = TextArea::new(log_parser.value);
-                    self.current_area = AreaEnum::Result;
-                    self.calculate_result();
-                }
+    /// Asks the worker for the clipboard, a log copied since the last check is loaded as a new
+    /// statement set.
+    pub fn watch_clipboard(&mut self) {
+        if !self.config.clipboard.read || self.worker.is_parsing() {
+            return;
+        }
+        self.worker
+            .read_clipboard(self.config.clipboard.clone(), true);
+    }
+
+    /// Loads the log read from the clipboard, a watched one replaces the statements shown and
+    /// keeps them in the history.
+    pub fn clipboard_read(&mut self, content: String, watched: bool) {
+        if self.worker.is_parsing() {
+            return;
+        }
+        if !watched && (!self.sql_input.is_empty() || !self.value_input.is_empty()) {
+            return;
+        }
+        self.load_log(content, watched);
+    }
+
+    /// Reads the statements of the log `content` unless it was read already, large logs on the
+    /// worker.
+    fn load_log(&mut self, content: String, watched: bool) {
+        if content == self.copied {
+            return;
+        }
+        let formats = self.config.log_formats();
+        if content.len() > BACKGROUND_LIMIT {
+            // a log is not read again while it is parsed
+            self.copied = content.clone();
+            self.worker
+                .parse(content, formats, self.bind_options.clone(), watched);
+            return;
+        }
+        let parsed = parse_log(
+            &content,
+            formats,
+            &self.bind_options,
+            watched,
+            &Progress::default(),
+        );
+        // a log without statements is not read again on every frame
+        self.copied = content;
+        if let Some(parsed) = parsed {
+            self.show_log(parsed);
+        }
+    }
+
+    /// Shows a log parsed on the worker.
+    pub fn parsed(&mut self, id: u64, parsed: Parsed) {
+        if self.worker.finish(id) {
+            self.show_log(parsed);
+        }
+    }
+
+    /// Lists the statements of a log, a statement typed before a watched log is kept in the
+    /// history.
+    fn show_log(&mut self, parsed: Parsed) {
+        let Parsed {
+            prepared,
+            statements,
+            watched,
+        } = parsed;
+        if prepared == 0 {
+            return;
+        }
+        // what was typed while the log was parsed is not replaced
+        if !watched && (!self.sql_input.is_empty() || !self.value_input.is_empty()) {
+            return;
+        }
+        self.log_diagnostics.clear();
+        if prepared > statements.len() {
+            let message = format!(
+                "{} of {} statements of the clipboard have no parameters line",
+                prepared - statements.len(),
+                prepared
+            );
+            self.message = Some(message.clone());
+            self.log_diagnostics.push(Diagnostic::new(message, None));
+        }
+        if statements.is_empty() {
+            return;
+        }
+        // a loaded log is in the history since `copy_all`, a typed statement is not
+        if watched && self.statements.is_empty() && !self.result.is_empty() {
+            let entry = HistoryEntry::new(
+                "tui",
+                "manual",
+                &self.get_sql_text(),
+                &self.get_value_text(),
+                &self.result,
+            );
+            self.record(vec![entry]);
+        }
+        self.statements = statements;
+        self.filter.clear();
+        self.selected = usize::MAX;
+        self.load(0);
+        self.current_area = if self.has_list() {
+            AreaEnum::List
+        } else {
+            AreaEnum::Result
+        };
+        // every statement is copied, the list picks single ones
+        self.copy_all();
+        if watched {
+            self.message = Some(match self.statements.len() {
+                1 => String::from("loaded 1 statement from the clipboard"),
+                count => format!("loaded {} statements from the clipboard", count),
+            });
+        }
+    }
+
+    /// Indices of the statements matching the filter, none while the filter is invalid.
+    pub fn visible_statements(&self) -> Vec<usize> {
+        let Ok(filter) = self.filter.parse::<Filter>() else {
+            return Vec::new();
+        };
+        (0..self.statements.len())
+            .filter(|&index| filter.matches(&Fields::of_item(&self.statements[index])))
+            .collect()
+    }
+
+    /// Why the filter cannot be used.
+    pub fn filter_error(&self) -> Option<String> {
+        self.filter.parse::<Filter>().err()
+    }
+
+    /// Shows another statement in the inputs, keeping the edits of the current one.
+    pub fn select(&mut self, index: usize) {
+        if index != self.selected {
+            self.store_edits();
+            self.load(index);
+        }
+    }
+
+    /// Moves the selection by `delta` listed statements.
+    pub fn select_by(&mut self, delta: isize) {
+        let visible = self.visible_statements();
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let position = visible.iter().position(|&index| index == self.selected);
+        let position = match position {
+            Some(position) => position.saturating_add_signed(delta).min(last),
+            None => 0,
+        };
+        self.select(visible[position]);
+    }
+
+    pub fn filter_char(&mut self, char: char) {
+        self.filter.push(char);
+        self.filter_changed();
+    }
+
+    pub fn filter_backspace(&mut self) {
+        self.filter.pop();
+        self.filter_changed();
+    }
+
+    /// Selects the first listed statement when the selected one is filtered out.
+    fn filter_changed(&mut self) {
+        let visible = self.visible_statements();
+        if !visible.contains(&self.selected) {
+            if let Some(&first) = visible.first() {
+                self.select(first);
+            }
+        }
+    }
+
+    fn store_edits(&mut self) {
+        let (sql, value) = (self.get_sql_text(), self.get_value_text());
+        if let Some(item) = self.statements.get_mut(self.selected) {
+            if item.statement.sql != sql || item.statement.value != value {
+                item.statement.sql = sql;
+                item.statement.value = value;
+                *item = StatementItem::new(item.statement.clone(), &self.bind_options);
             }
+        }
+    }
+
+    fn load(&mut self, index: usize) {
+        let Some(item) = self.statements.get(index) else {
+            return;
         };
+        self.selected = index;
+        self.sql_input = TextArea::from(item.statement.sql.lines());
+        self.value_input = TextArea::from(item.statement.value.lines());
+        self.sql_scroll = (0, 0);
+        self.value_scroll = (0, 0);
+        self.recalculate();
     }
 }
 
+/// Row and column, in characters, of the byte `offset` of `text`.
+fn position(text: &str, offset: usize) -> (u16, u16) {
+    let before = &text[..offset.min(text.len())];
+    let row = before.matches('\n').count();
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count();
+    (
+        u16::try_from(row).unwrap_or(u16::MAX),
+        u16::try_from(column).unwrap_or(u16::MAX),
+    )
+}
+
+/// How many values of the parameter list `text` end before the byte `offset`.
+fn values_before(text: &str, offset: usize) -> usize {
+    crate::core::values(text)
+        .iter()
+        .filter(|value| value.span.end < offset)
+        .count()
+}
+
+/// Vim starts in normal mode, the other profiles always insert.
+fn input_mode(profile: Profile) -> Mode {
+    match profile {
+        Profile::Vim => Mode::Normal,
+        Profile::Default | Profile::Emacs => Mode::Insert,
+    }
+}
+
+/// Moves the cursor of `input`, the select actions and a set `mark` extend the selection and
+/// the other moves end it. Returns whether `action` is a move.
+fn move_cursor(input: &mut TextArea, action: Action, page: u16, mark: bool) -> bool {
+    use Action::*;
+    let (movement, count) = match action {
+        Left | SelectLeft => (CursorMove::Back, 1),
+        Right | SelectRight => (CursorMove::Forward, 1),
+        Up | SelectUp => (CursorMove::Up, 1),
+        Down | SelectDown => (CursorMove::Down, 1),
+        WordLeft | SelectWordLeft => (CursorMove::WordBack, 1),
+        WordRight | SelectWordRight => (CursorMove::WordForward, 1),
+        WordEnd => (CursorMove::WordEnd, 1),
+        LineStart | SelectLineStart => (CursorMove::Head, 1),
+        LineEnd | SelectLineEnd => (CursorMove::End, 1),
+        PageUp | SelectPageUp => (CursorMove::Up, page),
+        PageDown | SelectPageDown => (CursorMove::Down, page),
+        Top | SelectTop => (CursorMove::Jump(0, 0), 1),
+        Bottom | SelectBottom => (CursorMove::Jump(u16::MAX, u16::MAX), 1),
+        _ => return false,
+    };
+    let select = matches!(
+        action,
+        SelectLeft
+            | SelectRight
+            | SelectUp
+            | SelectDown
+            | SelectWordLeft
+            | SelectWordRight
+            | SelectLineStart
+            | SelectLineEnd
+            | SelectPageUp
+            | SelectPageDown
+            | SelectTop
+            | SelectBottom
+    );
+    if !select && !mark {
+        input.cancel_selection();
+    } else if !input.is_selecting() {
+        input.start_selection();
+    }
+    for _ in 0..count {
+        input.move_cursor(movement);
+    }
+    true
+}
+
+/// Byte offset of the cursor in the lines of `textarea` joined with `\n`.
+fn cursor_offset(textarea: &TextArea) -> usize {
+    offset(textarea, textarea.cursor())
+}
+
+/// Byte offset of a (row, column) position in the lines of `textarea` joined with `\n`.
+fn offset(textarea: &TextArea, (row, column): (usize, usize)) -> usize {
+    let lines = textarea.lines();
+    let before: usize = lines[..row].iter().map(|line| line.len() + 1).sum();
+    let line = &lines[row];
+    before
+        + line
+            .char_indices()
+            .nth(column)
+            .map_or(line.len(), |(offset, _)| offset)
+}
+
 impl AreaEnum {
     pub fn title(&self) -> &str {
         match self {
+            AreaEnum::List => "Statements",
             AreaEnum::Sql => "Sql with placeholders",
             AreaEnum::Value => "Value",
             AreaEnum::Result => "Result",
+            AreaEnum::Diagnostics => "Diagnostics",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::app::{App, AreaEnum, StatementItem};
+    use crate::clipboard::ClipboardBackend;
+    use crate::config::Config;
+    use crate::core::Dialect;
+    use crate::export::ExportDialog;
+    use crate::history::History;
+    use crate::keymap::{Action, Command};
+    use crate::log_parser::parse_statements;
+    use crate::test_dir::TestDir;
+    use crate::worker::Message;
+    use std::time::{Duration, Instant};
+    use tui_textarea::{CursorMove, TextArea};
+
+    fn app(sql: &str, value: &str) -> App {
+        let mut config = Config::default();
+        config.clipboard.write = false;
+        config.history.enabled = false;
+        config.layout.remember = false;
+        let mut app = App::new(config);
+        app.sql_input = TextArea::from(sql.lines());
+        app.value_input = TextArea::from(value.lines());
+        app.input_changed();
+        app
+    }
+
+    /// Hands the outcome of the next background task to the app.
+    fn wait(app: &mut App) {
+        match app.worker.recv(Some(Duration::from_secs(10))) {
+            Some(Message::Parsed(id, parsed)) => app.parsed(id, parsed),
+            Some(Message::Bound(id, bound)) => app.bound(id, bound),
+            Some(Message::Clipboard(content, watched)) => app.clipboard_read(content, watched),
+            _ => panic!("a background task is done"),
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let mut app = app(
+            "SELECT * FROM user\nWHERE name = :name OR alias = :name AND age = :age",
+            "zhangsan(String),\n18(Integer)",
+        );
+        app.sql_input.move_cursor(CursorMove::Jump(1, 14));
+        let link = app.link().unwrap();
+        assert_eq!(vec![32..37, 49..54], link.placeholders);
+        assert_eq!("Value 1/2 (String): zhangsan", link.status());
+
+        app.current_area = AreaEnum::Value;
+        app.value_input.move_cursor(CursorMove::Jump(1, 3));
+        let link = app.link().unwrap();
+        assert_eq!(vec![65..69], link.placeholders);
+        assert_eq!(18..29, link.value.unwrap().span);
+    }
+
+    #[test]
+    fn test_live_result() {
+        let mut app = app("SELECT * FROM user WHERE id = ?", "1(Long)");
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
+        // the panes are styled again only once the text changed
+        let generation = app.generation;
+        app.focus(AreaEnum::Value);
+        assert_eq!(generation, app.generation);
+        app.input_char('2');
+        assert_eq!("SELECT * FROM user WHERE id = 21", app.result);
+        assert!(app.generation > generation);
+
+        // large inputs wait for typing to pause
+        app.value_input = TextArea::from(["1(Long), ".repeat(3000)]);
+        app.input_changed();
+        assert!(app.recalculate_at.is_some() && app.link().is_none());
+        app.recalculate_at = Some(Instant::now());
+        app.tick();
+        assert!(app.worker.is_binding());
+        wait(&mut app);
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
+        assert!(app.link().is_some() && app.worker.task().is_none());
+
+        // a binding of inputs changed since is dropped
+        app.recalculate_at = Some(Instant::now());
+        app.tick();
+        app.value_input = TextArea::from(["2(Long), ".repeat(3000)]);
+        wait(&mut app);
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
+        assert!(app.binding.is_none());
+    }
+
+    #[test]
+    fn test_background_parse() {
+        let log = "==>  Preparing: SELECT * FROM user WHERE id = ?\n==> Parameters: 1(Long)\n";
+        let mut parsing = app("", "");
+        parsing.load_log(log.repeat(2000), false);
+        assert!(parsing.worker.is_parsing() && parsing.statements.is_empty());
+        wait(&mut parsing);
+        assert_eq!(2000, parsing.statements.len());
+        assert_eq!(
+            "SELECT * FROM user WHERE id = 1",
+            parsing.statements[1999].bound
+        );
+        assert!(parsing.current_area == AreaEnum::List);
+
+        // a cancelled log is not shown, nor read again
+        let mut cancelled = app("", "");
+        cancelled.load_log(log.repeat(3000), true);
+        assert!(cancelled.cancel_parse());
+        assert_eq!(
+            Some("stopped reading the clipboard"),
+            cancelled.message.as_deref()
+        );
+        assert!(cancelled.worker.task().is_none());
+        cancelled.load_log(log.repeat(3000), true);
+        assert!(!cancelled.worker.is_parsing());
+        assert!(cancelled.statements.is_empty());
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut app = app("SELECT * FROM user WHERE id = ?", "1(Long)");
+        app.edit(Action::Bottom);
+        app.edit(Action::SelectWordLeft);
+        assert_eq!(Some(30..31), app.selection(AreaEnum::Sql));
+        app.edit(Action::Cut);
+        assert_eq!(None, app.selection(AreaEnum::Sql));
+        assert_eq!("SELECT * FROM user WHERE id = ", app.result);
+        app.edit(Action::Undo);
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
+        app.edit(Action::Redo);
+        app.edit(Action::Undo);
+
+        // typing replaces the selection
+        app.edit(Action::LineStart);
+        app.edit(Action::SelectWordRight);
+        "DELETE ".chars().for_each(|char| app.input_char(char));
+        assert_eq!("DELETE * FROM user WHERE id = ?", app.get_sql_text());
+        app.edit(Action::SelectAll);
+        app.edit(Action::Backspace);
+        assert!(app.get_sql_text().is_empty() && app.result.is_empty());
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut app = app("select * from t where b = ?", "true(Boolean)");
+        app.run_command("dialect oracle");
+        assert_eq!("select * from t where b = 1", app.result);
+        app.run_command("dialect db2");
+        assert_eq!(Some("unknown dialect 'db2'"), app.message.as_deref());
+
+        app.run_command("format");
+        assert!(app.get_sql_text().starts_with("SELECT *\nFROM t"));
+        // the selected text is deleted, then the formatted one inserted
+        app.edit(Action::Undo);
+        app.edit(Action::Undo);
+        assert_eq!("select * from t where b = ?", app.get_sql_text());
+
+        let dir = TestDir::new("write");
+        let path = dir.join("result.sql");
+        app.run_command(&format!("w {}", path.display()));
+        assert_eq!(
+            "select * from t where b = 1\n",
+            std::fs::read_to_string(&path).unwrap()
+        );
+        app.run_command("w");
+        assert_eq!(Some("usage: w <file>"), app.message.as_deref());
+    }
+
+    #[test]
+    fn test_external_editor() {
+        let mut app = app("select * from t where b = ?", "true(Boolean)");
+        app.run_command("edit");
+        assert_eq!(Some(AreaEnum::Sql), app.external_edit.take());
+        app.edited(
+            AreaEnum::Sql,
+            Ok(String::from("select b\r\nfrom t where b = ?")),
+        );
+        assert_eq!("select b\nfrom t where b = true", app.result);
+        app.edit(Action::Undo);
+        app.edit(Action::Undo);
+        assert_eq!("select * from t where b = ?", app.get_sql_text());
+
+        // the result is kept until the inputs change
+        app.focus(AreaEnum::Result);
+        app.execute(Command::Editor);
+        assert_eq!(Some(AreaEnum::Result), app.external_edit.take());
+        app.edited(AreaEnum::Result, Ok(String::from("select 1 from t")));
+        assert_eq!("select 1 from t", app.result);
+        app.edited(AreaEnum::Result, Err(String::from("vi exited with 1")));
+        assert_eq!(Some("vi exited with 1"), app.message.as_deref());
+        app.current_area = AreaEnum::Value;
+        app.input_char(' ');
+        assert_eq!("select * from t where b = true", app.result);
+    }
+
+    #[test]
+    fn test_export() {
+        let log = "
+            2025-06-28 20:18:06.100 DEBUG 6 --- [task-1] c.a.Executor : ==>  Preparing: SELECT * FROM user WHERE id = ?
+            2025-06-28 20:18:06.100 DEBUG 6 --- [task-1] c.a.Executor : ==> Parameters: 1(Long)
+            2025-06-28 20:18:06.102 DEBUG 6 --- [task-1] c.a.Executor : ==>  Preparing: UPDATE orders SET state = ? WHERE id = ?
+            2025-06-28 20:18:06.102 DEBUG 6 --- [task-1] c.a.Executor : ==> Parameters: paid(String), 7(Long)
+        ";
+        let dir = TestDir::new("app-export");
+        let path = |extension: &str| {
+            dir.join(&format!("result.{}", extension))
+                .display()
+                .to_string()
+        };
+        let mut app = app("SELECT ?", "1(Long)");
+        app.execute(Command::Export);
+        app.export.as_mut().unwrap().path = path("sql");
+        app.run_export();
+        assert!(app.export.is_none());
+        assert_eq!("SELECT 1\n", std::fs::read_to_string(path("sql")).unwrap());
+
+        app.statements = parse_statements(log.lines(), app.config.log_formats())
+            .into_iter()
+            .map(|statement| StatementItem::new(statement, &app.bind_options))
+            .collect();
+        app.load(0);
+        app.filter = String::from("-type:update");
+        let mut dialog = ExportDialog::default();
+        dialog.select_by(1);
+        dialog.path = path("sql");
+        app.export = Some(dialog);
+        app.run_export();
+        assert_eq!(
+            "SELECT * FROM user WHERE id = 1\n",
+            std::fs::read_to_string(path("sql")).unwrap()
+        );
+        app.filter.clear();
+        app.run_command("export");
+        app.export.as_mut().unwrap().select_by(3);
+        app.export.as_mut().unwrap().path = path("csv");
+        app.run_export();
+        assert_eq!(
+            Some(format!("written 2 statements to {}", path("csv"))),
+            app.message
+        );
+        let csv = std::fs::read_to_string(path("csv")).unwrap();
+        assert_eq!(3, csv.lines().count());
+        assert!(csv.contains("UPDATE orders SET state = 'paid' WHERE id = 7"));
+
+        // a failed export keeps the dialog open
+        app.execute(Command::Export);
+        app.export.as_mut().unwrap().path = String::from("/no/such/dir/result.sql");
+        app.run_export();
+        assert!(app.export.is_some());
+        assert!(app.message.as_deref().unwrap().starts_with("cannot write"));
+    }
+
+    #[test]
+    fn test_session() {
+        let log = "==>  Preparing: SELECT * FROM user WHERE id = ?\n==> Parameters: 1(Long)\n";
+        let mut saved = app("", "");
+        saved.statements = parse_statements(log.lines(), saved.config.log_formats())
+            .into_iter()
+            .map(|statement| StatementItem::new(statement, &saved.bind_options))
+            .collect();
+        saved.load(0);
+        saved.run_command("dialect mysql");
+        saved.edited(AreaEnum::Result, Ok(String::from("SELECT 1")));
+        let dir = TestDir::new("app-session");
+        let path = dir.join("saved.json");
+        saved.run_command(&format!("session save {}", path.display()));
+
+        let mut opened = app("SELECT 2", "");
+        opened.run_command(&format!("session open {}", path.display()));
+        assert_eq!(saved.session(), opened.session());
+        assert_eq!("SELECT 1", opened.result);
+        assert_eq!(Dialect::Mysql, opened.bind_options.dialect);
+        assert!(opened.current_area == AreaEnum::Result);
+        opened.run_command("session");
+        assert_eq!(
+            Some("usage: session save|open <file>"),
+            opened.message.as_deref()
+        );
+    }
+
+    #[test]
+    fn test_statement_list() {
+        let log = "
+            2025-06-28 20:18:06.100 DEBUG 6 --- [task-1] c.a.Executor : ==>  Preparing: SELECT * FROM user WHERE id = ?
+            2025-06-28 20:18:06.100 DEBUG 6 --- [task-1] c.a.Executor : ==> Parameters: 1(Long)
+            2025-06-28 20:18:06.101 DEBUG 6 --- [task-1] c.a.Executor : <==      Total: 1
+            2025-06-28 20:18:06.102 DEBUG 6 --- [task-1] c.a.Executor : ==>  Preparing: UPDATE orders SET state = ? WHERE id = ?
+            2025-06-28 20:18:06.102 DEBUG 6 --- [task-1] c.a.Executor : ==> Parameters: paid(String), 7(Long)
+            2025-06-28 20:18:06.103 DEBUG 6 --- [task-1] c.a.Executor : <==    Updates: 1
+        ";
+        let mut app = app("", "");
+        app.statements = parse_statements(log.lines(), app.config.log_formats())
+            .into_iter()
+            .map(|statement| StatementItem::new(statement, &app.bind_options))
+            .collect();
+        app.load(0);
+        assert!(app.has_list());
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
+
+        app.select_by(1);
+        assert_eq!("UPDATE orders SET state = 'paid' WHERE id = 7", app.result);
+        assert_eq!(Some("orders"), app.statements[1].table.as_deref());
+        assert_eq!(Some(1), app.statements[1].statement.rows);
+
+        // edits are kept when moving through the list
+        app.current_area = AreaEnum::Value;
+        app.input_char('u');
+        app.input_char('n');
+        app.select_by(-1);
+        app.select_by(1);
+        assert_eq!(
+            "UPDATE orders SET state = 'unpaid' WHERE id = 7",
+            app.result
+        );
+
+        app.filter_char('u');
+        app.filter_char('s');
+        app.filter_char('e');
+        assert_eq!(vec![0], app.visible_statements());
+        assert_eq!(0, app.selected);
+
+        app.filter = String::from("table:orders -type:select");
+        assert_eq!(vec![1], app.visible_statements());
+        app.filter = String::from("tabel:orders");
+        assert!(app.visible_statements().is_empty());
+        assert!(app.filter_error().is_some());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let log = "
+            ==>  Preparing: SELECT * FROM user WHERE id = ?
+            ==> Parameters: 1(Long)
+            ==>  Preparing: UPDATE orders SET state = ?, data = ?
+            WHERE id = ?
+            ==> Parameters: paid(String), [B@1a2b3c(byte[]), 7(Long)
+        ";
+        let mut app = app("", "");
+        app.statements = parse_statements(log.lines(), app.config.log_formats())
+            .into_iter()
+            .map(|statement| StatementItem::new(statement, &app.bind_options))
+            .collect();
+        app.load(0);
+        app.toggle_diagnostics();
+        let items = app.diagnostic_items();
+        assert_eq!(2, items.len());
+        assert_eq!(Some(1), items[0].statement);
+        assert_eq!("2 placeholders but 3 values", items[0].diagnostic.message);
+
+        // jumping selects the statement and puts the cursor on the value
+        app.select_diagnostic_by(1);
+        app.jump_to_diagnostic();
+        assert_eq!(1, app.selected);
+        assert!(app.current_area == AreaEnum::Value);
+        assert_eq!((0, 14), app.value_input.cursor());
+
+        // the statement in the inputs is checked as it is edited
+        app.value_input = TextArea::from(["paid(String), 7(Long)"]);
+        app.input_changed();
+        assert!(app.diagnostic_items().is_empty());
+    }
+
+    #[test]
+    fn test_watch_clipboard() {
+        let dir = TestDir::new("watch");
+        let path = dir.join("clipboard.txt");
+        let mut app = app("SELECT 1", "");
+        app.config.clipboard.backend = ClipboardBackend::File;
+        app.config.clipboard.path = Some(path.clone());
+        app.toggle_watch();
+
+        std::fs::write(
+            &path,
+            "==>  Preparing: SELECT * FROM user WHERE id = ?\n==> Parameters: 1(Long)\n",
+        )
+        .unwrap();
+        app.tick();
+        wait(&mut app);
+        assert_eq!(
+            Some("loaded 1 statement from the clipboard"),
+            app.message.as_deref()
+        );
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
+        // the same log is not loaded again
+        app.watch_at = Some(Instant::now());
+        app.message = None;
+        app.tick();
+        wait(&mut app);
+        assert_eq!(None, app.message);
+
+        std::fs::write(
+            &path,
+            "==>  Preparing: DELETE FROM user WHERE id = ?\n==> Parameters: 2(Long)\n",
+        )
+        .unwrap();
+        app.watch_at = Some(Instant::now());
+        app.tick();
+        wait(&mut app);
+        assert_eq!("DELETE FROM user WHERE id = 2", app.result);
+        assert!(app.next_tick().is_some());
+        app.toggle_watch();
+        assert!(app.next_tick().is_none());
+    }
+
+    #[test]
+    fn test_history_view() {
+        let dir = TestDir::new("app-history");
+        let mut app = app("SELECT * FROM user WHERE id = ?", "1(Long)");
+        app.config.history.enabled = true;
+        app.config.history.path = Some(dir.join("history.jsonl"));
+        app.history = History::new(&app.config.history);
+
+        app.focus(AreaEnum::Result);
+        // moving through the panes again copies nothing new
+        app.focus(AreaEnum::Sql);
+        app.focus(AreaEnum::Value);
+        app.focus(AreaEnum::Result);
+        app.sql_input = TextArea::from(["DELETE FROM orders WHERE id = ?"]);
+        app.input_changed();
+        app.copy_result();
+
+        app.toggle_history();
+        let view = app.history_view.as_mut().unwrap();
+        assert_eq!(2, view.entries.len());
+        assert_eq!("DELETE FROM orders WHERE id = 1", view.entries[0].bound);
+        assert_eq!("manual", view.entries[0].format);
+        for char in "user".chars() {
+            view.query_char(char);
         }
+        assert_eq!(vec![1], view.visible());
+        assert_eq!(1, view.selected);
+
+        app.open_history_entry();
+        assert!(app.history_view.is_none());
+        assert_eq!("SELECT * FROM user WHERE id = ?", app.get_sql_text());
+        assert_eq!("SELECT * FROM user WHERE id = 1", app.result);
     }
 }
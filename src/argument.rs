@@ -1,39 +1,501 @@
-use clap::Parser;
+use crate::clipboard::ClipboardBackend;
+use crate::config::Config;
+use crate::core::Dialect;
+use crate::filter::Filter;
+use crate::formatter::format_sql;
+use crate::history::{History, HistoryEntry};
+use crate::layout::LayoutPreset;
+use crate::log_parser::{Statement, StatementParser};
+use crate::output::{OutputFormat, Record, RecordWriter};
+use crate::stats::Stats;
+use crate::theme::ThemeName;
+use crate::{diff, follow, input};
+use clap::{Args, Parser, Subcommand};
+use regex::Regex;
 use std::error::Error;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(
     name = "sql-param",
     about = "A tool for automatically replacing sql placeholders",
     version,
-    long_about = "SQL-Param is a TUI tool that helps you replace SQL placeholders with actual values. 
-    It can automatically parse clipboard content for SQL statements and parameters from server logs, 
-    then generate the final SQL with replaced placeholders."
+    long_about = "SQL-Param is a TUI tool that helps you replace SQL placeholders with actual values.
+    It can automatically parse clipboard content for SQL statements and parameters from server logs,
+    then generate the final SQL with replaced placeholders.
+    Run without a command to open the TUI."
 )]
 pub struct Argument {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    global: GlobalArgs,
+
+    // without a command the log files are parsed like `parse` does, as before the commands
+    #[command(flatten)]
+    parse: ParseArgs,
+
+    /// Open the TUI as saved with `:session save` or the export dialog
+    #[arg(long, value_name = "FILE")]
+    session: Option<PathBuf>,
+
+    /// Same as `bind --sql`, kept for compatibility
+    #[arg(short, long, hide = true)]
+    sql: Option<String>,
+
+    /// Same as `bind --value`, kept for compatibility
+    #[arg(short, long, hide = true)]
+    value: Option<String>,
+}
+
+#[derive(Args)]
+struct GlobalArgs {
+    /// Database dialect the values are written for [default: the configured one, or generic]
+    #[arg(short, long, global = true, value_enum)]
+    dialect: Option<Dialect>,
+
+    /// Log file to read, use '-' for stdin. Can be repeated, gzip-compressed files are supported
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_name = "FILE",
+        visible_alias = "file",
+        visible_short_alias = 'f'
+    )]
+    input: Vec<String>,
+
+    /// How to print the results
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Sql)]
+    output: OutputFormat,
+
+    /// Config file to use instead of $XDG_CONFIG_HOME/sql-param/config.toml
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Clipboard of the TUI [default: the configured one, or auto]
+    #[arg(long, global = true, value_enum, value_name = "BACKEND")]
+    clipboard: Option<ClipboardBackend>,
+
+    /// Load the logs copied while the TUI is open
+    #[arg(long, global = true)]
+    watch: bool,
+
+    /// Layout of the TUI [default: the last one, or the configured one]
+    #[arg(long, global = true, value_enum, value_name = "PRESET")]
+    layout: Option<LayoutPreset>,
+
+    /// Colours of the TUI, NO_COLOR turns them off [default: the configured one, or dark]
+    #[arg(long, global = true, value_enum, value_name = "NAME")]
+    theme: Option<ThemeName>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Replace the placeholders of a SQL statement with parameter values
+    Bind(BindArgs),
+    /// Print every statement of logs with its placeholders replaced
+    Parse(ParseArgs),
+    /// Pretty-print SQL given as argument, or the statements of the input logs
+    Format {
+        /// SQL to format, the input is read when omitted
+        sql: Option<String>,
+    },
+    /// Summarise the statements of logs by type, table, thread and template
+    Stats(StatsArgs),
+    /// Compare how often each statement was executed in two logs
+    Diff {
+        /// Log file before the change
+        old: String,
+        /// Log file after the change
+        new: String,
+    },
+    /// List, search or prune the history of converted statements
+    History(HistoryArgs),
+}
+
+#[derive(Args)]
+struct BindArgs {
     /// SQL statement with placeholders. Example: 'UPDATE user SET name = ?, age = ?, update_time = ?, id_card = ? WHERE id = ? AND deleted = ?;'
     #[arg(short, long)]
-    sql: Option<String>,
+    sql: String,
 
     /// Parameter values (comma-separated). Example: 'zhangsan(String), 18(Integer), 2025-06-13 16:44:56.499(Timestamp), 123456789(Long), 1(Integer), 0(Integer)'
     #[arg(short, long)]
-    value: Option<String>,
+    value: String,
+
+    /// Print nothing unless the statement matches this filter, e.g. 'table:user type:update'
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true)]
+    filter: Option<Filter>,
+}
+
+#[derive(Args)]
+struct ParseArgs {
+    /// Log files to parse, same as --input
+    #[arg(value_name = "FILE")]
+    files: Vec<String>,
+
+    /// Also parse the rotated files of every log file (app.log.2.gz, app.log.1, ...), oldest first
+    #[arg(short, long, conflicts_with = "follow")]
+    rotated: bool,
+
+    /// Keep reading the log files (or stdin) and print each statement as soon as its parameters are logged
+    #[arg(short = 'F', long)]
+    follow: bool,
+
+    /// With --follow, also print the statements already in the log files instead of only new ones
+    #[arg(long, requires = "follow")]
+    from_start: bool,
+
+    /// Only print statements whose replaced SQL matches this regular expression
+    #[arg(short, long, value_name = "REGEX")]
+    grep: Option<Regex>,
+
+    /// Only print statements logged by a pod or container whose name contains this text
+    #[arg(long, value_name = "NAME")]
+    source: Option<String>,
+
+    /// Only print statements matching this filter, e.g. 'table:order_item -type:select thread:exec-1'
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true)]
+    filter: Option<Filter>,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// Log files to summarise, same as --input
+    #[arg(value_name = "FILE")]
+    files: Vec<String>,
+
+    /// Also read the rotated files of every log file, oldest first
+    #[arg(short, long)]
+    rotated: bool,
 }
 
-pub fn parse_arguments() -> Option<Result<(), Box<dyn Error>>> {
-    let cli = Argument::parse();
+#[derive(Args)]
+struct HistoryArgs {
+    /// Only list entries containing every word, e.g. a table name or a value
+    query: Vec<String>,
+
+    /// Number of entries listed, newest first
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+
+    /// Remove the entries older than this many days
+    #[arg(long, value_name = "DAYS")]
+    older_than: Option<u64>,
+
+    /// Remove all but the newest entries
+    #[arg(long, value_name = "N")]
+    keep: Option<usize>,
+
+    /// Remove every entry
+    #[arg(long, conflicts_with_all = ["older_than", "keep"])]
+    clear: bool,
+}
+
+pub fn parse_arguments() -> Argument {
+    Argument::parse()
+}
+
+impl Argument {
+    /// The file given with --config or `SQL_PARAM_CONFIG`.
+    pub fn config_path(&self) -> Option<&Path> {
+        self.global.config.as_deref()
+    }
 
-    if let (Some(sql), Some(value)) = (&cli.sql, &cli.value) {
-        let result = crate::core::replace_placeholder(sql, value);
+    /// The clipboard backend given with --clipboard.
+    pub fn clipboard(&self) -> Option<ClipboardBackend> {
+        self.global.clipboard
+    }
+
+    /// Whether --watch was given.
+    pub fn watch(&self) -> bool {
+        self.global.watch
+    }
+
+    /// The layout preset given with --layout.
+    pub fn layout(&self) -> Option<LayoutPreset> {
+        self.global.layout
+    }
+
+    /// The theme given with --theme.
+    pub fn theme(&self) -> Option<ThemeName> {
+        self.global.theme
+    }
+
+    /// The session file given with --session.
+    pub fn session(&self) -> Option<&Path> {
+        self.session.as_deref()
+    }
+
+    /// Runs the command, `None` when there is nothing to run and the TUI should open.
+    pub fn run(&self, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        let global = &self.global;
+
+        let result = match &self.command {
+            Some(Command::Bind(args)) => {
+                bind(global, config, &args.sql, &args.value, args.filter.as_ref())
+            }
+            Some(Command::Parse(args)) => parse(global, config, args),
+            Some(Command::Format { sql }) => format(global, config, sql.as_deref()),
+            Some(Command::Stats(args)) => stats(global, config, args),
+            Some(Command::Diff { old, new }) => diff(global, config, old, new),
+            Some(Command::History(args)) => history(global, config, args),
+            None => {
+                if let (Some(sql), Some(value)) = (&self.sql, &self.value) {
+                    return Some(bind(global, config, sql, value, None));
+                }
+                if self.sql.is_some() || self.value.is_some() {
+                    eprintln!("Error: Both --sql and --value must be provided together");
+                    eprintln!("Use --help for more information");
+                    std::process::exit(1);
+                }
+                if global.input.is_empty() && self.parse.files.is_empty() {
+                    return None;
+                }
+                parse(global, config, &self.parse)
+            }
+        };
+        Some(result)
+    }
+}
+
+impl ParseArgs {
+    fn matches(&self, record: &Record) -> bool {
+        if let Some(grep) = &self.grep {
+            if !grep.is_match(&record.bound) {
+                return false;
+            }
+        }
+        if let Some(name) = &self.source {
+            let in_pod = record.pod.as_ref().is_some_and(|pod| pod.contains(name));
+            let in_container = record
+                .container
+                .as_ref()
+                .is_some_and(|container| container.contains(name));
+            if !in_pod && !in_container {
+                return false;
+            }
+        }
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches_record(record))
+    }
+}
+
+fn bind(
+    global: &GlobalArgs,
+    config: &Config,
+    sql: &str,
+    value: &str,
+    filter: Option<&Filter>,
+) -> Result<(), Box<dyn Error>> {
+    let options = config.bind_options(global.dialect);
+    let result = crate::core::replace_placeholder(sql, value, &options);
+    // the history is a convenience, failing to write it does not fail the command
+    let entry = HistoryEntry::new("bind", "argument", sql, value, &result);
+    History::new(&config.history).add(vec![entry]);
+    let statement = Statement {
+        sql: sql.to_string(),
+        value: value.to_string(),
+        format: String::from("argument"),
+        source: None,
+        wrapper: None,
+        timestamp: None,
+        thread: None,
+        trace_id: None,
+        rows: None,
+    };
+    let record = Record::new(&statement, &options);
+    if filter.is_some_and(|filter| !filter.matches_record(&record)) {
+        return Ok(());
+    }
+    if global.output == OutputFormat::Sql {
         println!("{}", result);
-        return Some(Ok(()));
+        return Ok(());
     }
+    let mut writer = RecordWriter::new(io::stdout().lock(), global.output);
+    writer.write(record)?;
+    ignore_broken_pipe(writer.finish())
+}
 
-    if cli.sql.is_some() || cli.value.is_some() {
-        eprintln!("Error: Both --sql and --value must be provided together");
-        eprintln!("Use --help for more information");
-        std::process::exit(1);
+/// Prints every statement found in the input with its placeholders replaced.
+fn parse(global: &GlobalArgs, config: &Config, args: &ParseArgs) -> Result<(), Box<dyn Error>> {
+    let inputs = inputs(global, &args.files)?;
+    if args.follow && inputs.len() > 1 && inputs.iter().any(|input| input == "-") {
+        return Err("stdin ('-') cannot be followed together with files".into());
+    }
+    if args.follow && global.output == OutputFormat::Json {
+        return Err("json output cannot be followed, use ndjson instead".into());
     }
 
-    None
+    let options = config.bind_options(global.dialect);
+    let mut parser = StatementParser::new(config.log_formats());
+    let mut writer = RecordWriter::new(BufWriter::new(io::stdout().lock()), global.output);
+    let mut on_line = |line: &str| {
+        let Some(statement) = parser.push_line(line) else {
+            return Ok(());
+        };
+        let record = Record::new(&statement, &options);
+        if !args.matches(&record) {
+            return Ok(());
+        }
+        writer.write(record)?;
+        if args.follow {
+            writer.flush()?;
+        }
+        Ok(())
+    };
+    let result = if args.follow && inputs[0] != "-" {
+        let paths = inputs.iter().map(PathBuf::from).collect();
+        follow::follow(paths, args.from_start, on_line)
+    } else {
+        // stdin is streamed line by line, so following it needs nothing more
+        for_each_input_line(&inputs, args.rotated, &mut on_line)
+    };
+    ignore_broken_pipe(result.and_then(|_| writer.finish()))
+}
+
+fn format(global: &GlobalArgs, config: &Config, sql: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let text = match sql {
+        Some(sql) => sql.to_string(),
+        None if global.input.is_empty() => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        None => {
+            let mut text = String::new();
+            for_each_input_line(&global.input, false, |line| {
+                text.push_str(line);
+                text.push('\n');
+                Ok(())
+            })?;
+            text
+        }
+    };
+
+    // logs are formatted statement by statement, anything else is taken as sql
+    let options = config.bind_options(global.dialect);
+    let mut parser = StatementParser::new(config.log_formats());
+    let statements: Vec<Statement> = text
+        .lines()
+        .filter_map(|line| parser.push_line(line))
+        .collect();
+    let mut out = io::stdout().lock();
+    let result = if statements.is_empty() {
+        writeln!(out, "{}", format_sql(&text))
+    } else {
+        statements.iter().try_for_each(|statement| {
+            let record = Record::new(statement, &options);
+            writeln!(out, "{}\n", format_sql(&record.bound))
+        })
+    };
+    ignore_broken_pipe(result)
+}
+
+fn stats(global: &GlobalArgs, config: &Config, args: &StatsArgs) -> Result<(), Box<dyn Error>> {
+    let inputs = inputs(global, &args.files)?;
+    let mut stats = Stats::default();
+    for record in read_records(&inputs, args.rotated, global, config)? {
+        stats.add(&record);
+    }
+    let mut out = BufWriter::new(io::stdout().lock());
+    ignore_broken_pipe(
+        stats
+            .write(&mut out, global.output)
+            .and_then(|_| out.flush()),
+    )
+}
+
+fn diff(global: &GlobalArgs, config: &Config, old: &str, new: &str) -> Result<(), Box<dyn Error>> {
+    let old = read_records(&[old.to_string()], false, global, config)?;
+    let new = read_records(&[new.to_string()], false, global, config)?;
+    let entries = diff::diff(&old, &new);
+    let mut out = BufWriter::new(io::stdout().lock());
+    ignore_broken_pipe(diff::write(&mut out, &entries, global.output).and_then(|_| out.flush()))
+}
+
+fn history(global: &GlobalArgs, config: &Config, args: &HistoryArgs) -> Result<(), Box<dyn Error>> {
+    let mut history = History::new(&config.history);
+    if !history.is_enabled() {
+        return Err(
+            "the history is turned off, see `enabled` in the [history] config section".into(),
+        );
+    }
+    if args.clear || args.keep.is_some() || args.older_than.is_some() {
+        let keep = if args.clear { Some(0) } else { args.keep };
+        let removed = history.prune(keep, args.older_than.map(|days| days * 86_400))?;
+        println!("removed {} entries", removed);
+        return Ok(());
+    }
+    let entries = history.entries()?;
+    let query = args.query.join(" ");
+    let mut matching: Vec<&HistoryEntry> = entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.matches(&query))
+        .take(args.limit)
+        .collect();
+    // newest last, like a shell history
+    matching.reverse();
+    let mut out = BufWriter::new(io::stdout().lock());
+    ignore_broken_pipe(
+        crate::history::write(&mut out, &matching, global.output).and_then(|_| out.flush()),
+    )
+}
+
+/// The files of a command together with the global --input files.
+fn inputs(global: &GlobalArgs, files: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    let inputs: Vec<String> = global.input.iter().chain(files).cloned().collect();
+    if inputs.is_empty() {
+        return Err("no input, pass log files or '-' to read stdin".into());
+    }
+    Ok(inputs)
+}
+
+fn for_each_input_line(
+    inputs: &[String],
+    rotated: bool,
+    mut f: impl FnMut(&str) -> io::Result<()>,
+) -> io::Result<()> {
+    for input in inputs {
+        let paths = if rotated && input != "-" {
+            input::with_rotated(Path::new(input))?
+        } else {
+            vec![PathBuf::from(input)]
+        };
+        for path in paths {
+            input::for_each_line(input::open(&path.to_string_lossy())?, &mut f)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_records(
+    inputs: &[String],
+    rotated: bool,
+    global: &GlobalArgs,
+    config: &Config,
+) -> io::Result<Vec<Record>> {
+    let options = config.bind_options(global.dialect);
+    let mut parser = StatementParser::new(config.log_formats());
+    let mut records = Vec::new();
+    for_each_input_line(inputs, rotated, |line| {
+        if let Some(statement) = parser.push_line(line) {
+            records.push(Record::new(&statement, &options));
+        }
+        Ok(())
+    })?;
+    Ok(records)
+}
+
+fn ignore_broken_pipe(result: io::Result<()>) -> Result<(), Box<dyn Error>> {
+    match result {
+        // the reader of the pipe went away, e.g. `sql-param parse app.log | head`
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => Ok(other?),
+    }
 }
@@ -0,0 +1,247 @@
+use crate::config::ClipboardConfig;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Where the result is copied to and logs are read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardBackend {
+    /// The system clipboard, or OSC 52 when there is none (over SSH, in containers)
+    #[default]
+    Auto,
+    /// The system clipboard
+    Native,
+    /// Escape sequences the terminal copies from, also through tmux and screen. Write only
+    Osc52,
+    /// The X11 primary selection, pasted with the middle mouse button
+    Primary,
+    /// A file, `path` of the `[clipboard]` config
+    File,
+    /// Nothing is read or copied
+    None,
+}
+
+/// The clipboard of the configured backend. Failures are returned, never panic.
+pub struct Clipboard {
+    backend: ClipboardBackend,
+    /// Whether the backend was chosen for `Auto`, so that another can be tried
+    auto: bool,
+    /// Why `Auto` moved to OSC 52, until it is shown
+    fallback: Option<String>,
+    /// Opened on first use, `None` when there is no system clipboard
+    native: Option<arboard::Clipboard>,
+    path: PathBuf,
+}
+
+impl Clipboard {
+    pub fn new(config: &ClipboardConfig) -> Clipboard {
+        let path = config.path.clone().unwrap_or_else(|| {
+            crate::config::data_dir()
+                .unwrap_or_else(env::temp_dir)
+                .join("sql-param")
+                .join("clipboard.txt")
+        });
+        let mut clipboard = Clipboard {
+            backend: config.backend,
+            auto: config.backend == ClipboardBackend::Auto,
+            fallback: None,
+            native: None,
+            path,
+        };
+        if clipboard.backend == ClipboardBackend::Auto {
+            // without a display server OSC 52 is the only way out
+            clipboard.backend = match clipboard.native() {
+                Ok(_) => ClipboardBackend::Native,
+                Err(_) => ClipboardBackend::Osc52,
+            };
+        }
+        clipboard
+    }
+
+    /// The backend in use, `Auto` is resolved when the clipboard is created.
+    pub fn backend(&self) -> ClipboardBackend {
+        self.backend
+    }
+
+    /// Why `Auto` stopped using the system clipboard, once.
+    pub fn take_fallback(&mut self) -> Option<String> {
+        self.fallback.take()
+    }
+
+    pub fn get_text(&mut self) -> Result<String, String> {
+        match self.backend {
+            ClipboardBackend::Auto | ClipboardBackend::Native => {
+                self.native()?.get_text().map_err(|e| e.to_string())
+            }
+            ClipboardBackend::Primary => self.get_primary(),
+            ClipboardBackend::File => match fs::read_to_string(&self.path) {
+                Ok(text) => Ok(text),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+                Err(e) => Err(format!("cannot read {}: {}", self.path.display(), e)),
+            },
+            ClipboardBackend::Osc52 => Err(String::from("OSC 52 cannot be read")),
+            ClipboardBackend::None => Err(String::from("the clipboard is turned off")),
+        }
+    }
+
+    /// Copies `text`. With `Auto` a failing system clipboard is given up for OSC 52, the reason
+    /// is kept for [`Clipboard::take_fallback`].
+    pub fn set_text(&mut self, text: &str) -> Result<(), String> {
+        match self.backend {
+            ClipboardBackend::Auto | ClipboardBackend::Native => {
+                let copied = self
+                    .native()
+                    .and_then(|native| native.set_text(text).map_err(|e| e.to_string()));
+                match copied {
+                    Err(e) if self.auto => {
+                        self.backend = ClipboardBackend::Osc52;
+                        self.fallback = Some(format!(
+                            "the system clipboard failed ({}), copying with OSC 52",
+                            e
+                        ));
+                        self.set_text(text)
+                    }
+                    copied => copied,
+                }
+            }
+            ClipboardBackend::Primary => self.set_primary(text),
+            ClipboardBackend::Osc52 => {
+                let mut out = io::stdout();
+                out.write_all(osc52(text, env::var_os("TMUX").is_some()).as_bytes())
+                    .and_then(|_| out.flush())
+                    .map_err(|e| e.to_string())
+            }
+            ClipboardBackend::File => {
+                if let Some(dir) = self.path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                fs::write(&self.path, text)
+                    .map_err(|e| format!("cannot write {}: {}", self.path.display(), e))
+            }
+            ClipboardBackend::None => Ok(()),
+        }
+    }
+
+    fn native(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.native.is_none() {
+            self.native = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        Ok(self.native.as_mut().expect("opened above"))
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    fn get_primary(&mut self) -> Result<String, String> {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+        self.native()?
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    fn set_primary(&mut self, text: &str) -> Result<(), String> {
+        use arboard::{LinuxClipboardKind, SetExtLinux};
+        self.native()?
+            .set()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text(text)
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    )))]
+    fn get_primary(&mut self) -> Result<String, String> {
+        Err(String::from("there is no primary selection on this system"))
+    }
+
+    #[cfg(not(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    )))]
+    fn set_primary(&mut self, _text: &str) -> Result<(), String> {
+        Err(String::from("there is no primary selection on this system"))
+    }
+}
+
+/// The OSC 52 sequence setting the clipboard to `text`, wrapped to pass through tmux.
+pub fn osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if tmux {
+        // tmux passes on what follows `Ptmux;` with every escape doubled
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clipboard::{base64, osc52, Clipboard, ClipboardBackend};
+    use crate::config::ClipboardConfig;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+        assert_eq!("\x1b]52;c;U0VMRUNUIDE=\x07", osc52("SELECT 1", false));
+        assert_eq!(
+            "\x1bPtmux;\x1b\x1b]52;c;U0VMRUNUIDE=\x07\x1b\\",
+            osc52("SELECT 1", true)
+        );
+    }
+
+    #[test]
+    fn test_file_clipboard() {
+        let dir = TestDir::new("clipboard");
+        let mut clipboard = Clipboard::new(&ClipboardConfig {
+            backend: ClipboardBackend::File,
+            path: Some(dir.join("clipboard.txt")),
+            ..ClipboardConfig::default()
+        });
+        assert_eq!(Ok(String::new()), clipboard.get_text());
+        clipboard.set_text("SELECT 1").unwrap();
+        assert_eq!(Ok(String::from("SELECT 1")), clipboard.get_text());
+
+        let mut clipboard = Clipboard::new(&ClipboardConfig {
+            backend: ClipboardBackend::None,
+            ..ClipboardConfig::default()
+        });
+        assert!(clipboard.set_text("SELECT 1").is_ok());
+        assert!(clipboard.get_text().is_err());
+    }
+}
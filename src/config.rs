@@ -0,0 +1,316 @@
+use crate::clipboard::ClipboardBackend;
+use crate::core::{BindOptions, Dialect, ValueKind};
+use crate::keymap::Keybindings;
+use crate::layout::PaneLayout;
+use crate::lexer::PlaceholderStyle;
+use crate::log_parser::LogFormat;
+use crate::theme::{ThemeConfig, ThemeName};
+use serde::de::value::StrDeserializer;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+const APP_NAME: &str = "sql-param";
+
+/// Settings read from `config.toml`, shared by the command line and the TUI.
+///
+/// Values are taken from, lowest priority first: the defaults, the config file, the
+/// `SQL_PARAM_*` environment variables and the command line options.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub dialect: Dialect,
+    /// Only placeholders of this style are replaced, e.g. to keep `:name` in PostgreSQL casts
+    pub placeholder_style: Option<PlaceholderStyle>,
+    /// How values of java types missing from the built-in list are written
+    pub types: HashMap<String, ValueKind>,
+    /// Log formats tried before the built-in MyBatis one
+    pub log_formats: Vec<LogFormat>,
+    pub clipboard: ClipboardConfig,
+    pub history: HistoryConfig,
+    pub theme: ThemeConfig,
+    pub layout: PaneLayout,
+    pub keybindings: Keybindings,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+    /// Fill the empty inputs with the statements of a log copied to the clipboard
+    pub read: bool,
+    /// Copy the result to the clipboard
+    pub write: bool,
+    pub backend: ClipboardBackend,
+    /// Load the logs copied while the TUI is open
+    pub watch: bool,
+    /// How often the clipboard is checked when it is watched, in milliseconds
+    pub watch_interval: u64,
+    /// File of the `file` backend instead of `$XDG_DATA_HOME/sql-param/clipboard.txt`
+    pub path: Option<PathBuf>,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        ClipboardConfig {
+            read: true,
+            write: true,
+            backend: ClipboardBackend::default(),
+            watch: false,
+            watch_interval: 500,
+            path: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Keep the converted statements in the history file
+    pub enabled: bool,
+    /// Number of entries kept, 0 keeps them all
+    pub limit: usize,
+    /// History file to use instead of `$XDG_DATA_HOME/sql-param/history.jsonl`
+    pub path: Option<PathBuf>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            enabled: true,
+            limit: 1000,
+            path: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file at `path`, or at the `SQL_PARAM_CONFIG` path, or in the config
+    /// directory. Only a missing file in the config directory is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn Error>> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var_os("SQL_PARAM_CONFIG").map(PathBuf::from));
+        let path = explicit
+            .clone()
+            .or_else(|| config_dir().map(|dir| dir.join(APP_NAME).join("config.toml")));
+
+        let mut config = match path {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(text) => Config::parse(&text)
+                    .map_err(|e| format!("invalid config file {}: {}", path.display(), e))?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound && explicit.is_none() => {
+                    Config::default()
+                }
+                Err(e) => return Err(format!("cannot read {}: {}", path.display(), e).into()),
+            },
+            None => Config::default(),
+        };
+        config.apply_env(|name| env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), Box<dyn Error>> {
+        if let Some(value) = var("SQL_PARAM_DIALECT") {
+            self.dialect = from_env("SQL_PARAM_DIALECT", &value)?;
+        }
+        if let Some(value) = var("SQL_PARAM_PLACEHOLDER_STYLE") {
+            self.placeholder_style = Some(from_env("SQL_PARAM_PLACEHOLDER_STYLE", &value)?);
+        }
+        if let Some(value) = var("SQL_PARAM_THEME") {
+            self.theme.name = from_env::<ThemeName>("SQL_PARAM_THEME", &value)?;
+        }
+        // https://no-color.org: any value but an empty one turns the colours off
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            self.theme.no_color = true;
+        }
+        if let Some(value) = var("SQL_PARAM_CLIPBOARD") {
+            self.clipboard.backend = from_env("SQL_PARAM_CLIPBOARD", &value)?;
+        }
+        for (name, flag) in [
+            ("SQL_PARAM_CLIPBOARD_READ", &mut self.clipboard.read),
+            ("SQL_PARAM_CLIPBOARD_WRITE", &mut self.clipboard.write),
+            ("SQL_PARAM_CLIPBOARD_WATCH", &mut self.clipboard.watch),
+            ("SQL_PARAM_HISTORY", &mut self.history.enabled),
+        ] {
+            if let Some(value) = var(name) {
+                *flag = value
+                    .parse()
+                    .map_err(|_| format!("{}: expected true or false, got '{}'", name, value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The options to bind values with, `dialect` overrides the configured one.
+    pub fn bind_options(&self, dialect: Option<Dialect>) -> BindOptions {
+        BindOptions {
+            dialect: dialect.unwrap_or(self.dialect),
+            placeholder_style: self.placeholder_style,
+            types: self.types.clone(),
+        }
+    }
+
+    /// The configured log formats followed by the built-in one.
+    pub fn log_formats(&self) -> Vec<LogFormat> {
+        let mut formats = self.log_formats.clone();
+        formats.push(LogFormat::mybatis());
+        formats
+    }
+}
+
+fn from_env<'de, T: Deserialize<'de>>(name: &str, value: &'de str) -> Result<T, String> {
+    T::deserialize(StrDeserializer::<serde::de::value::Error>::new(value))
+        .map_err(|e| format!("{}: {}", name, e))
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+}
+
+/// `$XDG_DATA_HOME`, or `~/.local/share`.
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clipboard::ClipboardBackend;
+    use crate::config::Config;
+    use crate::core::{Dialect, ValueKind};
+    use crate::layout::LayoutPreset;
+    use crate::lexer::PlaceholderStyle;
+    use crate::theme::{ThemeElement, ThemeName};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::style::{Color, Style, Stylize};
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r##"
+            dialect = "postgresql"
+            placeholder_style = "numbered"
+
+            [types]
+            JsonNode = "string"
+            "com.example.Money" = "raw"
+
+            [[log_formats]]
+            name = "jdbc"
+            sql = 'SQL: (.+)'
+            parameters = 'Params: \[(.*)\]'
+
+            [clipboard]
+            write = false
+            backend = "osc52"
+
+            [history]
+            limit = 50
+
+            [theme]
+            name = "solarized"
+            accent = "#ff8800"
+
+            [theme.styles]
+            keyword = "blue on white bold"
+
+            [layout]
+            preset = "stacked"
+            inputs = 60
+
+            [keybindings]
+            exit = "ctrl+q"
+            prev_area = "shift+tab"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(Dialect::Postgresql, config.dialect);
+        assert_eq!(Some(PlaceholderStyle::Numbered), config.placeholder_style);
+        assert_eq!(Some(&ValueKind::String), config.types.get("JsonNode"));
+        assert_eq!("jdbc", config.log_formats[0].name);
+        assert_eq!(2, config.log_formats().len());
+        assert!(config.clipboard.read);
+        assert!(!config.clipboard.write);
+        assert_eq!(ClipboardBackend::Osc52, config.clipboard.backend);
+        assert!(config.history.enabled);
+        assert_eq!(50, config.history.limit);
+        assert_eq!(ThemeName::Solarized, config.theme.name);
+        assert_eq!(Some(Color::Rgb(0xff, 0x88, 0x00)), config.theme.accent);
+        assert_eq!(
+            Style::new().fg(Color::Blue).bg(Color::White).bold(),
+            config.theme.styles[&ThemeElement::Keyword].0
+        );
+        assert_eq!(LayoutPreset::Stacked, config.layout.preset);
+        assert_eq!((60, 50), (config.layout.inputs, config.layout.sql));
+        assert!(config.layout.remember);
+        assert!(config
+            .keybindings
+            .exit
+            .matches(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)));
+        assert!(config
+            .keybindings
+            .prev_area
+            .matches(&KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert_eq!("Ctrl+l", config.keybindings.clear.to_string());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let error = |text: &str| Config::parse(text).unwrap_err().to_string();
+        assert!(error("dialect = \"db2\"").contains("unknown variant `db2`"));
+        assert!(error("colour = true").contains("unknown field `colour`"));
+        assert!(error("[types]\nJsonNode = \"text\"").contains("unknown variant `text`"));
+        assert!(error("[theme]\naccent = \"greenish\"").contains("unknown colour 'greenish'"));
+        assert!(error("[theme.styles]\nkeywords = \"bold\"").contains("unknown variant `keywords`"));
+        assert!(error("[keybindings]\nexit = \"hyper+x\"").contains("unknown modifier 'hyper'"));
+        assert!(
+            error("[[log_formats]]\nname = \"a\"\nsql = \"(\"\nparameters = \"(.*)\"")
+                .contains("invalid regex")
+        );
+        assert!(
+            error("[[log_formats]]\nname = \"a\"\nsql = \"SQL:\"\nparameters = \"(.*)\"")
+                .contains("capture group")
+        );
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = Config::parse("dialect = \"mysql\"").unwrap();
+        config
+            .apply_env(|name| match name {
+                "SQL_PARAM_DIALECT" => Some(String::from("oracle")),
+                "SQL_PARAM_CLIPBOARD_READ" => Some(String::from("false")),
+                "SQL_PARAM_HISTORY" => Some(String::from("false")),
+                "SQL_PARAM_CLIPBOARD" => Some(String::from("file")),
+                "SQL_PARAM_THEME" => Some(String::from("high-contrast")),
+                "NO_COLOR" => Some(String::from("1")),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(Dialect::Oracle, config.dialect);
+        assert_eq!(ClipboardBackend::File, config.clipboard.backend);
+        assert!(!config.clipboard.read);
+        assert!(!config.history.enabled);
+        assert_eq!(ThemeName::HighContrast, config.theme.name);
+        assert!(config.theme.no_color);
+        assert!(config
+            .apply_env(|name| (name == "SQL_PARAM_DIALECT").then(|| String::from("db2")))
+            .unwrap_err()
+            .to_string()
+            .starts_with("SQL_PARAM_DIALECT: unknown variant `db2`"));
+    }
+}
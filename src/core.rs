@@ -1,61 +1,338 @@
-#[derive(Debug)]
-struct Value {
-    field_value: String,
-    field_type: String,
+use crate::lexer::{tokenize, PlaceholderStyle, TokenKind};
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+
+lazy_static! {
+    // value(Type) as printed by MyBatis, the type is a simple or qualified java class name,
+    // byte[] for arrays
+    static ref TYPED_VALUE_REGEX: Regex = Regex::new(r"\([A-Za-z_][\w.$]*(?:\[\])*\)\s*$").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Value {
+    #[serde(rename = "value")]
+    pub field_value: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    /// Byte range of the value and its type in the parameter list
+    #[serde(skip)]
+    pub span: Range<usize>,
+}
+
+/// The database the replaced sql is written for, it decides how values are quoted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    #[default]
+    Generic,
+    Mysql,
+    Postgresql,
+    Oracle,
+    Sqlserver,
+}
+
+/// How a value of a java type is written into sql.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueKind {
+    String,
+    Timestamp,
+    Date,
+    Time,
+    Boolean,
+    /// Written as is, numbers and unknown types
+    Raw,
+}
+
+impl ValueKind {
+    pub fn of(field_type: &str) -> ValueKind {
+        // java.time.LocalDate and LocalDate are the same type
+        let name = field_type.rsplit('.').next().unwrap_or(field_type);
+        match name {
+            "String" | "Character" | "char" | "UUID" | "Clob" | "Enum" => ValueKind::String,
+            "Timestamp" | "LocalDateTime" | "OffsetDateTime" | "ZonedDateTime" | "Instant" => {
+                ValueKind::Timestamp
+            }
+            "Date" | "LocalDate" => ValueKind::Date,
+            "Time" | "LocalTime" | "OffsetTime" => ValueKind::Time,
+            "Boolean" | "boolean" => ValueKind::Boolean,
+            _ => ValueKind::Raw,
+        }
+    }
+}
+
+/// How placeholders are replaced.
+#[derive(Debug, Clone, Default)]
+pub struct BindOptions {
+    pub dialect: Dialect,
+    /// Only placeholders of this style are replaced, all of them when `None`
+    pub placeholder_style: Option<PlaceholderStyle>,
+    /// Java type names mapped to how their values are written, checked before the built-in types
+    pub types: HashMap<String, ValueKind>,
+}
+
+impl BindOptions {
+    pub fn kind(&self, field_type: &str) -> ValueKind {
+        let name = field_type.rsplit('.').next().unwrap_or(field_type);
+        self.types
+            .get(field_type)
+            .or_else(|| self.types.get(name))
+            .copied()
+            .unwrap_or_else(|| ValueKind::of(field_type))
+    }
+}
+
+impl From<Dialect> for BindOptions {
+    fn from(dialect: Dialect) -> Self {
+        BindOptions {
+            dialect,
+            ..BindOptions::default()
+        }
+    }
 }
 
 impl Value {
-    fn is_string(&self) -> bool {
-        self.field_type == "String" || self.field_type == "Timestamp"
+    fn to_sql(&self, options: &BindOptions) -> String {
+        let dialect = options.dialect;
+        let quoted = || match dialect {
+            Dialect::Mysql => format!(
+                "'{}'",
+                self.field_value.replace('\\', "\\\\").replace('\'', "''")
+            ),
+            _ => format!("'{}'", self.field_value.replace('\'', "''")),
+        };
+        match options.kind(&self.field_type) {
+            ValueKind::String => quoted(),
+            ValueKind::Timestamp if dialect == Dialect::Oracle => format!("TIMESTAMP {}", quoted()),
+            ValueKind::Date if dialect == Dialect::Oracle => format!("DATE {}", quoted()),
+            ValueKind::Timestamp | ValueKind::Date | ValueKind::Time => quoted(),
+            ValueKind::Boolean => match (dialect, self.field_value.as_str()) {
+                (Dialect::Oracle | Dialect::Sqlserver, "true") => String::from("1"),
+                (Dialect::Oracle | Dialect::Sqlserver, "false") => String::from("0"),
+                (Dialect::Mysql | Dialect::Postgresql, value) => value.to_uppercase(),
+                (_, value) => value.to_string(),
+            },
+            ValueKind::Raw => self.field_value.clone(),
+        }
     }
 }
 
-fn values(value: &str) -> Vec<Value> {
-    value
-        .split(",")
-        .map(|v| {
-            let mut split = v.trim().split('(');
-            return Value {
-                field_value: String::from(split.next().unwrap()),
-                field_type: String::from(
-                    split.next().unwrap_or("").split(')').next().unwrap_or(""),
-                ),
-            };
+/// The result of replacing the placeholders of a sql.
+#[derive(Debug)]
+pub struct Binding {
+    pub result: String,
+    /// How many values the placeholders refer to, repeated `:name`s or `$n`s count once
+    pub expected_values: usize,
+    pub placeholder_style: Option<PlaceholderStyle>,
+    pub values: Vec<Value>,
+    pub placeholders: Vec<Placeholder>,
+    /// Placeholders left as they are written
+    pub unbound: Vec<Unbound>,
+}
+
+/// A placeholder of the sql and where its value was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// Byte range of the placeholder in the sql
+    pub span: Range<usize>,
+    /// Index of the value it refers to, there may be fewer values
+    pub value: usize,
+    /// Byte range of the value in the result, `None` when the value is missing
+    pub result: Option<Range<usize>>,
+}
+
+/// A placeholder of the sql that refers to no value, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unbound {
+    /// Byte range of the placeholder in the sql
+    pub span: Range<usize>,
+    pub reason: String,
+}
+
+fn parse_value(text: &str, range: Range<usize>) -> Value {
+    let v = &text[range.clone()];
+    let start = range.start + v.len() - v.trim_start().len();
+    let v = v.trim();
+    let span = start..start + v.len();
+    match TYPED_VALUE_REGEX.find(v) {
+        Some(m) => Value {
+            field_value: String::from(&v[..m.start()]),
+            field_type: String::from(m.as_str().trim_end().trim_matches(['(', ')'])),
+            span,
+        },
+        None => Value {
+            field_value: String::from(v),
+            field_type: String::new(),
+            span,
+        },
+    }
+}
+
+/// Splits a MyBatis parameter list like `a, b(String), 18(Integer), null`.
+///
+/// When the values carry types, a comma only ends a value after its `(Type)` or a `null`,
+/// so string values containing commas are kept whole.
+pub fn values(value: &str) -> Vec<Value> {
+    let mut pieces: Vec<Range<usize>> = Vec::new();
+    let mut start = 0;
+    for (comma, _) in value.match_indices(',') {
+        pieces.push(start..comma);
+        start = comma + 1;
+    }
+    pieces.push(start..value.len());
+    if !pieces
+        .iter()
+        .any(|piece| TYPED_VALUE_REGEX.is_match(&value[piece.clone()]))
+    {
+        return pieces
+            .into_iter()
+            .map(|piece| parse_value(value, piece))
+            .collect();
+    }
+
+    let mut values = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    for piece in pieces {
+        let range = current
+            .take()
+            .map_or(piece.clone(), |current| current.start..piece.end);
+        let trimmed = value[range.clone()].trim();
+        if TYPED_VALUE_REGEX.is_match(trimmed) || trimmed == "null" || trimmed.is_empty() {
+            values.push(parse_value(value, range));
+        } else {
+            current = Some(range);
+        }
+    }
+    if let Some(range) = current {
+        values.push(parse_value(value, range));
+    }
+    values
+}
+
+/// Writes values back as a parameter list, `values` reads it into the same values.
+pub fn format_values(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(|value| {
+            if value.field_type.is_empty() {
+                value.field_value.clone()
+            } else {
+                format!("{}({})", value.field_value, value.field_type)
+            }
         })
-        .collect::<Vec<Value>>()
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
-pub fn replace_placeholder(sql: &str, value: &str) -> String {
+pub fn replace_placeholder(sql: &str, value: &str, options: &BindOptions) -> String {
     if sql.is_empty() || value.is_empty() {
         return String::from("");
     }
 
-    let mut result = String::new();
+    bind(sql, value, options).result
+}
 
+/// Replaces the placeholders of `sql` with `value`, placeholders inside string literals and
+/// comments are left alone. `$n` placeholders take the n-th value, each distinct `:name` the
+/// next unused one. The style of the first placeholder is the one replaced, the values cannot
+/// be told apart when the styles are mixed.
+pub fn bind(sql: &str, value: &str, options: &BindOptions) -> Binding {
     let values = values(value);
+    let mut result = String::new();
+    let mut question_count = 0;
+    let mut expected_values = 0;
+    let mut placeholder_style = None;
+    let mut names: Vec<&str> = Vec::new();
+    let mut placeholders = Vec::new();
+    let mut unbound = Vec::new();
 
-    let mut i = 0;
-    for char in sql.chars() {
-        if char == '?' {
-            if let Some(v) = values.get(i) {
-                if v.is_string() {
-                    result.push_str(format!("'{}'", v.field_value.as_str()).as_str());
-                } else {
-                    result.push_str(format!("{}", v.field_value.as_str()).as_str());
-                }
+    for token in tokenize(sql) {
+        let text = &sql[token.start..token.end];
+        let style = match token.kind {
+            TokenKind::Placeholder(style)
+                if options.placeholder_style.is_none_or(|only| only == style) =>
+            {
+                style
             }
-            i += 1;
-        } else {
-            result.push(char);
+            _ => {
+                result.push_str(text);
+                continue;
+            }
+        };
+        let first = *placeholder_style.get_or_insert(style);
+        let number = text[1..].parse::<usize>();
+        let reason = match style {
+            _ if style != first => Some(format!(
+                "{} is left as is, the sql mixes placeholder styles and only the {} ones are replaced",
+                text,
+                first.example()
+            )),
+            PlaceholderStyle::Numbered if number == Ok(0) => Some(format!(
+                "{} is left as is, numbered placeholders start at $1",
+                text
+            )),
+            PlaceholderStyle::Numbered if number.is_err() => Some(format!(
+                "{} is left as is, its number is too large",
+                text
+            )),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            unbound.push(Unbound {
+                span: token.start..token.end,
+                reason,
+            });
+            result.push_str(text);
+            continue;
         }
+        let index = match style {
+            PlaceholderStyle::Question => question_count,
+            PlaceholderStyle::Numbered => number.map_or(0, |number| number - 1),
+            PlaceholderStyle::Named => match names.iter().position(|name| *name == text) {
+                Some(index) => index,
+                None => {
+                    names.push(text);
+                    names.len() - 1
+                }
+            },
+        };
+        if style == PlaceholderStyle::Question {
+            question_count += 1;
+        }
+        expected_values = expected_values.max(index + 1);
+        let start = result.len();
+        if let Some(v) = values.get(index) {
+            result.push_str(v.to_sql(options).as_str());
+        }
+        placeholders.push(Placeholder {
+            span: token.start..token.end,
+            value: index,
+            result: values.get(index).map(|_| start..result.len()),
+        });
     }
 
-    result
+    Binding {
+        result,
+        expected_values,
+        placeholder_style,
+        values,
+        placeholders,
+        unbound,
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::core::replace_placeholder;
+    use crate::core::{bind, format_values, values, BindOptions, Dialect, ValueKind};
+    use crate::lexer::PlaceholderStyle;
+
+    fn replace_placeholder(sql: &str, value: &str) -> String {
+        crate::core::replace_placeholder(sql, value, &BindOptions::default())
+    }
 
     #[test]
     fn test() {
@@ -93,4 +370,183 @@ mod test {
         let value = String::from("");
         assert_eq!("", replace_placeholder(sql.as_str(), value.as_str()));
     }
+
+    #[test]
+    fn test_placeholder_in_literal() {
+        let sql = "SELECT * FROM user WHERE name = ? AND remark = 'why?' -- ?\nAND age = ?";
+        let value = "zhangsan(String), 18(Integer)";
+        assert_eq!(
+            "SELECT * FROM user WHERE name = 'zhangsan' AND remark = 'why?' -- ?\nAND age = 18",
+            replace_placeholder(sql, value)
+        );
+    }
+
+    #[test]
+    fn test_numbered_and_named() {
+        let value = "zhangsan(String), 18(Integer)";
+        assert_eq!(
+            "SELECT * FROM user WHERE age = 18 AND name = 'zhangsan'",
+            replace_placeholder("SELECT * FROM user WHERE age = $2 AND name = $1", value)
+        );
+        assert_eq!(
+            "SELECT * FROM user WHERE name = 'zhangsan' OR alias = 'zhangsan' AND age = 18",
+            replace_placeholder(
+                "SELECT * FROM user WHERE name = :name OR alias = :name AND age = :age",
+                value
+            )
+        );
+    }
+
+    #[test]
+    fn test_unbound_placeholders() {
+        let binding = bind(
+            "SELECT $0, $99999999999999999999, :a, ?, $1",
+            "1(Integer)",
+            &BindOptions::default(),
+        );
+        assert_eq!("SELECT $0, $99999999999999999999, :a, ?, 1", binding.result);
+        assert_eq!(1, binding.expected_values);
+        let unbound: Vec<_> = binding
+            .unbound
+            .iter()
+            .map(|unbound| (unbound.span.clone(), unbound.reason.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (7..9, "$0 is left as is, numbered placeholders start at $1"),
+                (
+                    11..32,
+                    "$99999999999999999999 is left as is, its number is too large"
+                ),
+                (
+                    34..36,
+                    ":a is left as is, the sql mixes placeholder styles and only the $n ones are replaced"
+                ),
+                (
+                    38..39,
+                    "? is left as is, the sql mixes placeholder styles and only the $n ones are replaced"
+                ),
+            ],
+            unbound
+        );
+
+        // a placeholder of another style is not given the next value
+        let binding = bind(
+            "SELECT ?, :a",
+            "1(Integer), 2(Integer)",
+            &BindOptions::default(),
+        );
+        assert_eq!("SELECT 1, :a", binding.result);
+        assert_eq!(1, binding.unbound.len());
+    }
+
+    #[test]
+    fn test_placeholder_spans() {
+        let binding = bind(
+            "SELECT * FROM user WHERE age = $2 AND name = $1 OR id = $3",
+            "zhangsan(String), 18(Integer)",
+            &BindOptions::default(),
+        );
+        assert_eq!(
+            "SELECT * FROM user WHERE age = 18 AND name = 'zhangsan' OR id = ",
+            binding.result
+        );
+        let spans: Vec<_> = binding
+            .placeholders
+            .iter()
+            .map(|p| (p.span.clone(), p.value, p.result.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (31..33, 1, Some(31..33)),
+                (45..47, 0, Some(45..55)),
+                (56..58, 2, None)
+            ],
+            spans
+        );
+    }
+
+    #[test]
+    fn test_values_with_commas() {
+        let values = values("a, b(String), null, 18(Integer)");
+        assert_eq!(3, values.len());
+        assert_eq!("a, b", values[0].field_value);
+        assert_eq!(0..12, values[0].span);
+        assert_eq!("null", values[1].field_value);
+        assert_eq!(14..18, values[1].span);
+        assert_eq!("Integer", values[2].field_type);
+        assert_eq!(20..31, values[2].span);
+    }
+
+    #[test]
+    fn test_format_values() {
+        let text = "a, b(String), null, (String), 2025-06-13 16:44:56.499(java.sql.Timestamp)";
+        let parsed = values(text);
+        assert_eq!(text, format_values(&parsed));
+        let again: Vec<_> = values(&format_values(&parsed))
+            .into_iter()
+            .map(|v| (v.field_value, v.field_type))
+            .collect();
+        let parsed: Vec<_> = parsed
+            .into_iter()
+            .map(|v| (v.field_value, v.field_type))
+            .collect();
+        assert_eq!(parsed, again);
+    }
+
+    #[test]
+    fn test_dialect() {
+        let sql = "INSERT INTO t VALUES (?, ?, ?, ?)";
+        let value = "it's a\\b(String), 2025-06-13 16:44:56.499(Timestamp), true(Boolean), 2025-06-13(LocalDate)";
+        assert_eq!(
+            "INSERT INTO t VALUES ('it''s a\\b', '2025-06-13 16:44:56.499', true, '2025-06-13')",
+            bind(sql, value, &Dialect::Generic.into()).result
+        );
+        assert_eq!(
+            "INSERT INTO t VALUES ('it''s a\\\\b', '2025-06-13 16:44:56.499', TRUE, '2025-06-13')",
+            bind(sql, value, &Dialect::Mysql.into()).result
+        );
+        assert_eq!(
+            "INSERT INTO t VALUES ('it''s a\\b', TIMESTAMP '2025-06-13 16:44:56.499', 1, DATE '2025-06-13')",
+            bind(sql, value, &Dialect::Oracle.into()).result
+        );
+    }
+
+    #[test]
+    fn test_generic_dialect() {
+        // written like before the dialects
+        assert_eq!(
+            "SELECT 'zhangsan', '2025-06-13 16:44:56.499', 18, true, {}, 1.5",
+            replace_placeholder(
+                "SELECT ?, ?, ?, ?, ?, ?",
+                "zhangsan(String), 2025-06-13 16:44:56.499(Timestamp), 18(Integer), true(Boolean), {}(JsonNode), 1.5"
+            )
+        );
+        // quotes are doubled, and every text, date and time type is quoted
+        assert_eq!(
+            "SELECT 'it''s', 'a', '2025-06-13', '16:44:56', 'PAID', '2025-06-13 16:44:56'",
+            replace_placeholder(
+                "SELECT ?, ?, ?, ?, ?, ?",
+                "it's(String), a(Character), 2025-06-13(LocalDate), 16:44:56(LocalTime), PAID(Enum), 2025-06-13 16:44:56(java.sql.Timestamp)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_bind_options() {
+        let options = BindOptions {
+            dialect: Dialect::Generic,
+            placeholder_style: Some(PlaceholderStyle::Question),
+            types: [(String::from("JsonNode"), ValueKind::String)].into(),
+        };
+        assert_eq!(
+            "SELECT '{\"a\": 1}'::jsonb, :name FROM t",
+            bind(
+                "SELECT ?::jsonb, :name FROM t",
+                "{\"a\": 1}(com.fasterxml.jackson.databind.JsonNode)",
+                &options
+            )
+            .result
+        );
+    }
 }
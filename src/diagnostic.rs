@@ -0,0 +1,250 @@
+use crate::core::{BindOptions, Binding, ValueKind};
+use crate::lexer::{tokenize, TokenKind};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+
+lazy_static! {
+    // Object.toString() of an array: [B@1a2b3c, [Ljava.lang.String;@1a2b3c
+    static ref ARRAY_REGEX: Regex = Regex::new(r"^\[+(?:[BCDFIJSZ]|L[\w.$]+;)@[0-9a-f]+$").unwrap();
+    // Object.toString() of a class without its own: com.foo.Bar@1a2b3c
+    static ref OBJECT_REGEX: Regex =
+        Regex::new(r"^[A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)+@[0-9a-f]+$").unwrap();
+}
+
+/// Java types written as is on purpose, the other types written as is are unknown.
+const NUMBER_TYPES: &[&str] = &[
+    "Integer",
+    "int",
+    "Long",
+    "long",
+    "Short",
+    "short",
+    "Byte",
+    "byte",
+    "Double",
+    "double",
+    "Float",
+    "float",
+    "BigDecimal",
+    "BigInteger",
+    "Number",
+];
+
+/// What logging frameworks append to a line they cut.
+const TRUNCATION_MARKS: &[&str] = &["...", "…", "[truncated]", "(truncated)"];
+
+/// Something that looks wrong with a statement, e.g. values missing for some placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub message: String,
+    /// Where it is, `None` when it is not about a part of the statement
+    #[serde(skip)]
+    pub location: Option<Location>,
+}
+
+/// A byte range of the sql or of the parameter list of a statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    Sql(Range<usize>),
+    Value(Range<usize>),
+}
+
+impl Diagnostic {
+    pub fn new(message: String, location: Option<Location>) -> Diagnostic {
+        Diagnostic { message, location }
+    }
+}
+
+/// Checks the binding of `sql` with the parameter list `value`.
+pub fn check(sql: &str, value: &str, binding: &Binding, options: &BindOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let value_count = binding
+        .values
+        .iter()
+        .filter(|v| !v.field_value.is_empty() || !v.field_type.is_empty())
+        .count();
+    if binding.expected_values != value_count {
+        // the first placeholder without a value, or the first value left over
+        let location = binding
+            .placeholders
+            .iter()
+            .find(|p| p.value >= value_count)
+            .map(|p| Location::Sql(p.span.clone()))
+            .or_else(|| {
+                let extra = binding.values.get(binding.expected_values)?;
+                Some(Location::Value(extra.span.clone()))
+            });
+        diagnostics.push(Diagnostic::new(
+            format!(
+                "{} placeholders but {} values",
+                binding.expected_values, value_count
+            ),
+            location,
+        ));
+    }
+
+    for unbound in &binding.unbound {
+        diagnostics.push(Diagnostic::new(
+            unbound.reason.clone(),
+            Some(Location::Sql(unbound.span.clone())),
+        ));
+    }
+
+    for (index, v) in binding.values.iter().enumerate() {
+        let location = Some(Location::Value(v.span.clone()));
+        let name = v.field_type.rsplit('.').next().unwrap_or(&v.field_type);
+        let known = v.field_type.is_empty()
+            || options.kind(&v.field_type) != ValueKind::Raw
+            || options.types.contains_key(&v.field_type)
+            || options.types.contains_key(name)
+            || NUMBER_TYPES.contains(&name)
+            || name.ends_with("[]");
+        if !known {
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "value {} has the unknown type {}, it is written as is",
+                    index + 1,
+                    v.field_type
+                ),
+                location.clone(),
+            ));
+        }
+        if ARRAY_REGEX.is_match(&v.field_value) {
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "value {} is how Java prints an array ({}), not its content",
+                    index + 1,
+                    v.field_value
+                ),
+                location,
+            ));
+        } else if OBJECT_REGEX.is_match(&v.field_value) {
+            diagnostics.push(Diagnostic::new(
+                format!(
+                    "value {} is how Java prints an object ({}), not its content",
+                    index + 1,
+                    v.field_value
+                ),
+                location,
+            ));
+        }
+    }
+
+    if let Some(reason) = sql_truncation(sql) {
+        diagnostics.push(Diagnostic::new(
+            format!("the sql looks truncated: {}", reason),
+            Some(Location::Sql(sql.len()..sql.len())),
+        ));
+    }
+    if ends_with_mark(value) {
+        diagnostics.push(Diagnostic::new(
+            String::from("the parameters look truncated"),
+            Some(Location::Value(value.len()..value.len())),
+        ));
+    }
+    diagnostics
+}
+
+fn ends_with_mark(text: &str) -> bool {
+    let text = text.trim_end();
+    TRUNCATION_MARKS.iter().any(|mark| text.ends_with(mark))
+}
+
+/// Why `sql` seems cut off, if it does.
+fn sql_truncation(sql: &str) -> Option<&'static str> {
+    if ends_with_mark(sql) {
+        return Some("it ends with a truncation mark");
+    }
+    let tokens = tokenize(sql);
+    let last = tokens
+        .iter()
+        .rev()
+        .find(|token| token.kind != TokenKind::Whitespace)?;
+    let text = &sql[last.start..last.end];
+    if last.kind == TokenKind::String && (text.len() < 2 || !text.ends_with('\'')) {
+        return Some("a string is not closed");
+    }
+    let depth: isize = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::Punctuation)
+        .map(|token| match &sql[token.start..token.end] {
+            "(" => 1,
+            ")" => -1,
+            _ => 0,
+        })
+        .sum();
+    (depth > 0).then_some("a parenthesis is not closed")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::{bind, BindOptions};
+    use crate::diagnostic::{check, Location};
+
+    fn messages(sql: &str, value: &str) -> Vec<String> {
+        let options = BindOptions::default();
+        check(sql, value, &bind(sql, value, &options), &options)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(messages("SELECT * FROM t WHERE id IN (?, ?)", "1(Long), 2(Integer)").is_empty());
+
+        let sql = "SELECT * FROM t WHERE a = ? AND b = ?";
+        let options = BindOptions::default();
+        let diagnostics = check(sql, "1(Long)", &bind(sql, "1(Long)", &options), &options);
+        assert_eq!("2 placeholders but 1 values", diagnostics[0].message);
+        assert_eq!(Some(Location::Sql(36..37)), diagnostics[0].location);
+
+        assert_eq!(
+            vec![
+                "value 1 has the unknown type JsonNode, it is written as is",
+                "value 2 is how Java prints an array ([B@1a2b3c), not its content",
+                "value 3 is how Java prints an object (com.foo.Bar@abc), not its content",
+            ],
+            messages(
+                "INSERT INTO t VALUES (?, ?, ?)",
+                "{}(JsonNode), [B@1a2b3c(byte[]), com.foo.Bar@abc(String)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_unbound() {
+        assert_eq!(
+            vec![
+                "0 placeholders but 1 values",
+                "$0 is left as is, numbered placeholders start at $1",
+            ],
+            messages("SELECT $0", "1(Integer)")
+        );
+        assert_eq!(
+            vec![":a is left as is, the sql mixes placeholder styles and only the ? ones are replaced"],
+            messages("SELECT ?, :a", "1(Integer)")
+        );
+    }
+
+    #[test]
+    fn test_truncation() {
+        assert_eq!(
+            vec!["the sql looks truncated: a parenthesis is not closed"],
+            messages("INSERT INTO t VALUES (?", "1(Integer)")
+        );
+        assert_eq!(
+            vec!["the sql looks truncated: a string is not closed"],
+            messages("SELECT * FROM t WHERE a = ? AND b = 'ab", "1(Integer)")
+        );
+        assert_eq!(
+            vec![
+                "the sql looks truncated: it ends with a truncation mark",
+                "the parameters look truncated",
+            ],
+            messages("SELECT * FROM t WHERE a = ? ...", "1(Integer)...")
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::output::{csv_field, OutputFormat, Record};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// A statement template executed a different number of times in two logs.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DiffEntry {
+    pub template: String,
+    pub old: usize,
+    pub new: usize,
+}
+
+/// Compares how often each statement template was executed, templates with equal counts are left out.
+/// Removed templates come first, then added ones, then the ones with changed counts.
+pub fn diff(old: &[Record], new: &[Record]) -> Vec<DiffEntry> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for record in old {
+        counts.entry(normalize(&record.template)).or_default().0 += 1;
+    }
+    for record in new {
+        counts.entry(normalize(&record.template)).or_default().1 += 1;
+    }
+    let mut entries: Vec<DiffEntry> = counts
+        .into_iter()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(template, (old, new))| DiffEntry { template, old, new })
+        .collect();
+    entries.sort_by_key(|entry| match (entry.old, entry.new) {
+        (_, 0) => 0,
+        (0, _) => 1,
+        _ => 2,
+    });
+    entries
+}
+
+/// Templates that only differ in whitespace are the same statement.
+fn normalize(template: &str) -> String {
+    template.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn write(out: &mut impl Write, entries: &[DiffEntry], format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::Sql => {
+            for entry in entries {
+                match (entry.old, entry.new) {
+                    (old, 0) => writeln!(out, "- {:>6}  {}", old, entry.template)?,
+                    (0, new) => writeln!(out, "+ {:>6}  {}", new, entry.template)?,
+                    (old, new) => writeln!(out, "~ {:>6}  {} (was {})", new, entry.template, old)?,
+                }
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, entries)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for entry in entries {
+                serde_json::to_writer(&mut *out, entry)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "template,old,new")?;
+            for entry in entries {
+                writeln!(
+                    out,
+                    "{},{},{}",
+                    csv_field(&entry.template),
+                    entry.old,
+                    entry.new
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::BindOptions;
+    use crate::diff::{diff, DiffEntry};
+    use crate::log_parser::StatementParser;
+    use crate::output::Record;
+
+    fn records(log: &str) -> Vec<Record> {
+        let mut parser = StatementParser::default();
+        log.lines()
+            .filter_map(|line| parser.push_line(line))
+            .map(|statement| Record::new(&statement, &BindOptions::default()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = records(
+            "==>  Preparing: SELECT * FROM user WHERE id = ?
+            ==> Parameters: 1(Long)
+            ==>  Preparing: DELETE FROM cart WHERE id = ?
+            ==> Parameters: 1(Long)",
+        );
+        let new = records(
+            "==>  Preparing: SELECT *  FROM user WHERE id = ?
+            ==> Parameters: 1(Long)
+            ==>  Preparing: SELECT * FROM user WHERE id = ?
+            ==> Parameters: 2(Long)
+            ==>  Preparing: SELECT * FROM cart
+            ==> Parameters: ",
+        );
+        assert_eq!(
+            vec![
+                DiffEntry {
+                    template: String::from("DELETE FROM cart WHERE id = ?"),
+                    old: 1,
+                    new: 0
+                },
+                DiffEntry {
+                    template: String::from("SELECT * FROM cart"),
+                    old: 0,
+                    new: 1
+                },
+                DiffEntry {
+                    template: String::from("SELECT * FROM user WHERE id = ?"),
+                    old: 1,
+                    new: 2
+                },
+            ],
+            diff(&old, &new)
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The editor of `$VISUAL` or `$EDITOR`, with its arguments, e.g. `code --wait`.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| String::from(if cfg!(windows) { "notepad" } else { "vi" }))
+}
+
+/// Lets `command` edit `text` in a temporary file ending with `extension`, and returns the
+/// text it saved. The terminal has to be left to the editor while it runs.
+pub fn edit_text(command: &str, text: &str, extension: &str) -> Result<String, String> {
+    let words = split_command(command)?;
+    let (program, arguments) = words
+        .split_first()
+        .ok_or("no editor, set $VISUAL or $EDITOR")?;
+    let (path, mut file) = create_temporary(extension)
+        .map_err(|e| format!("cannot create a temporary file: {}", e))?;
+    let written = writeln!(file, "{}", text).and_then(|_| file.sync_all());
+    drop(file);
+    if let Err(e) = written {
+        let _ = fs::remove_file(&path);
+        return Err(format!("cannot write {}: {}", path.display(), e));
+    }
+    let status = Command::new(program).args(arguments).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => return Err(format!("{} exited with {}", program, status)),
+        Err(e) => return Err(format!("cannot run {}: {}", program, e)),
+    }
+    let edited = edited.map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    // editors end the last line, the panes do not
+    let edited = edited.strip_suffix('\n').unwrap_or(&edited);
+    Ok(edited.strip_suffix('\r').unwrap_or(edited).to_string())
+}
+
+/// A new file of the temporary directory only the user can read. Its name is random and it
+/// must not exist yet, so that no file or link put there by another user is written to.
+fn create_temporary(extension: &str) -> io::Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut attempts = 0;
+    loop {
+        let random = RandomState::new().build_hasher().finish();
+        let path = env::temp_dir().join(format!("sql-param-edit-{:016x}.{}", random, extension));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < 10 => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The words of `command` as a shell splits them: quotes and backslashes keep spaces in a word,
+/// e.g. `"/opt/My Editor/edit" --wait`. Variables and other shell syntax are not expanded.
+fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(char) => word.push(char),
+                        None => return Err(format!("unclosed quote in the editor {}", command)),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(char @ ('"' | '\\' | '$' | '`')) => word.push(char),
+                            Some(char) => {
+                                word.push('\\');
+                                word.push(char);
+                            }
+                            None => {
+                                return Err(format!("unclosed quote in the editor {}", command))
+                            }
+                        },
+                        Some(char) => word.push(char),
+                        None => return Err(format!("unclosed quote in the editor {}", command)),
+                    }
+                }
+            }
+            // backslashes are path separators on windows
+            '\\' if !cfg!(windows) => {
+                let word = word.get_or_insert_with(String::new);
+                if let Some(char) = chars.next() {
+                    word.push(char);
+                }
+            }
+            char if char.is_whitespace() => words.extend(word.take()),
+            char => word.get_or_insert_with(String::new).push(char),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::editor::{edit_text, split_command};
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_text() {
+        assert_eq!(
+            Ok(String::from("SELECT 1\nFROM dual")),
+            edit_text("true", "SELECT 1\nFROM dual", "sql")
+        );
+        assert!(edit_text("false", "SELECT 1", "sql")
+            .unwrap_err()
+            .starts_with("false exited with"));
+        assert!(edit_text("sql-param-no-such-editor", "SELECT 1", "sql")
+            .unwrap_err()
+            .starts_with("cannot run sql-param-no-such-editor"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_split_command() {
+        let split = |command: &str| split_command(command).unwrap();
+        assert_eq!(vec!["code", "--wait"], split("  code   --wait "));
+        assert_eq!(
+            vec!["/opt/My Editor/edit", "-n", "it's"],
+            split("\"/opt/My Editor/edit\" -n 'it'\\''s'")
+        );
+        assert_eq!(
+            vec!["/opt/My Editor/edit", ""],
+            split("/opt/My\\ Editor/edit ''")
+        );
+        assert_eq!(vec!["a\"b\\c"], split("\"a\\\"b\\c\""));
+        assert!(split_command("vim 'unclosed").is_err());
+        assert!(split("").is_empty());
+    }
+}
@@ -1,118 +1,1138 @@
 use crate::app::{App, AreaEnum};
+use crate::keymap::{Action, Command, Mode, Profile};
+use crate::param_table::ParamColumn;
 use crossterm::event::{
     KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
+use tui_textarea::CursorMove;
+
+const LIST_PAGE: isize = 10;
+/// Columns the Result pane scrolls sideways when its lines are not wrapped
+const RESULT_COLUMNS: usize = 8;
+/// Lines the mouse wheel scrolls
+const WHEEL_LINES: isize = 3;
 
 pub fn handle_key(app: &mut App, key: KeyEvent) {
     if key.kind != KeyEventKind::Press {
         return;
     }
+    app.message = None;
+    // Esc stops reading a large log before anything else
+    if key.code == KeyCode::Esc && key.modifiers.is_empty() && app.cancel_parse() {
+        return;
+    }
+    if matches!(app.mode, Mode::Command(_)) {
+        handle_command_key(app, key);
+        return;
+    }
+    if app.search.as_ref().is_some_and(|search| search.typing) {
+        handle_search_key(app, key);
+        return;
+    }
 
-    if key.code == KeyCode::Esc {
-        app.should_exit = true;
+    if app.help.is_some() {
+        handle_help_key(app, key);
+        return;
+    }
+    if app.palette.is_some() {
+        handle_palette_key(app, key);
+        return;
+    }
+    if app.export.is_some() {
+        handle_export_key(app, key);
+        return;
+    }
+    let keybindings = &app.config.keybindings;
+    if app.history_view.is_some() {
+        if keybindings.exit.matches(&key) || keybindings.history.matches(&key) {
+            app.toggle_history();
+        } else {
+            handle_history_key(app, key);
+        }
+        return;
+    }
+    let table = app.current_area == AreaEnum::Value && app.param_table.is_some();
+    if table && handle_table_key(app, key) {
+        return;
+    }
+    // the modes and editor keys of the inputs come before the keys below
+    if !table
+        && matches!(app.current_area, AreaEnum::Sql | AreaEnum::Value)
+        && handle_mode_key(app, key)
+    {
+        return;
+    }
+    let keybindings = &app.config.keybindings;
+    if keybindings.profile == Profile::Vim
+        && app.current_area == AreaEnum::Result
+        && key.code == KeyCode::Char(':')
+    {
+        app.mode = Mode::Command(String::new());
+        return;
+    }
+    if app.current_area == AreaEnum::Result && handle_result_key(app, key) {
+        return;
+    }
+    // `?` and `/` are typed into the inputs and filter the statements
+    if key.code == KeyCode::Char('?')
+        && matches!(app.current_area, AreaEnum::Result | AreaEnum::Diagnostics)
+    {
+        app.toggle_help();
+        return;
+    }
+    if key.code == KeyCode::Char('/')
+        && matches!(app.current_area, AreaEnum::Result | AreaEnum::Diagnostics)
+    {
+        app.start_search();
+        return;
+    }
+    // Esc stops marking the search matches before it exits
+    if key.code == KeyCode::Esc && key.modifiers.is_empty() && app.search.take().is_some() {
+        return;
+    }
+    if let Some(command) = app.config.keybindings.command(&key) {
+        app.execute(command);
         return;
     }
 
     match app.current_area {
-        AreaEnum::Sql => match key.code {
-            KeyCode::Tab => app.current_area = app.next_area(),
-            KeyCode::BackTab => {
-                app.current_area = app.prev_area();
-                app.calculate_result();
-            }
-            _other => handle_common_key(app, key),
-        },
-        AreaEnum::Value => match key.code {
-            KeyCode::Tab => {
-                app.current_area = app.next_area();
-                app.calculate_result();
-            }
-            KeyCode::BackTab => app.current_area = app.prev_area(),
-            _other => handle_common_key(app, key),
-        },
-        AreaEnum::Result => match key.code {
-            KeyCode::Tab => app.current_area = app.next_area(),
-            KeyCode::BackTab => app.current_area = app.prev_area(),
-            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.input_clear();
-            },
-            _ => return,
-        },
+        AreaEnum::List => handle_list_key(app, key),
+        AreaEnum::Value if table => {}
+        AreaEnum::Sql | AreaEnum::Value => type_char(app, key),
+        AreaEnum::Diagnostics => handle_diagnostics_key(app, key),
+        AreaEnum::Result => {}
     }
 }
 
-fn handle_common_key(app: &mut App, key: KeyEvent) {
+/// Arrows scroll the help, its keys, Esc and `q` close it.
+fn handle_help_key(app: &mut App, key: KeyEvent) {
+    let keybindings = &app.config.keybindings;
+    if keybindings.help.matches(&key)
+        || keybindings.exit.matches(&key)
+        || matches!(key.code, KeyCode::Esc | KeyCode::Char('?' | 'q'))
+    {
+        app.toggle_help();
+        return;
+    }
+    let delta = match key.code {
+        KeyCode::Up | KeyCode::Char('k') => -1,
+        KeyCode::Down | KeyCode::Char('j') => 1,
+        KeyCode::PageUp => -LIST_PAGE,
+        KeyCode::PageDown => LIST_PAGE,
+        KeyCode::Home | KeyCode::Char('g') => isize::MIN,
+        KeyCode::End | KeyCode::Char('G') => isize::MAX,
+        _ => return,
+    };
+    // the end is kept within the help when it is drawn
+    if let Some(scroll) = app.help.as_mut() {
+        *scroll = (*scroll as isize)
+            .saturating_add(delta)
+            .clamp(0, u16::MAX as isize) as u16;
+    }
+}
+
+/// Typing searches the pane and moves to the first match, arrows and the search key move to
+/// the other matches, Enter keeps them marked and Esc goes back.
+fn handle_search_key(app: &mut App, key: KeyEvent) {
+    let keybindings = &app.config.keybindings;
+    let next = keybindings.search.matches(&key)
+        || keybindings.search_next.matches(&key)
+        || app.keymap.action(&key) == Some(Action::Search);
+    let previous = keybindings.search_prev.matches(&key);
+    let cancel = key.code == KeyCode::Esc
+        || (key.modifiers == KeyModifiers::CONTROL && matches!(key.code, KeyCode::Char('c' | 'g')));
+    if cancel {
+        app.end_search(false);
+        return;
+    }
     match key.code {
-        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.input_clear();
+        _ if next => app.search_next(1),
+        _ if previous => app.search_next(-1),
+        KeyCode::Down | KeyCode::Tab => app.search_next(1),
+        KeyCode::Up | KeyCode::BackTab => app.search_next(-1),
+        KeyCode::Enter => app.end_search(true),
+        KeyCode::Backspace if app.search.as_ref().is_some_and(|s| s.query.is_empty()) => {
+            app.end_search(false)
         }
-        KeyCode::Char(char) => {
-            app.input_char(char);
+        KeyCode::Backspace => app.search_backspace(),
+        KeyCode::Char(char) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+            app.search_char(char)
         }
-        KeyCode::Backspace => {
-            app.input_backspace();
+        _ => {}
+    }
+}
+
+/// Arrows pick what to write, typing edits the path with Tab completing it and Enter writes.
+fn handle_export_key(app: &mut App, key: KeyEvent) {
+    let keybindings = &app.config.keybindings;
+    let cancel = key.code == KeyCode::Esc
+        || keybindings.export.matches(&key)
+        || keybindings.exit.matches(&key)
+        || (key.modifiers == KeyModifiers::CONTROL && matches!(key.code, KeyCode::Char('c' | 'g')));
+    if cancel {
+        app.toggle_export();
+        return;
+    }
+    if key.code == KeyCode::Enter {
+        app.run_export();
+        return;
+    }
+    let clear = keybindings.clear.matches(&key);
+    let Some(dialog) = app.export.as_mut() else {
+        return;
+    };
+    match key.code {
+        _ if clear => {
+            dialog.path.clear();
+            dialog.completions.clear();
         }
-        KeyCode::Delete => {
-            app.input_delete();
+        KeyCode::Up => dialog.select_by(-1),
+        KeyCode::Down => dialog.select_by(1),
+        KeyCode::Tab => dialog.complete(),
+        KeyCode::Backspace => dialog.path_backspace(),
+        KeyCode::Char(char) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+            dialog.path_char(char)
         }
-        KeyCode::Enter => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.insert_newline();
-            }
+        _ => {}
+    }
+}
+
+/// Typing searches the commands, arrows select one and Enter runs it.
+fn handle_palette_key(app: &mut App, key: KeyEvent) {
+    let keybindings = &app.config.keybindings;
+    let cancel = key.code == KeyCode::Esc
+        || keybindings.palette.matches(&key)
+        || keybindings.exit.matches(&key)
+        || (key.modifiers == KeyModifiers::CONTROL && matches!(key.code, KeyCode::Char('c' | 'g')));
+    if cancel {
+        app.toggle_palette();
+        return;
+    }
+    if key.code == KeyCode::Enter {
+        app.run_palette();
+        return;
+    }
+    let Some(palette) = app.palette.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Up => palette.select_by(-1),
+        KeyCode::Down => palette.select_by(1),
+        KeyCode::PageUp => palette.select_by(-LIST_PAGE),
+        KeyCode::PageDown => palette.select_by(LIST_PAGE),
+        KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => palette.select_by(1),
+        KeyCode::Char(char) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+            palette.query_char(char)
         }
-        KeyCode::Up => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.move_cursor(tui_textarea::CursorMove::Up);
+        KeyCode::Backspace => palette.query_backspace(),
+        _ => {}
+    }
+}
+
+/// Arrows move through the diagnostics and Enter jumps to the selected one.
+fn handle_diagnostics_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => app.select_diagnostic_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.select_diagnostic_by(1),
+        KeyCode::PageUp => app.select_diagnostic_by(-LIST_PAGE),
+        KeyCode::PageDown => app.select_diagnostic_by(LIST_PAGE),
+        KeyCode::Home => app.select_diagnostic_by(isize::MIN),
+        KeyCode::End => app.select_diagnostic_by(isize::MAX),
+        KeyCode::Enter => app.jump_to_diagnostic(),
+        _ => {}
+    }
+}
+
+/// Keys of the parameter table, returns whether the key was used. While a cell is edited or
+/// the type picker is open they take every key without Ctrl or Alt.
+fn handle_table_key(app: &mut App, key: KeyEvent) -> bool {
+    let plain = (key.modifiers - KeyModifiers::SHIFT).is_empty();
+    let mut params = app.params().to_vec();
+    let Some(table) = app.param_table.as_mut() else {
+        return false;
+    };
+    if let Some(text) = table.editing.as_mut() {
+        let changed = match key.code {
+            KeyCode::Enter => table.commit_edit(&mut params),
+            KeyCode::Esc => {
+                table.editing = None;
+                false
             }
-        }
-        KeyCode::Down => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.move_cursor(tui_textarea::CursorMove::Down);
+            KeyCode::Backspace => {
+                text.pop();
+                false
             }
-        }
-        KeyCode::Left => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.move_cursor(tui_textarea::CursorMove::Back);
+            KeyCode::Char(char) if plain => {
+                text.push(char);
+                false
             }
+            _ => return plain,
+        };
+        if changed {
+            app.set_params(&params);
         }
-        KeyCode::Right => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.move_cursor(tui_textarea::CursorMove::Forward);
+        return true;
+    }
+    if let Some((types, index)) = table.picker.as_mut() {
+        let changed = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                *index = index.saturating_sub(1);
+                false
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                *index = (*index + 1).min(types.len() - 1);
+                false
             }
+            KeyCode::Enter => table.pick(&mut params),
+            KeyCode::Esc => {
+                table.picker = None;
+                false
+            }
+            _ => return plain,
+        };
+        if changed {
+            app.set_params(&params);
+        }
+        return true;
+    }
+    if !plain {
+        return false;
+    }
+    let count = params.len();
+    let changed = match key.code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            table.select_by(-1, count);
+            false
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            table.select_by(1, count);
+            false
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            table.select_by(isize::MIN, count);
+            false
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            table.select_by(isize::MAX, count);
+            false
+        }
+        KeyCode::Left | KeyCode::Char('h') => {
+            table.column = ParamColumn::Value;
+            false
         }
-        KeyCode::Home => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.move_cursor(tui_textarea::CursorMove::Head);
+        KeyCode::Right | KeyCode::Char('l') => {
+            table.column = ParamColumn::Type;
+            false
+        }
+        KeyCode::Enter | KeyCode::Char('e' | 'i') => {
+            table.start_edit(&params);
+            false
+        }
+        KeyCode::Char('t') => {
+            table.open_picker(&params);
+            false
+        }
+        KeyCode::Char('n') => table.toggle_null(&mut params),
+        KeyCode::Char('a') => {
+            table.add(&mut params);
+            table.start_edit(&params);
+            true
+        }
+        KeyCode::Char('d') | KeyCode::Delete => table.remove(&mut params),
+        _ => return false,
+    };
+    if changed {
+        app.set_params(&params);
+    }
+    true
+}
+
+/// Arrows move through the statements, typing filters them.
+fn handle_list_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Up => app.select_by(-1),
+        KeyCode::Down => app.select_by(1),
+        KeyCode::PageUp => app.select_by(-LIST_PAGE),
+        KeyCode::PageDown => app.select_by(LIST_PAGE),
+        KeyCode::Home => app.select_by(isize::MIN),
+        KeyCode::End => app.select_by(isize::MAX),
+        KeyCode::Enter => app.focus(AreaEnum::Sql),
+        KeyCode::Char(char) => app.filter_char(char),
+        KeyCode::Backspace => app.filter_backspace(),
+        _ => {}
+    }
+}
+
+/// Arrows move through the history, typing searches it and Enter opens an entry.
+fn handle_history_key(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Enter {
+        app.open_history_entry();
+        return;
+    }
+    let clear = app.config.keybindings.clear.matches(&key);
+    let Some(view) = app.history_view.as_mut() else {
+        return;
+    };
+    if clear {
+        view.query.clear();
+        return;
+    }
+    match key.code {
+        KeyCode::Up => view.select_by(-1),
+        KeyCode::Down => view.select_by(1),
+        KeyCode::PageUp => view.select_by(-LIST_PAGE),
+        KeyCode::PageDown => view.select_by(LIST_PAGE),
+        KeyCode::Home => view.select_by(isize::MIN),
+        KeyCode::End => view.select_by(isize::MAX),
+        KeyCode::Char(char) => view.query_char(char),
+        KeyCode::Backspace => view.query_backspace(),
+        _ => {}
+    }
+}
+
+/// Arrows move the cursor of the Result pane and Shift selects lines, returns whether the key
+/// was used.
+fn handle_result_key(app: &mut App, key: KeyEvent) -> bool {
+    let modifiers = key.modifiers - KeyModifiers::SHIFT;
+    if !modifiers.is_empty() {
+        return false;
+    }
+    let select = key.modifiers.contains(KeyModifiers::SHIFT);
+    let page = app.page_height() as isize;
+    let view = &mut app.result_view;
+    let delta = match key.code {
+        KeyCode::Up | KeyCode::Char('k') => -1,
+        KeyCode::Down | KeyCode::Char('j') => 1,
+        KeyCode::PageUp => -page,
+        KeyCode::PageDown => page,
+        KeyCode::Home | KeyCode::Char('g') => isize::MIN,
+        KeyCode::End | KeyCode::Char('G') => isize::MAX,
+        KeyCode::Left | KeyCode::Char('h') => {
+            view.column = view.column.saturating_sub(RESULT_COLUMNS);
+            return true;
+        }
+        KeyCode::Right | KeyCode::Char('l') if !view.wrap => {
+            view.column += RESULT_COLUMNS;
+            return true;
+        }
+        KeyCode::Char('w') => {
+            view.wrap = !view.wrap;
+            view.column = 0;
+            return true;
+        }
+        KeyCode::Char('v' | ' ') => {
+            view.toggle_lines();
+            return true;
+        }
+        KeyCode::Char('s') => {
+            view.select_statement(&app.result);
+            return true;
+        }
+        KeyCode::Char('y') | KeyCode::Enter => {
+            app.copy_result_selection();
+            return true;
+        }
+        KeyCode::Char('n') => {
+            app.search_next(1);
+            return true;
+        }
+        KeyCode::Char('N') => {
+            app.search_next(-1);
+            return true;
+        }
+        // Esc drops the selection before it exits
+        KeyCode::Esc if view.selection.is_some() => {
+            view.selection = None;
+            return true;
+        }
+        _ => return false,
+    };
+    view.move_by(&app.result, delta, select);
+    true
+}
+
+/// Keys of the vim modes and the editor keymap, returns whether the key was used.
+fn handle_mode_key(app: &mut App, key: KeyEvent) -> bool {
+    let vim = app.config.keybindings.profile == Profile::Vim;
+    if vim && app.mode != Mode::Insert && handle_vim_key(app, key) {
+        return true;
+    }
+    if vim && app.mode == Mode::Insert && key.code == KeyCode::Esc {
+        app.mode = Mode::Normal;
+        app.edit(Action::Left);
+        return true;
+    }
+    if let Some(action) = app.keymap.action(&key) {
+        app.edit(action);
+        return true;
+    }
+    false
+}
+
+/// Keys of the vim normal and visual modes, returns whether the key was used.
+fn handle_vim_key(app: &mut App, key: KeyEvent) -> bool {
+    let visual = app.mode == Mode::Visual;
+    let pending = app.pending.take();
+    let modifiers = key.modifiers - KeyModifiers::SHIFT;
+    if modifiers == KeyModifiers::CONTROL {
+        let action = match key.code {
+            KeyCode::Char('r') => Action::Redo,
+            KeyCode::Char('d' | 'f') => Action::PageDown,
+            KeyCode::Char('u' | 'b') => Action::PageUp,
+            _ => return false,
+        };
+        app.edit(action);
+        return true;
+    }
+    if !modifiers.is_empty() {
+        return false;
+    }
+    let char = match key.code {
+        KeyCode::Esc => {
+            if visual {
+                app.edit(Action::Cancel);
+                app.mode = Mode::Normal;
+            } else {
+                app.search = None;
             }
+            return true;
+        }
+        KeyCode::Enter => '\n',
+        KeyCode::Backspace => 'h',
+        KeyCode::Delete => 'x',
+        KeyCode::Char(char) => char,
+        _ => return false,
+    };
+    let action = match (pending, char) {
+        (Some('g'), 'g') => Action::Top,
+        (Some('d'), 'd') => return edit_line(app, Action::Cut),
+        (Some('y'), 'y') => return edit_line(app, Action::Copy),
+        (None, 'g') => return wait_for_second_key(app, char),
+        (None, 'd' | 'y') if !visual => return wait_for_second_key(app, char),
+        (_, 'h') => Action::Left,
+        (_, 'l' | ' ') => Action::Right,
+        (_, 'j' | '\n') => Action::Down,
+        (_, 'k') => Action::Up,
+        (_, 'w') => Action::WordRight,
+        (_, 'e') => Action::WordEnd,
+        (_, 'b') => Action::WordLeft,
+        (_, '0' | '^') => Action::LineStart,
+        (_, '$') => Action::LineEnd,
+        (_, 'G') => Action::Bottom,
+        (_, 'p' | 'P') => Action::Paste,
+        (_, 'u') => Action::Undo,
+        (_, 'x' | 'd') if visual => Action::Cut,
+        (_, 'y') if visual => Action::Copy,
+        (_, 'x') => Action::Delete,
+        (_, 'v') if visual => Action::Cancel,
+        (_, 'v') => Action::SetMark,
+        (_, ':') => {
+            app.mode = Mode::Command(String::new());
+            return true;
+        }
+        (_, '?') => {
+            app.execute(Command::Help);
+            return true;
+        }
+        (_, '/') => {
+            app.execute(Command::Search);
+            return true;
+        }
+        (_, 'n') => {
+            app.execute(Command::SearchNext);
+            return true;
+        }
+        (_, 'N') => {
+            app.execute(Command::SearchPrev);
+            return true;
         }
-        KeyCode::End => {
-            if let Some(textarea) = app.get_current_input() {
-                textarea.move_cursor(tui_textarea::CursorMove::End);
+        (_, 'i' | 'a' | 'A' | 'I' | 'o' | 'O') => {
+            insert(app, char);
+            return true;
+        }
+        // other letters are not typed in normal mode
+        _ => return true,
+    };
+    app.edit(action);
+    app.mode = match action {
+        Action::SetMark => Mode::Visual,
+        Action::Cancel | Action::Cut | Action::Copy | Action::Paste => Mode::Normal,
+        _ => app.mode.clone(),
+    };
+    true
+}
+
+fn wait_for_second_key(app: &mut App, char: char) -> bool {
+    app.pending = Some(char);
+    true
+}
+
+fn edit_line(app: &mut App, action: Action) -> bool {
+    app.edit_line(action);
+    true
+}
+
+/// Enters insert mode like `i`, `a`, `A`, `I`, `o` and `O`.
+fn insert(app: &mut App, char: char) {
+    if let Some(input) = app.get_current_input() {
+        input.cancel_selection();
+        let (row, column) = input.cursor();
+        match char {
+            // `a` appends after the cursor without moving to the next line
+            'a' if column < input.lines()[row].chars().count() => {
+                input.move_cursor(CursorMove::Forward)
             }
+            'A' | 'o' => input.move_cursor(CursorMove::End),
+            'I' | 'O' => input.move_cursor(CursorMove::Head),
+            _ => {}
+        }
+    }
+    app.mark = false;
+    app.mode = Mode::Insert;
+    match char {
+        'o' => app.edit(Action::Newline),
+        'O' => {
+            app.edit(Action::Newline);
+            app.edit(Action::Up);
+        }
+        _ => {}
+    }
+}
+
+/// Typing into the command line, `Enter` runs the command.
+fn handle_command_key(app: &mut App, key: KeyEvent) {
+    let cancel = key.code == KeyCode::Esc
+        || (key.modifiers == KeyModifiers::CONTROL && matches!(key.code, KeyCode::Char('c' | 'g')));
+    let Mode::Command(line) = &mut app.mode else {
+        return;
+    };
+    if cancel {
+        app.mode = app.input_mode();
+        return;
+    }
+    match key.code {
+        KeyCode::Enter => {
+            let line = std::mem::take(line);
+            app.mode = app.input_mode();
+            app.run_command(&line);
+        }
+        KeyCode::Backspace if line.is_empty() => app.mode = app.input_mode(),
+        KeyCode::Backspace => {
+            line.pop();
+        }
+        KeyCode::Char(char) => line.push(char),
+        _ => {}
+    }
+}
+
+/// Types the character of keys without Ctrl or Alt into the input.
+fn type_char(app: &mut App, key: KeyEvent) {
+    // AltGr is reported as Ctrl+Alt on Windows
+    let modifiers = key.modifiers - KeyModifiers::SHIFT;
+    if let KeyCode::Char(char) = key.code {
+        if modifiers.is_empty() || modifiers == KeyModifiers::CONTROL | KeyModifiers::ALT {
+            app.input_char(char);
         }
-        _ => return,
     }
 }
 
 pub fn handle_paste(app: &mut App, data: String) {
+    if let Mode::Command(line) = &mut app.mode {
+        line.extend(data.chars().filter(|char| !char.is_control()));
+        return;
+    }
+    if let Some(dialog) = app.export.as_mut() {
+        data.trim()
+            .chars()
+            .filter(|char| !char.is_control())
+            .for_each(|char| dialog.path_char(char));
+        return;
+    }
+    if app.search.as_ref().is_some_and(|search| search.typing) {
+        data.chars()
+            .filter(|char| !char.is_control())
+            .for_each(|char| app.search_char(char));
+        return;
+    }
+    // the help takes all keys while it is shown, pastes too
+    if app.help.is_some() {
+        return;
+    }
+    if let Some(palette) = app.palette.as_mut() {
+        data.chars()
+            .filter(|char| !char.is_control())
+            .for_each(|char| palette.query_char(char));
+        return;
+    }
+    if let Some(view) = app.history_view.as_mut() {
+        // a pasted line break separates search words like a space
+        data.chars()
+            .map(|char| if char.is_whitespace() { ' ' } else { char })
+            .for_each(|char| view.query_char(char));
+        return;
+    }
     match app.current_area {
         AreaEnum::Sql => {
             app.sql_input.insert_str(&data);
         }
+        AreaEnum::Value if app.param_table.is_some() => {
+            // pasted into the edited cell, on one line
+            if let Some(text) = app.param_table.as_mut().and_then(|t| t.editing.as_mut()) {
+                text.push_str(&data.replace(['\r', '\n'], " "));
+            }
+            return;
+        }
         AreaEnum::Value => {
             app.value_input.insert_str(&data);
         }
         _ => return,
     }
+    app.input_changed();
 }
 
 pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
-    if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
-        if let Some(clicked_area) = app.get_area_by_coordinate(mouse.column, mouse.row) {
-            app.current_area = clicked_area;
-            if clicked_area == AreaEnum::Result {
-                app.calculate_result();
+    if app.history_view.is_some()
+        || app.help.is_some()
+        || app.palette.is_some()
+        || app.export.is_some()
+    {
+        return;
+    }
+    let delta = match mouse.kind {
+        // the borders between panes are dragged to resize them
+        MouseEventKind::Down(MouseButton::Left) if app.start_drag(mouse.column, mouse.row) => {
+            return;
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            app.drag(mouse.column, mouse.row);
+            return;
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            app.stop_drag();
+            return;
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(clicked_area) = app.get_area_by_coordinate(mouse.column, mouse.row) {
+                app.focus(clicked_area);
+                match clicked_area {
+                    AreaEnum::List => select_clicked(app, mouse.row),
+                    AreaEnum::Result => click_result(app, mouse.row),
+                    AreaEnum::Value => click_param(app, mouse.row),
+                    AreaEnum::Diagnostics => click_diagnostic(app, mouse.row),
+                    AreaEnum::Sql => {}
+                }
+            }
+            return;
+        }
+        MouseEventKind::ScrollUp => -WHEEL_LINES,
+        MouseEventKind::ScrollDown => WHEEL_LINES,
+        _ => return,
+    };
+    // the wheel scrolls the pane under the mouse without focusing it
+    match app.get_area_by_coordinate(mouse.column, mouse.row) {
+        Some(AreaEnum::Result) => app.result_view.move_by(&app.result, delta, false),
+        Some(AreaEnum::List) => app.select_by(delta),
+        Some(AreaEnum::Diagnostics) => app.select_diagnostic_by(delta),
+        Some(AreaEnum::Value) if app.param_table.is_some() => {
+            let count = app.params().len();
+            if let Some(table) = app.param_table.as_mut() {
+                table.select_by(delta, count);
             }
         }
+        Some(area @ (AreaEnum::Sql | AreaEnum::Value)) => {
+            let input = if area == AreaEnum::Sql {
+                &mut app.sql_input
+            } else {
+                &mut app.value_input
+            };
+            let movement = if delta < 0 {
+                CursorMove::Up
+            } else {
+                CursorMove::Down
+            };
+            for _ in 0..delta.unsigned_abs() {
+                input.move_cursor(movement);
+            }
+        }
+        None => {}
+    }
+}
+
+/// Selects the clicked diagnostic, a second click on it jumps to it.
+fn click_diagnostic(app: &mut App, row: u16) {
+    let Some(&rect) = app.area_coordinates.get(&AreaEnum::Diagnostics) else {
+        return;
+    };
+    let Some(line) = row.checked_sub(rect.y + 1) else {
+        return;
+    };
+    let position = app.diagnostic_state.offset() + line as usize;
+    if position >= app.diagnostic_items().len() {
+        return;
+    }
+    if position == app.diagnostic_selected {
+        app.jump_to_diagnostic();
+    } else {
+        app.diagnostic_selected = position;
+    }
+}
+
+/// Selects the clicked parameter of the table.
+fn click_param(app: &mut App, row: u16) {
+    let Some(&rect) = app.area_coordinates.get(&AreaEnum::Value) else {
+        return;
+    };
+    let count = app.params().len();
+    let Some(table) = app.param_table.as_mut() else {
+        return;
+    };
+    // below the border and the header
+    if let Some(row) = row.checked_sub(rect.y + 2) {
+        let row = table.state.offset() + row as usize;
+        if row < count {
+            table.selected = row;
+        }
+    }
+}
+
+/// Moves the cursor of the Result pane to the clicked line.
+fn click_result(app: &mut App, row: u16) {
+    let Some(&rect) = app.area_coordinates.get(&AreaEnum::Result) else {
+        return;
+    };
+    let Some(row) = row.checked_sub(rect.y + 1) else {
+        return;
+    };
+    let view = &mut app.result_view;
+    if let Some(&line) = view.rows.get(view.scroll + row as usize) {
+        view.selection = None;
+        view.cursor = line;
+    }
+}
+
+fn select_clicked(app: &mut App, row: u16) {
+    let Some(&rect) = app.area_coordinates.get(&AreaEnum::List) else {
+        return;
+    };
+    // the first row is the border
+    let Some(line) = row.checked_sub(rect.y + 1) else {
+        return;
+    };
+    let position = app.list_state.offset() + line as usize;
+    if let Some(&index) = app.visible_statements().get(position) {
+        app.select(index);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::app::{App, AreaEnum};
+    use crate::config::Config;
+    use crate::core::Dialect;
+    use crate::event_handler::{handle_key, handle_mouse, handle_paste};
+    use crate::keymap::{Mode, Profile};
+    use crate::layout::LayoutPreset;
+    use crate::ui::ui;
+    use crossterm::event::{
+        KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use tui_textarea::TextArea;
+
+    fn app(profile: Profile, sql: &str, value: &str) -> App {
+        let mut config = Config::default();
+        config.clipboard.write = false;
+        config.history.enabled = false;
+        config.layout.remember = false;
+        config.keybindings.profile = profile;
+        let mut app = App::new(config);
+        app.sql_input = TextArea::from(sql.lines());
+        app.value_input = TextArea::from(value.lines());
+        app.input_changed();
+        app
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for char in keys.chars() {
+            let code = match char {
+                '\x1b' => KeyCode::Esc,
+                '\n' => KeyCode::Enter,
+                char => KeyCode::Char(char),
+            };
+            handle_key(app, KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn test_vim_keys() {
+        let mut app = app(Profile::Vim, "SELECT * FROM user\nWHERE id = ?", "1(Long)");
+        assert_eq!(Mode::Normal, app.mode);
+
+        // letters move in normal mode and Esc does not exit
+        press(&mut app, "jwx\x1b");
+        assert!(!app.should_exit);
+        assert_eq!("WHERE d = ?", app.get_sql_text().lines().nth(1).unwrap());
+        press(&mut app, "u");
+        press(&mut app, "A AND deleted = 0\x1b");
+        assert_eq!(Mode::Normal, app.mode);
+        assert_eq!(
+            "SELECT * FROM user\nWHERE id = 1 AND deleted = 0",
+            app.result
+        );
+
+        press(&mut app, "ggdd");
+        assert_eq!("WHERE id = ? AND deleted = 0", app.get_sql_text());
+        press(&mut app, "vww");
+        assert_eq!(Mode::Visual, app.mode);
+        assert_eq!(Some(0..9), app.selection(AreaEnum::Sql));
+        press(&mut app, "d");
+        assert_eq!(Mode::Normal, app.mode);
+        assert_eq!("= ? AND deleted = 0", app.get_sql_text());
+
+        press(&mut app, ":nope\n");
+        assert_eq!(Some("unknown command: nope"), app.message.as_deref());
+        // a paste goes to the command line while it is open
+        press(&mut app, ":");
+        handle_paste(&mut app, String::from("dialect\r\n mysql"));
+        assert_eq!(Mode::Command(String::from("dialect mysql")), app.mode);
+        press(&mut app, "\x1b");
+        assert_eq!("= ? AND deleted = 0", app.get_sql_text());
+        press(&mut app, ":q\n");
+        assert!(app.should_exit);
+    }
+
+    #[test]
+    fn test_result_keys() {
+        let mut app = app(
+            Profile::Default,
+            "SELECT 1; SELECT ?;\nDELETE FROM t;",
+            "2(Integer)",
+        );
+        app.focus(AreaEnum::Result);
+
+        press(&mut app, "ss");
+        assert_eq!(Some(10..19), app.result_view.selected_range(&app.result));
+        press(&mut app, "jvk");
+        assert_eq!(0, app.result_view.cursor);
+        assert_eq!(
+            Some(0..app.result.len()),
+            app.result_view.selected_range(&app.result)
+        );
+        // Esc drops the selection first
+        press(&mut app, "\x1b");
+        assert!(!app.should_exit);
+        assert_eq!(None, app.result_view.selection);
+        press(&mut app, "w");
+        assert!(!app.result_view.wrap);
+    }
+
+    #[test]
+    fn test_table_keys() {
+        let mut app = app(
+            Profile::Default,
+            "SELECT * FROM user WHERE id = ? AND name = ?",
+            "1(Long), null",
+        );
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+        );
+        assert!(app.current_area == AreaEnum::Value);
+
+        // typing edits the cell, not the text behind the table
+        press(&mut app, "jeJoe\n");
+        assert_eq!("1(Long), Joe", app.get_value_text());
+        press(&mut app, "t\n");
+        assert_eq!("1(Long), Joe(String)", app.get_value_text());
+        assert_eq!(
+            "SELECT * FROM user WHERE id = 1 AND name = 'Joe'",
+            app.result
+        );
+        press(&mut app, "n");
+        assert_eq!(
+            "SELECT * FROM user WHERE id = 1 AND name = null",
+            app.result
+        );
+        press(&mut app, "kd");
+        assert_eq!("null", app.get_value_text());
+        assert!(app.params()[0].null);
+        // the table follows a value replaced from elsewhere
+        app.value_input = TextArea::from(["7(Long), x(String)"]);
+        app.input_changed();
+        assert_eq!(2, app.params().len());
+        assert_eq!("Long", app.params()[0].field_type);
+    }
+
+    fn mouse(app: &mut App, kind: MouseEventKind, column: u16, row: u16) {
+        handle_mouse(
+            app,
+            MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            },
+        );
+    }
+
+    #[test]
+    fn test_layout_keys() {
+        let mut app = app(Profile::Default, "", "");
+        let mut terminal = Terminal::new(TestBackend::new(100, 43)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+        // dragging the border between the Sql and Value panes resizes them
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 50, 5);
+        mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), 70, 5);
+        mouse(&mut app, MouseEventKind::Up(MouseButton::Left), 70, 5);
+        assert_eq!(70, app.config.layout.sql);
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(70, app.area_coordinates[&AreaEnum::Sql].width);
+        assert!(app.current_area == AreaEnum::Sql);
+
+        let alt = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::ALT);
+        handle_key(&mut app, alt('m'));
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(100, app.area_coordinates[&AreaEnum::Sql].width);
+        assert!(!app.area_coordinates.contains_key(&AreaEnum::Result));
+        handle_key(&mut app, alt('m'));
+
+        handle_key(&mut app, alt('l'));
+        assert_eq!(LayoutPreset::Stacked, app.config.layout.preset);
+        handle_key(&mut app, alt('l'));
+        assert_eq!(Some("layout: result-only"), app.message.as_deref());
+        // the hidden inputs lose the focus and are skipped
+        assert!(app.current_area == AreaEnum::Result);
+        handle_key(&mut app, KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert!(app.current_area == AreaEnum::Result);
+        app.run_command("layout side-by-side");
+        assert_eq!(LayoutPreset::SideBySide, app.config.layout.preset);
+    }
+
+    #[test]
+    fn test_help_and_palette() {
+        let mut app = app(
+            Profile::Default,
+            "SELECT * FROM t WHERE b = ?",
+            "true(Boolean)",
+        );
+        let mut terminal = Terminal::new(TestBackend::new(100, 43)).unwrap();
+        // whether a row of the screen shows all of `texts`
+        let shows = |terminal: &Terminal<TestBackend>, texts: &[&str]| {
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .chunks(buffer.area.width as usize)
+                .any(|row| {
+                    let row: String = row.iter().map(|cell| cell.symbol()).collect();
+                    texts.iter().all(|text| row.contains(text))
+                })
+        };
+
+        handle_key(&mut app, KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE));
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert!(shows(&terminal, &["Ctrl+p ", " Open the command palette"]));
+        press(&mut app, "G");
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert!(shows(&terminal, &["palette ", " Open the command palette"]));
+        // nothing is pasted into the input behind the help
+        handle_paste(&mut app, String::from("x"));
+        assert_eq!("SELECT * FROM t WHERE b = ?", app.get_sql_text());
+        // Esc closes the help without exiting
+        press(&mut app, "\x1b");
+        assert!(app.help.is_none() && !app.should_exit);
+        // `?` is typed into the inputs, elsewhere it shows the help
+        press(&mut app, "?");
+        assert!(app.help.is_none());
+        assert!(app.get_sql_text().starts_with("?SELECT"));
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE),
+        );
+        app.focus(AreaEnum::Result);
+        press(&mut app, "?");
+        assert!(app.help.is_some());
+        press(&mut app, "?");
+
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        handle_key(&mut app, ctrl_p);
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert!(shows(&terminal, &["Format the sql"]));
+        press(&mut app, "dial ora\n");
+        assert!(app.palette.is_none());
+        assert_eq!(Dialect::Oracle, app.bind_options.dialect);
+        assert_eq!("SELECT * FROM t WHERE b = 1", app.result);
+
+        handle_key(&mut app, ctrl_p);
+        press(&mut app, "write\n");
+        assert_eq!(Mode::Command(String::from("w ")), app.mode);
+        press(&mut app, "\x1b");
+        handle_key(&mut app, ctrl_p);
+        press(&mut app, "nothing like it\n");
+        assert!(app.palette.is_none() && !app.should_exit);
+    }
+
+    #[test]
+    fn test_search_keys() {
+        let mut app = app(
+            Profile::Default,
+            "SELECT id FROM orders o\nJOIN order_item i ON i.order_id = o.id WHERE o.id = ?",
+            "7(Long)",
+        );
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let key = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+
+        handle_key(&mut app, key(KeyCode::Char('f'), KeyModifiers::CONTROL));
+        press(&mut app, "order");
+        assert_eq!((0, 15), app.sql_input.cursor());
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(Some("SEARCH"), footer_mode(&terminal));
+        handle_key(&mut app, key(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!((1, 5), app.sql_input.cursor());
+        // Enter closes the prompt, the matches stay marked for F3 and Shift+F3
+        press(&mut app, "\n");
+        handle_key(&mut app, key(KeyCode::F(3), KeyModifiers::NONE));
+        assert_eq!((1, 23), app.sql_input.cursor());
+        handle_key(&mut app, key(KeyCode::F(3), KeyModifiers::NONE));
+        assert_eq!((0, 15), app.sql_input.cursor());
+        handle_key(&mut app, key(KeyCode::F(3), KeyModifiers::SHIFT));
+        assert_eq!((1, 23), app.sql_input.cursor());
+        assert_eq!(3, app.search.as_ref().unwrap().matches.len());
+        // the matches follow the text
+        app.sql_input.insert_str(" order");
+        app.input_changed();
+        assert_eq!(4, app.search.as_ref().unwrap().matches.len());
+        // the first Esc stops marking them, the second exits
+        press(&mut app, "\x1b");
+        assert!(app.search.is_none() && !app.should_exit);
+
+        // `/` searches the Result, upper case makes the search case sensitive
+        app.focus(AreaEnum::Result);
+        press(&mut app, "/WHERE");
+        assert_eq!(1, app.result_view.cursor);
+        press(&mut app, "\n");
+        press(&mut app, "N");
+        assert_eq!(1, app.result_view.cursor);
+        app.result_view.cursor = 0;
+        press(&mut app, "/Order");
+        assert!(app.search.as_ref().unwrap().matches.is_empty());
+        // Esc goes back to where the search started
+        press(&mut app, "\x1b");
+        assert!(app.search.is_none());
+        assert_eq!(0, app.result_view.cursor);
+    }
+
+    /// The mode shown in the title of the footer.
+    fn footer_mode(terminal: &Terminal<TestBackend>) -> Option<&'static str> {
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect();
+        ["SEARCH", "COMMAND"]
+            .into_iter()
+            .find(|mode| rows.iter().any(|row| row.contains(&format!(" {} ", mode))))
     }
 }
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, MAIN_SEPARATOR};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// What the export dialog writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum ExportKind {
+    /// The result shown
+    Result,
+    /// The bound sql of every listed statement, one per line
+    Statements,
+    /// Every listed statement with its details, as the json and csv output formats
+    Json,
+    Csv,
+    /// Everything needed to open the TUI as it is
+    Session,
+}
+
+impl ExportKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            ExportKind::Result => "Bound SQL",
+            ExportKind::Statements => "Statement list",
+            ExportKind::Json => "JSON dump",
+            ExportKind::Csv => "CSV dump",
+            ExportKind::Session => "Session",
+        }
+    }
+
+    /// The file written when no other path is typed.
+    pub fn default_path(&self) -> &'static str {
+        match self {
+            ExportKind::Result => "result.sql",
+            ExportKind::Statements => "statements.sql",
+            ExportKind::Json => "statements.json",
+            ExportKind::Csv => "statements.csv",
+            ExportKind::Session => "sql-param-session.json",
+        }
+    }
+}
+
+/// The export dialog: what to write and where.
+#[derive(Debug)]
+pub struct ExportDialog {
+    pub kind: ExportKind,
+    pub path: String,
+    /// Paths the last Tab could complete to, Tab again goes through them
+    pub completions: Vec<String>,
+}
+
+impl Default for ExportDialog {
+    fn default() -> Self {
+        ExportDialog {
+            kind: ExportKind::Result,
+            path: String::from(ExportKind::Result.default_path()),
+            completions: Vec::new(),
+        }
+    }
+}
+
+impl ExportDialog {
+    /// Picks another kind by `delta`, a default path follows the kind.
+    pub fn select_by(&mut self, delta: isize) {
+        let kinds: Vec<ExportKind> = ExportKind::iter().collect();
+        let position = kinds
+            .iter()
+            .position(|kind| *kind == self.kind)
+            .unwrap_or(0);
+        let kind = kinds[position.saturating_add_signed(delta).min(kinds.len() - 1)];
+        if self.path == self.kind.default_path() {
+            self.path = String::from(kind.default_path());
+        }
+        self.kind = kind;
+    }
+
+    pub fn path_char(&mut self, char: char) {
+        self.path.push(char);
+        self.completions.clear();
+    }
+
+    pub fn path_backspace(&mut self) {
+        self.path.pop();
+        self.completions.clear();
+    }
+
+    /// Completes the path like a shell: as far as the matching files agree, then through each
+    /// of them.
+    pub fn complete(&mut self) {
+        if !self.completions.is_empty() {
+            let next = self
+                .completions
+                .iter()
+                .position(|path| *path == self.path)
+                .map_or(0, |position| (position + 1) % self.completions.len());
+            self.path = self.completions[next].clone();
+            return;
+        }
+        let candidates = complete_path(&self.path);
+        let common = common_prefix(&candidates);
+        if common.len() > self.path.len() {
+            self.path = common;
+        }
+        if candidates.len() > 1 {
+            self.completions = candidates;
+        }
+    }
+}
+
+/// The files and directories starting like the last part of `path`, directories end with a
+/// separator.
+pub fn complete_path(path: &str) -> Vec<String> {
+    let (dir, prefix) = match path.rfind(['/', MAIN_SEPARATOR]) {
+        Some(end) => (&path[..=end], &path[end + 1..]),
+        None => ("", path),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir)
+    }) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // hidden files only when asked for
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let separator = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, separator))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn common_prefix(paths: &[String]) -> String {
+    let Some(first) = paths.first() else {
+        return String::new();
+    };
+    let mut end = first.len();
+    for path in &paths[1..] {
+        end = first
+            .char_indices()
+            .zip(path.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(end);
+    }
+    first[..end].to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::export::{common_prefix, complete_path, ExportDialog, ExportKind};
+    use crate::test_dir::TestDir;
+    use std::fs;
+
+    #[test]
+    fn test_complete_path() {
+        let test_dir = TestDir::new("export");
+        fs::create_dir(test_dir.join("orders")).unwrap();
+        fs::write(test_dir.join("order_item.sql"), "").unwrap();
+        fs::write(test_dir.join("user.sql"), "").unwrap();
+        fs::write(test_dir.join(".hidden"), "").unwrap();
+        let dir = format!("{}/", test_dir.path().display());
+
+        assert_eq!(
+            vec![format!("{}order_item.sql", dir), format!("{}orders/", dir)],
+            complete_path(&format!("{}or", dir))
+        );
+        assert_eq!(3, complete_path(&dir).len());
+        assert_eq!(
+            vec![format!("{}.hidden", dir)],
+            complete_path(&format!("{}.", dir))
+        );
+        assert!(complete_path("/no/such/dir/x").is_empty());
+
+        let mut dialog = ExportDialog {
+            path: format!("{}o", dir),
+            ..ExportDialog::default()
+        };
+        dialog.complete();
+        assert_eq!(format!("{}order", dir), dialog.path);
+        dialog.complete();
+        assert_eq!(format!("{}order_item.sql", dir), dialog.path);
+        dialog.complete();
+        assert_eq!(format!("{}orders/", dir), dialog.path);
+        dialog.path_char('x');
+        assert!(dialog.completions.is_empty());
+        dialog.path = format!("{}u", dir);
+        dialog.complete();
+        assert_eq!(format!("{}user.sql", dir), dialog.path);
+
+        assert_eq!("", common_prefix(&[]));
+        assert_eq!(
+            "ab",
+            common_prefix(&[String::from("abc"), String::from("abd")])
+        );
+    }
+
+    #[test]
+    fn test_export_dialog() {
+        let mut dialog = ExportDialog::default();
+        dialog.select_by(2);
+        assert_eq!(ExportKind::Json, dialog.kind);
+        assert_eq!("statements.json", dialog.path);
+        dialog.select_by(10);
+        assert_eq!(ExportKind::Session, dialog.kind);
+        dialog.path = String::from("mine.json");
+        dialog.select_by(-10);
+        assert_eq!(ExportKind::Result, dialog.kind);
+        assert_eq!("mine.json", dialog.path);
+    }
+}
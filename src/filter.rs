@@ -0,0 +1,221 @@
+use crate::analysis::tables;
+use crate::app::StatementItem;
+use crate::output::Record;
+use std::str::FromStr;
+
+/// Statements to keep, e.g. `table:order_item type:update -thread:main "order by"`.
+///
+/// Every term has to match. A term is `field:text` or just `text` for any field, `-` in front
+/// keeps the statements it does not match and quotes keep spaces in the text. Texts are found
+/// anywhere in the field, ignoring case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    field: Option<Field>,
+    /// In lower case
+    text: String,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// Any table after FROM, INTO, UPDATE or JOIN
+    Table,
+    /// The first keyword, e.g. `select`
+    Type,
+    Thread,
+    /// The sql, with or without its placeholders replaced
+    Text,
+}
+
+/// What a filter looks at in a statement.
+pub struct Fields<'a> {
+    pub sql: &'a str,
+    pub bound: &'a str,
+    pub kind: &'a str,
+    pub tables: &'a [String],
+    pub thread: Option<&'a str>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn of_item(item: &'a StatementItem) -> Fields<'a> {
+        Fields {
+            sql: &item.statement.sql,
+            bound: &item.bound,
+            kind: &item.kind,
+            tables: &item.tables,
+            thread: item.statement.thread.as_deref(),
+        }
+    }
+
+    /// The fields of a record, its tables are found with `tables`.
+    pub fn of_record(record: &'a Record, tables: &'a [String]) -> Fields<'a> {
+        Fields {
+            sql: &record.template,
+            bound: &record.bound,
+            kind: &record.kind,
+            tables,
+            thread: record.thread.as_deref(),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+        for word in words(text) {
+            let (negated, word) = match word.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, word.as_str()),
+            };
+            // a quoted word is text only, so it may contain a colon
+            let (field, text) = match word.split_once(':') {
+                Some((name, text))
+                    if !word.starts_with('"')
+                        && !name.is_empty()
+                        && name.chars().all(|c| c.is_ascii_alphabetic()) =>
+                {
+                    (Some(field(name)?), text)
+                }
+                _ => (None, word),
+            };
+            let text = text.replace('"', "").to_lowercase();
+            // `table:` while typing keeps everything rather than nothing
+            if !text.is_empty() {
+                terms.push(Term {
+                    field,
+                    text,
+                    negated,
+                });
+            }
+        }
+        Ok(Filter { terms })
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, fields: &Fields) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.matches(fields) != term.negated)
+    }
+
+    /// Whether a record matches, looking up its tables only when a term needs them.
+    pub fn matches_record(&self, record: &Record) -> bool {
+        let tables = if self.needs_tables() {
+            tables(&record.template)
+        } else {
+            Vec::new()
+        };
+        self.matches(&Fields::of_record(record, &tables))
+    }
+
+    /// Whether a term looks at the tables, `table:` ones and those of no field.
+    fn needs_tables(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|term| matches!(term.field, Some(Field::Table) | None))
+    }
+}
+
+impl Term {
+    fn matches(&self, fields: &Fields) -> bool {
+        let contains = |text: &str| text.to_lowercase().contains(&self.text);
+        let table = || fields.tables.iter().any(|table| contains(table));
+        let kind = || contains(fields.kind);
+        let thread = || fields.thread.is_some_and(contains);
+        let text = || contains(fields.sql) || contains(fields.bound);
+        match self.field {
+            Some(Field::Table) => table(),
+            Some(Field::Type) => kind(),
+            Some(Field::Thread) => thread(),
+            Some(Field::Text) => text(),
+            None => table() || kind() || thread() || text(),
+        }
+    }
+}
+
+fn field(name: &str) -> Result<Field, String> {
+    match name.to_lowercase().as_str() {
+        "table" => Ok(Field::Table),
+        "type" | "kind" => Ok(Field::Type),
+        "thread" => Ok(Field::Thread),
+        "text" | "sql" => Ok(Field::Text),
+        _ => Err(format!(
+            "unknown filter field '{}', expected table, type, thread or text",
+            name
+        )),
+    }
+}
+
+/// Splits at whitespace outside of double quotes, keeping the quotes.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for char in text.chars() {
+        if char.is_whitespace() && !quoted {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if char == '"' {
+            quoted = !quoted;
+        }
+        word.push(char);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use crate::filter::{Fields, Filter};
+
+    #[test]
+    fn test_filter() {
+        let tables = vec![String::from("orders"), String::from("order_item")];
+        let fields = Fields {
+            sql: "SELECT * FROM orders o JOIN order_item i ON i.order_id = o.id WHERE o.id = ?",
+            bound: "SELECT * FROM orders o JOIN order_item i ON i.order_id = o.id WHERE o.id = 42",
+            kind: "SELECT",
+            tables: &tables,
+            thread: Some("http-nio-8080-exec-1"),
+        };
+        let matches = |text: &str| text.parse::<Filter>().unwrap().matches(&fields);
+        assert!(matches(""));
+        assert!(matches("table:order_item"));
+        assert!(matches("TABLE:Order_Item type:select"));
+        assert!(matches("kind:sel thread:exec-1"));
+        assert!(matches("text:42 table:"));
+        assert!(matches("-table:user"));
+        assert!(matches("\"o.id = 42\""));
+        assert!(matches("exec"));
+        assert!(!matches("table:order_item type:update"));
+        assert!(!matches("-thread:exec"));
+        assert!(!matches("text:user"));
+        assert!(!matches("\"table:orders\""));
+        assert_eq!(
+            Err(String::from(
+                "unknown filter field 'tabel', expected table, type, thread or text"
+            )),
+            "tabel:orders".parse::<Filter>()
+        );
+        assert!("12:30".parse::<Filter>().is_ok());
+
+        let needs_tables = |text: &str| text.parse::<Filter>().unwrap().needs_tables();
+        assert!(!needs_tables(""));
+        assert!(!needs_tables("type:select -thread:exec text:42"));
+        assert!(needs_tables("type:select orders"));
+        assert!(needs_tables("-table:user"));
+    }
+}
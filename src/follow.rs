@@ -0,0 +1,165 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Reads the lines appended to a log file, like `tail -F`.
+///
+/// When the file is rotated (renamed and recreated) the rest of the old file is read
+/// before switching to the new one, when it is truncated reading restarts at the top.
+pub struct Tailer {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+    file_id: Option<(u64, u64)>,
+    position: u64,
+    partial: Vec<u8>,
+}
+
+impl Tailer {
+    pub fn open(path: PathBuf, from_start: bool) -> io::Result<Tailer> {
+        let file = File::open(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        let file_id = file_id(&file.metadata()?);
+        let mut reader = BufReader::new(file);
+        let position = if from_start {
+            0
+        } else {
+            reader.seek(SeekFrom::End(0))?
+        };
+        Ok(Tailer {
+            path,
+            reader: Some(reader),
+            file_id,
+            position,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Calls `f` for every complete line appended since the last call.
+    pub fn read_lines(&mut self, mut f: impl FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+        self.drain(&mut f)?;
+
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            // rotated away and not recreated yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if self.reader.is_none() || file_id(&metadata) != self.file_id {
+            if let Ok(file) = File::open(&self.path) {
+                self.file_id = file_id(&file.metadata()?);
+                self.reader = Some(BufReader::new(file));
+                self.position = 0;
+                self.partial.clear();
+                self.drain(&mut f)?;
+            }
+        } else if metadata.len() < self.position {
+            if let Some(reader) = self.reader.as_mut() {
+                reader.seek(SeekFrom::Start(0))?;
+            }
+            self.position = 0;
+            self.partial.clear();
+            self.drain(&mut f)?;
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self, f: &mut impl FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+        let Some(reader) = self.reader.as_mut() else {
+            return Ok(());
+        };
+        loop {
+            let read = reader.read_until(b'\n', &mut self.partial)?;
+            if read == 0 {
+                return Ok(());
+            }
+            self.position += read as u64;
+            // keep an incomplete last line until the writer finishes it
+            if self.partial.last() != Some(&b'\n') {
+                return Ok(());
+            }
+            let line = String::from_utf8_lossy(&self.partial).into_owned();
+            self.partial.clear();
+            f(line.trim_end_matches(['\n', '\r']))?;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Follows all `paths` until the process is interrupted, calling `f` for every new line.
+pub fn follow(
+    paths: Vec<PathBuf>,
+    from_start: bool,
+    mut f: impl FnMut(&str) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut tailers = paths
+        .into_iter()
+        .map(|path| Tailer::open(path, from_start))
+        .collect::<io::Result<Vec<_>>>()?;
+    loop {
+        for tailer in tailers.iter_mut() {
+            tailer.read_lines(&mut f)?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::follow::Tailer;
+    use crate::test_dir::TestDir;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn test_tail_rotation_and_truncation() {
+        let dir = TestDir::new("follow");
+        let path = dir.join("app.log");
+        fs::write(&path, "old\n").unwrap();
+
+        let mut tailer = Tailer::open(path.clone(), false).unwrap();
+        let mut lines = Vec::new();
+        let read = |tailer: &mut Tailer, lines: &mut Vec<String>| {
+            tailer
+                .read_lines(|line| {
+                    lines.push(line.to_string());
+                    Ok(())
+                })
+                .unwrap()
+        };
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"first\nsec").unwrap();
+        read(&mut tailer, &mut lines);
+        file.write_all(b"ond\n").unwrap();
+        read(&mut tailer, &mut lines);
+
+        fs::rename(&path, dir.join("app.log.1")).unwrap();
+        file.write_all(b"last of old\n").unwrap();
+        fs::write(&path, "new\n").unwrap();
+        read(&mut tailer, &mut lines);
+
+        fs::write(&path, "").unwrap();
+        read(&mut tailer, &mut lines);
+        fs::write(&path, "after truncate\n").unwrap();
+        read(&mut tailer, &mut lines);
+
+        assert_eq!(
+            vec!["first", "second", "last of old", "new", "after truncate"],
+            lines
+        );
+    }
+}
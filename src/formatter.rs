@@ -0,0 +1,198 @@
+use crate::lexer::{tokenize, Token, TokenKind};
+
+const INDENT: &str = "  ";
+
+/// Keywords that start a clause on a new line.
+#[rustfmt::skip]
+const CLAUSES: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "GROUP", "ORDER", "HAVING", "LIMIT", "OFFSET", "FETCH", "UNION",
+    "VALUES", "SET", "INSERT", "UPDATE", "DELETE", "MERGE", "RETURNING", "LEFT", "RIGHT",
+    "INNER", "FULL", "CROSS", "JOIN", "WITH",
+];
+
+/// Clauses whose comma-separated items go on their own lines.
+const LIST_CLAUSES: &[&str] = &["SELECT", "SET", "GROUP", "ORDER"];
+
+/// Pretty-prints sql: one clause per line, keywords in upper case, select/set lists and
+/// AND/OR conditions one per line, subqueries indented. Comments and literals are kept.
+pub fn format_sql(sql: &str) -> String {
+    let tokens: Vec<Token> = tokenize(sql);
+    let mut out = String::new();
+    // the clause being written and the indent of its lines, for every subquery depth
+    let mut clauses: Vec<(String, usize)> = vec![(String::new(), 0)];
+    let mut line_indent = 0;
+    // for every open parenthesis, whether it opened a subquery
+    let mut parens: Vec<bool> = Vec::new();
+    let mut prev = String::new();
+    let mut in_between = false;
+    let mut space = false;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let text = &sql[token.start..token.end];
+        let depth = clauses.len() - 1;
+        let base = clauses[depth].1;
+        let in_list_parens = parens.last() == Some(&false);
+        match token.kind {
+            TokenKind::Whitespace => {
+                space = true;
+                continue;
+            }
+            TokenKind::Keyword => {
+                let keyword = text.to_uppercase();
+                let joined = matches!(
+                    (prev.as_str(), keyword.as_str()),
+                    ("DELETE", "FROM")
+                        | ("INSERT", "INTO")
+                        | ("KEY", "UPDATE")
+                        | ("UNION", _)
+                        | (
+                            "LEFT" | "RIGHT" | "INNER" | "FULL" | "CROSS" | "OUTER",
+                            "JOIN"
+                        )
+                        | ("LEFT" | "RIGHT" | "FULL", "OUTER")
+                        | ("(", "SELECT")
+                );
+                let is_function = next_text(sql, &tokens, i) == Some("(")
+                    && matches!(keyword.as_str(), "LEFT" | "RIGHT" | "REPLACE");
+                if CLAUSES.contains(&keyword.as_str()) && !joined && !is_function {
+                    line_indent = new_line(&mut out, base);
+                    clauses[depth].0 = keyword.clone();
+                } else if keyword == "BETWEEN" {
+                    in_between = true;
+                    push(&mut out, &keyword, space);
+                    prev = keyword;
+                    space = false;
+                    continue;
+                } else if keyword == "AND" && in_between {
+                    in_between = false;
+                } else if (keyword == "AND" || keyword == "OR")
+                    && !in_list_parens
+                    && clauses[depth].0 != "SELECT"
+                {
+                    line_indent = new_line(&mut out, base + 1);
+                } else if joined && keyword == "SELECT" {
+                    line_indent = new_line(&mut out, base);
+                    clauses[depth].0 = keyword.clone();
+                }
+                push(&mut out, &keyword, space);
+                prev = keyword;
+            }
+            TokenKind::Punctuation if text == "(" => {
+                push(&mut out, text, space);
+                let subquery = next_text(sql, &tokens, i)
+                    .is_some_and(|next| next.eq_ignore_ascii_case("SELECT"));
+                parens.push(subquery);
+                if subquery {
+                    clauses.push((String::new(), line_indent + 1));
+                }
+                prev = String::from("(");
+            }
+            TokenKind::Punctuation if text == ")" => {
+                if parens.pop() == Some(true) && clauses.len() > 1 {
+                    clauses.pop();
+                    line_indent = new_line(&mut out, base - 1);
+                }
+                out.push(')');
+                prev = String::from(")");
+            }
+            TokenKind::Punctuation if text == "," => {
+                out.push(',');
+                if LIST_CLAUSES.contains(&clauses[depth].0.as_str()) && !in_list_parens {
+                    line_indent = new_line(&mut out, base + 1);
+                }
+                prev = String::from(",");
+            }
+            TokenKind::Punctuation if text == ";" => {
+                out.push(';');
+                out.push('\n');
+                clauses = vec![(String::new(), 0)];
+                parens.clear();
+                line_indent = 0;
+                prev = String::from(";");
+            }
+            TokenKind::Comment => {
+                push(&mut out, text, space);
+                if text.starts_with("--") {
+                    line_indent = new_line(&mut out, line_indent);
+                }
+            }
+            _ => {
+                push(&mut out, text, space);
+                prev = text.to_uppercase();
+            }
+        }
+        space = false;
+    }
+    out.trim().to_string()
+}
+
+fn next_text<'a>(sql: &'a str, tokens: &[Token], i: usize) -> Option<&'a str> {
+    tokens[i + 1..]
+        .iter()
+        .find(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .map(|token| &sql[token.start..token.end])
+}
+
+fn push(out: &mut String, text: &str, space: bool) {
+    let at_line_start = out.is_empty() || out.ends_with('\n') || out.ends_with(INDENT);
+    if space && !at_line_start {
+        out.push(' ');
+    }
+    out.push_str(text);
+}
+
+/// Starts a new line unless the current one is empty, returns `indent`.
+fn new_line(out: &mut String, indent: usize) -> usize {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    if !out.is_empty() {
+        out.push_str(&INDENT.repeat(indent));
+    }
+    indent
+}
+
+#[cfg(test)]
+mod test {
+    use crate::formatter::format_sql;
+
+    #[test]
+    fn test_format_select() {
+        let sql = "select u.id, u.name from user u left join `order` o on o.user_id = u.id \
+            where u.age between 18 and 30 and o.id in (select id from order_item where price > 0) \
+            or u.name = 'a, b' order by u.id desc limit 10";
+        assert_eq!(
+            "SELECT u.id,
+  u.name
+FROM user u
+LEFT JOIN `order` o ON o.user_id = u.id
+WHERE u.age BETWEEN 18 AND 30
+  AND o.id IN (
+    SELECT id
+    FROM order_item
+    WHERE price > 0
+  )
+  OR u.name = 'a, b'
+ORDER BY u.id DESC
+LIMIT 10",
+            format_sql(sql)
+        );
+    }
+
+    #[test]
+    fn test_format_update_and_insert() {
+        assert_eq!(
+            "UPDATE user
+SET name = ?,
+  age = ?
+WHERE id = ?;
+INSERT INTO user (name, age)
+VALUES (?, ?);",
+            format_sql(
+                "UPDATE user SET name = ?, age = ? WHERE id = ?; INSERT INTO user (name, age) VALUES (?, ?);"
+            )
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::keymap::{Command, KeyBinding, Keybindings, Keymap, Profile};
+
+/// A section of the help: its title and the keys with what they do.
+pub struct HelpSection {
+    pub title: &'static str,
+    pub keys: Vec<(String, String)>,
+}
+
+/// Keys of the vim normal mode, they are not part of the editor keymap.
+const VIM_KEYS: [(&str, &str); 15] = [
+    ("h j k l", "Move"),
+    ("w b e", "Next word, previous word, end of the word"),
+    ("0 ^ $", "Start or end of the line"),
+    ("gg G", "Top or bottom"),
+    ("i a I A o O", "Insert mode"),
+    ("v", "Visual mode"),
+    ("x", "Delete the character"),
+    ("dd yy", "Cut or copy the line"),
+    ("d y", "Cut or copy the selection in visual mode"),
+    ("p", "Paste"),
+    ("u Ctrl+r", "Undo or redo"),
+    ("Ctrl+d Ctrl+u", "Page down or up"),
+    (":", "Command line"),
+    ("?", "Show the keys"),
+    ("/ n N", "Search, next or previous match"),
+];
+
+const RESULT_KEYS: [(&str, &str); 8] = [
+    ("Up Down j k", "Move the cursor"),
+    ("Shift+Up Shift+Down", "Select lines"),
+    ("Left Right h l", "Scroll sideways"),
+    ("w", "Wrap the long lines"),
+    ("v Space", "Start or stop selecting lines"),
+    ("s", "Select the statement under the cursor"),
+    ("y Enter", "Copy the selection"),
+    ("/ n N", "Search, next or previous match"),
+];
+
+const TABLE_KEYS: [(&str, &str); 6] = [
+    ("Up Down j k", "Select a parameter"),
+    ("Left Right h l", "Select the value or the type"),
+    ("Enter e i", "Edit the cell"),
+    ("t", "Pick the type"),
+    ("n", "Set to NULL or back"),
+    ("a d", "Add or delete a parameter"),
+];
+
+const LIST_KEYS: [(&str, &str); 4] = [
+    ("Up Down PageUp PageDown", "Select a statement"),
+    ("Home End", "First or last statement"),
+    ("Enter", "Edit the statement"),
+    ("Typing", "Filter: text, table:, type:, thread:, -negated"),
+];
+
+const OTHER_KEYS: [(&str, &str); 9] = [
+    ("Typing", "Search: move to the first match"),
+    ("Down Up", "Search: next or previous match"),
+    ("Enter Esc", "Search: keep the matches marked, or go back"),
+    ("Enter", "Diagnostics: show where it points to"),
+    ("Typing", "History: search the entries"),
+    ("Enter", "History: open the entry"),
+    ("Click", "Focus the area, select a line"),
+    ("Drag", "Resize the areas by their borders"),
+    ("Wheel", "Scroll the area under the mouse"),
+];
+
+const COMMANDS: [(&str, &str); 17] = [
+    ("w <file>", "Write the result"),
+    ("wq <file>", "Write the result and exit"),
+    ("q", "Exit"),
+    ("yank", "Copy the result"),
+    ("format", "Format the sql"),
+    ("edit", "Edit the focused area in $EDITOR"),
+    ("export", "Export to a file"),
+    ("session save|open <file>", "Save the session or open one"),
+    ("dialect <name>", "Switch the dialect"),
+    ("layout [name]", "Switch the layout"),
+    ("zoom", "Show the focused area alone"),
+    ("history", "Open the history"),
+    ("watch", "Watch the clipboard"),
+    ("clear", "Clear the inputs"),
+    ("help", "Show the keys"),
+    ("palette", "Open the command palette"),
+    ("noh", "Stop marking the search matches"),
+];
+
+/// Every key of the active profile and keymap, then the commands of the command line.
+pub fn help_sections(keybindings: &Keybindings, keymap: &Keymap) -> Vec<HelpSection> {
+    let owned = |keys: &[(&str, &str)]| {
+        keys.iter()
+            .map(|(key, text)| (key.to_string(), text.to_string()))
+            .collect()
+    };
+    let mut sections = vec![HelpSection {
+        title: "Anywhere",
+        keys: keybindings
+            .commands()
+            .iter()
+            .map(|(key, command)| (key.to_string(), command.title()))
+            .chain(
+                [("?", Command::Help), ("/", Command::Search)].map(|(key, command)| {
+                    (
+                        String::from(key),
+                        format!("{}, outside the Sql and Value areas", command.title()),
+                    )
+                }),
+            )
+            .collect(),
+    }];
+    if keybindings.profile == Profile::Vim {
+        sections.push(HelpSection {
+            title: "Sql and Value, normal mode",
+            keys: owned(&VIM_KEYS),
+        });
+    }
+    sections.push(HelpSection {
+        title: match keybindings.profile {
+            Profile::Vim => "Sql and Value, insert mode",
+            Profile::Default | Profile::Emacs => "Sql and Value",
+        },
+        keys: keymap
+            .bindings()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| (join(keys), words(&format!("{:?}", action))))
+            .collect(),
+    });
+    sections.extend([
+        HelpSection {
+            title: "Result",
+            keys: owned(&RESULT_KEYS),
+        },
+        HelpSection {
+            title: "Parameter table",
+            keys: owned(&TABLE_KEYS),
+        },
+        HelpSection {
+            title: "Statements",
+            keys: owned(&LIST_KEYS),
+        },
+        HelpSection {
+            title: "Other areas and the mouse",
+            keys: owned(&OTHER_KEYS),
+        },
+        HelpSection {
+            title: "Commands",
+            keys: owned(&COMMANDS),
+        },
+    ]);
+    sections
+}
+
+fn join(keys: &[KeyBinding]) -> String {
+    keys.iter()
+        .map(KeyBinding::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `SelectWordLeft` as "Select word left".
+fn words(name: &str) -> String {
+    let mut text = String::new();
+    for char in name.chars() {
+        if char.is_uppercase() && !text.is_empty() {
+            text.push(' ');
+            text.extend(char.to_lowercase());
+        } else {
+            text.push(char);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::Config;
+    use crate::help::{help_sections, words};
+
+    #[test]
+    fn test_help_sections() {
+        let config =
+            Config::parse("[keybindings]\nprofile = \"vim\"\npalette = \"alt+p\"").unwrap();
+        let keybindings = &config.keybindings;
+        let sections = help_sections(keybindings, &keybindings.editor_keymap());
+        assert_eq!("Anywhere", sections[0].title);
+        assert!(sections[0].keys.contains(&(
+            String::from("Alt+p"),
+            String::from("Open the command palette")
+        )));
+        assert_eq!("Sql and Value, normal mode", sections[1].title);
+        assert!(sections[2].keys.contains(&(
+            String::from("Ctrl+Shift+Left"),
+            String::from("Select word left")
+        )));
+        assert_eq!("Select word left", words("SelectWordLeft"));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::lexer::{tokenize, TokenKind};
+use crate::theme::Theme;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+fn token_style(kind: TokenKind, theme: &Theme) -> Style {
+    match kind {
+        TokenKind::Keyword => theme.keyword,
+        TokenKind::String => theme.string,
+        TokenKind::Number => theme.number,
+        TokenKind::Comment => theme.comment,
+        TokenKind::QuotedIdentifier => theme.quoted_identifier,
+        TokenKind::Placeholder(_) => theme.placeholder,
+        TokenKind::Whitespace | TokenKind::Identifier | TokenKind::Punctuation => Style::new(),
+    }
+}
+
+/// Splits `sql` into styled lines with the same tokens the binder sees. `marks` are byte
+/// ranges styled as given whatever tokens they contain, e.g. substituted values.
+pub fn highlight(sql: &str, marks: &[(Range<usize>, Style)], theme: &Theme) -> Vec<Line<'static>> {
+    let tokens = tokenize(sql)
+        .into_iter()
+        .map(|token| (token.start..token.end, token_style(token.kind, theme)));
+    styled_lines(sql, tokens, marks)
+}
+
+/// Splits `text` into lines without highlighting other than `marks`.
+pub fn mark(text: &str, marks: &[(Range<usize>, Style)]) -> Vec<Line<'static>> {
+    styled_lines(text, [(0..text.len(), Style::new())].into_iter(), marks)
+}
+
+fn styled_lines(
+    text: &str,
+    pieces: impl Iterator<Item = (Range<usize>, Style)>,
+    marks: &[(Range<usize>, Style)],
+) -> Vec<Line<'static>> {
+    let marks = layered(marks);
+    // the pieces come in order, so the marks are walked once alongside them
+    let mut next = 0;
+    let mut lines = vec![Line::default()];
+    for (piece, piece_style) in pieces {
+        let mut start = piece.start;
+        while start < piece.end {
+            while marks.get(next).is_some_and(|(range, _)| range.end <= start) {
+                next += 1;
+            }
+            let (end, style) = match marks.get(next) {
+                Some((range, style)) if range.start <= start => (range.end.min(piece.end), *style),
+                Some((range, _)) => (range.start.min(piece.end), piece_style),
+                None => (piece.end, piece_style),
+            };
+            for (i, part) in text[start..end].split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Line::default());
+                }
+                if !part.is_empty() {
+                    let line = lines.last_mut().unwrap();
+                    line.push_span(Span::styled(part.trim_end_matches('\r').to_string(), style));
+                }
+            }
+            start = end;
+        }
+    }
+    lines
+}
+
+/// `marks` as ranges sorted by start that do not overlap, where marks overlap the one given
+/// first is kept.
+fn layered(marks: &[(Range<usize>, Style)]) -> Vec<(Range<usize>, Style)> {
+    let mut bounds: Vec<(usize, bool, usize)> = marks
+        .iter()
+        .enumerate()
+        .filter(|(_, (range, _))| !range.is_empty())
+        .flat_map(|(index, (range, _))| [(range.start, true, index), (range.end, false, index)])
+        .collect();
+    bounds.sort_unstable();
+    // the marks covering the offset reached, the first given is the smallest
+    let mut covering = BTreeSet::new();
+    let mut layered: Vec<(Range<usize>, usize)> = Vec::new();
+    for (position, &(offset, starts, index)) in bounds.iter().enumerate() {
+        if starts {
+            covering.insert(index);
+        } else {
+            covering.remove(&index);
+        }
+        let until = bounds.get(position + 1).map_or(offset, |bound| bound.0);
+        let Some(&top) = covering.first().filter(|_| until > offset) else {
+            continue;
+        };
+        match layered.last_mut() {
+            Some((range, last)) if range.end == offset && *last == top => range.end = until,
+            _ => layered.push((offset..until, top)),
+        }
+    }
+    layered
+        .into_iter()
+        .map(|(range, index)| (range, marks[index].1))
+        .collect()
+}
+
+/// Styled lines kept between frames while the text and the marks stay the same.
+#[derive(Debug, Default)]
+pub struct LineCache {
+    key: u64,
+    lines: Vec<Line<'static>>,
+}
+
+impl LineCache {
+    /// The lines for `key` of `text_key`, styled again by `style` when the key changed.
+    pub fn get(
+        &mut self,
+        key: u64,
+        style: impl FnOnce() -> Vec<Line<'static>>,
+    ) -> &[Line<'static>] {
+        if self.key != key || self.lines.is_empty() {
+            self.lines = style();
+            self.key = key;
+        }
+        &self.lines
+    }
+}
+
+/// Key of a text by its `generation`, which moves whenever the text changes, and of its marks.
+/// The text itself is not read, so that large ones cost nothing while they stay the same.
+pub fn text_key(generation: u64, marks: &[(Range<usize>, Style)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    generation.hash(&mut hasher);
+    marks.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::{bind, Dialect};
+    use crate::highlight::{highlight, mark, text_key, LineCache};
+    use crate::theme::Theme;
+    use ratatui::style::Style;
+    use ratatui::text::Line;
+
+    fn spans(lines: Vec<Line>) -> Vec<Vec<(String, Style)>> {
+        lines
+            .into_iter()
+            .map(|line| {
+                line.spans
+                    .into_iter()
+                    .map(|span| (span.content.to_string(), span.style))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight() {
+        let theme = Theme::default();
+        let lines = spans(highlight("SELECT 'a\nb' WHERE id = ?", &[], &theme));
+        assert_eq!(2, lines.len());
+        assert_eq!(("SELECT".to_string(), theme.keyword), lines[0][0]);
+        assert_eq!(("'a".to_string(), theme.string), lines[0][2]);
+        assert_eq!(("b'".to_string(), theme.string), lines[1][0]);
+        assert_eq!(Some(&("?".to_string(), theme.placeholder)), lines[1].last());
+
+        // a substituted value is one literal even when it spans several tokens
+        let binding = bind(
+            "WHERE t = ?",
+            "2025-06-13(Timestamp)",
+            &Dialect::Oracle.into(),
+        );
+        let literals: Vec<_> = binding
+            .placeholders
+            .into_iter()
+            .filter_map(|placeholder| Some((placeholder.result?, theme.literal)))
+            .collect();
+        let lines = spans(highlight(&binding.result, &literals, &theme));
+        assert_eq!(
+            Some(&("TIMESTAMP".to_string(), theme.literal)),
+            lines[0].iter().find(|(text, _)| text == "TIMESTAMP")
+        );
+        assert!(lines[0][lines[0].len() - 2..]
+            .iter()
+            .all(|(_, style)| *style == theme.literal));
+
+        let lines = spans(mark("1(Long),\n2(Long)", &[(9..16, theme.linked)]));
+        assert_eq!(("2(Long)".to_string(), theme.linked), lines[1][0]);
+
+        // the mark given first wins where marks overlap, also inside a longer one
+        let marks = [
+            (4..6, theme.current_match),
+            (2..8, theme.selection),
+            (0..10, theme.literal),
+        ];
+        assert_eq!(
+            vec![vec![
+                ("ab".to_string(), theme.literal),
+                ("cd".to_string(), theme.selection),
+                ("ef".to_string(), theme.current_match),
+                ("gh".to_string(), theme.selection),
+                ("ij".to_string(), theme.literal),
+                ("k".to_string(), Style::new()),
+            ]],
+            spans(mark("abcdefghijk", &marks))
+        );
+        // thousands of marks are walked once
+        let text = "?, ".repeat(5000);
+        let marks: Vec<_> = (0..5000)
+            .rev()
+            .map(|index| (index * 3..index * 3 + 1, theme.literal))
+            .collect();
+        let lines = spans(highlight(&text, &marks, &theme));
+        assert_eq!(15_000, lines[0].len());
+        assert_eq!(("?".to_string(), theme.literal), lines[0][14_997]);
+        assert_eq!((",".to_string(), Style::new()), lines[0][14_998]);
+    }
+
+    #[test]
+    fn test_line_cache() {
+        let theme = Theme::default();
+        let mut cache = LineCache::default();
+        let key = text_key(1, &[]);
+        assert_eq!(
+            2,
+            cache
+                .get(key, || highlight("SELECT 1\nFROM t", &[], &theme))
+                .len()
+        );
+        // the same key keeps the lines styled before
+        assert_eq!(2, cache.get(key, Vec::new).len());
+        assert_ne!(key, text_key(1, &[(0..1, theme.linked)]));
+        let key = text_key(2, &[]);
+        assert_eq!(
+            1,
+            cache.get(key, || highlight("SELECT 2", &[], &theme)).len()
+        );
+    }
+}
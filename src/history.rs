@@ -0,0 +1,413 @@
+use crate::config::HistoryConfig;
+use crate::output::{csv_field, OutputFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A converted statement as kept in the history file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch
+    pub time: u64,
+    /// What converted it, `tui` or `bind`
+    pub source: String,
+    /// Log format the statement was read with, `manual` when it was typed
+    pub format: String,
+    pub sql: String,
+    pub value: String,
+    pub bound: String,
+}
+
+impl HistoryEntry {
+    pub fn new(source: &str, format: &str, sql: &str, value: &str, bound: &str) -> HistoryEntry {
+        HistoryEntry {
+            time: now(),
+            source: source.to_string(),
+            format: format.to_string(),
+            sql: sql.to_string(),
+            value: value.to_string(),
+            bound: bound.to_string(),
+        }
+    }
+
+    /// Whether every word of `query` is found in the entry, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let text = format!(
+            "{} {} {} {} {}",
+            self.source, self.format, self.sql, self.value, self.bound
+        )
+        .to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| text.contains(word))
+    }
+
+    /// Entries of the same conversion have the same key.
+    fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (&self.sql, &self.bound).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// The history file, one json entry per line, oldest first.
+pub struct History {
+    /// `None` when the history is turned off
+    path: Option<PathBuf>,
+    limit: usize,
+    /// The thread appending the added entries, started by the first one
+    writer: Option<(Sender<Vec<HistoryEntry>>, JoinHandle<()>)>,
+}
+
+impl History {
+    pub fn new(config: &HistoryConfig) -> History {
+        let path = config.path.clone().or_else(|| {
+            crate::config::data_dir().map(|dir| dir.join("sql-param").join("history.jsonl"))
+        });
+        History {
+            path: path.filter(|_| config.enabled),
+            limit: config.limit,
+            writer: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Appends the entries not in the history yet on a thread of its own, so converting never
+    /// waits for the file. The history is a convenience, failing to write it is ignored.
+    pub fn add(&mut self, entries: Vec<HistoryEntry>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let (sender, _) = self.writer.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            let (path, limit) = (path.clone(), self.limit);
+            let writer = thread::spawn(move || {
+                let _ = write_entries(&path, limit, receiver);
+            });
+            (sender, writer)
+        });
+        let _ = sender.send(entries);
+    }
+
+    /// Waits until the entries added so far are written.
+    fn flush(&mut self) {
+        if let Some((sender, writer)) = self.writer.take() {
+            drop(sender);
+            let _ = writer.join();
+        }
+    }
+
+    /// All entries, oldest first. Lines that cannot be read are skipped.
+    pub fn entries(&mut self) -> io::Result<Vec<HistoryEntry>> {
+        self.flush();
+        match &self.path {
+            Some(path) => read_entries(path),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Removes the entries older than `max_age` seconds and all but the last `keep` ones,
+    /// returns how many were removed. Entries a running tui appends meanwhile are kept.
+    pub fn prune(&mut self, keep: Option<usize>, max_age: Option<u64>) -> io::Result<usize> {
+        self.flush();
+        let Some(path) = &self.path else {
+            return Ok(0);
+        };
+        let (entries, read) = read_entries_from(path, 0)?;
+        let oldest = max_age.map_or(0, |age| now().saturating_sub(age));
+        let mut kept: Vec<HistoryEntry> = entries
+            .iter()
+            .filter(|entry| entry.time >= oldest)
+            .cloned()
+            .collect();
+        if let Some(keep) = keep {
+            kept.drain(..kept.len().saturating_sub(keep));
+        }
+        let removed = entries.len() - kept.len();
+        replace_entries(path, read, kept)?;
+        Ok(removed)
+    }
+}
+
+impl Drop for History {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Appends the entries received to the file at `path`, those of a conversion already in it
+/// are skipped. The file is read once, then again only to trim it to `limit` entries.
+fn write_entries(
+    path: &Path,
+    limit: usize,
+    receiver: Receiver<Vec<HistoryEntry>>,
+) -> io::Result<()> {
+    let mut keys: HashSet<u64> = read_entries(path)?.iter().map(HistoryEntry::key).collect();
+    let mut count = keys.len();
+    for entries in receiver {
+        let added: Vec<HistoryEntry> = entries
+            .into_iter()
+            .filter(|entry| keys.insert(entry.key()))
+            .collect();
+        if added.is_empty() {
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        for entry in &added {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        file.flush()?;
+        count += added.len();
+        // some room over the limit so the file is not rewritten on every conversion
+        if limit > 0 && count > limit + limit / 10 {
+            let (mut entries, read) = read_entries_from(path, 0)?;
+            entries.drain(..entries.len().saturating_sub(limit));
+            let entries = replace_entries(path, read, entries)?;
+            keys = entries.iter().map(HistoryEntry::key).collect();
+            count = entries.len();
+        }
+    }
+    Ok(())
+}
+
+fn read_entries(path: &Path) -> io::Result<Vec<HistoryEntry>> {
+    read_entries_from(path, 0).map(|(entries, _)| entries)
+}
+
+/// The entries from byte `start` on and where the last whole line ends, a line still being
+/// written is left for the next read.
+fn read_entries_from(path: &Path, start: u64) -> io::Result<(Vec<HistoryEntry>, u64)> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), start)),
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(start))?;
+    let mut reader = BufReader::new(file);
+    let (mut entries, mut end, mut line) = (Vec::new(), start, String::new());
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        end += read as u64;
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok((entries, end))
+}
+
+/// Replaces the file with `kept`, the entries of the first `read` bytes that stay, followed by
+/// those appended after them since, so a rewrite never loses what another process added.
+fn replace_entries(
+    path: &Path,
+    read: u64,
+    mut kept: Vec<HistoryEntry>,
+) -> io::Result<Vec<HistoryEntry>> {
+    let (appended, _) = read_entries_from(path, read)?;
+    kept.extend(appended);
+    write_file(path, &kept)?;
+    Ok(kept)
+}
+
+fn write_file(path: &Path, entries: &[HistoryEntry]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // written next to the file and renamed, a crash never leaves half a history
+    let temporary = path.with_extension("jsonl.tmp");
+    let mut out = BufWriter::new(File::create(&temporary)?);
+    for entry in entries {
+        writeln!(out, "{}", serde_json::to_string(entry)?)?;
+    }
+    out.flush()?;
+    fs::rename(temporary, path)
+}
+
+/// Prints `entries` in the given format, the sql one keeps them runnable with a comment above.
+pub fn write(
+    out: &mut impl Write,
+    entries: &[&HistoryEntry],
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Sql => {
+            for entry in entries {
+                writeln!(
+                    out,
+                    "-- {} {} {}",
+                    format_time(entry.time),
+                    entry.source,
+                    entry.format
+                )?;
+                writeln!(out, "{}", entry.bound)?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, entries)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for entry in entries {
+                serde_json::to_writer(&mut *out, entry)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "time,source,format,sql,value,bound")?;
+            for entry in entries {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{}",
+                    format_time(entry.time),
+                    csv_field(&entry.source),
+                    csv_field(&entry.format),
+                    csv_field(&entry.sql),
+                    csv_field(&entry.value),
+                    csv_field(&entry.bound)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// `2025-06-28 20:18:06` in UTC.
+pub fn format_time(time: u64) -> String {
+    let (days, seconds) = (time / 86_400, time % 86_400);
+    // days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::HistoryConfig;
+    use crate::history::{
+        format_time, read_entries, read_entries_from, replace_entries, History, HistoryEntry,
+    };
+    use crate::test_dir::TestDir;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    #[test]
+    fn test_history() {
+        let dir = TestDir::new("history");
+        let mut history = History::new(&HistoryConfig {
+            enabled: true,
+            limit: 10,
+            path: Some(dir.join("history.jsonl")),
+        });
+        for id in 0..11 {
+            let sql = format!("SELECT * FROM user WHERE id = {}", id);
+            let entry = HistoryEntry::new(
+                "tui",
+                "mybatis",
+                "SELECT * FROM user WHERE id = ?",
+                "",
+                &sql,
+            );
+            // repeated conversions are kept once
+            history.add(vec![entry.clone(), entry.clone()]);
+            history.add(vec![entry]);
+        }
+        let entries = history.entries().unwrap();
+        assert_eq!(11, entries.len());
+        // also when other conversions came in between, and after the file was read again
+        history.add(vec![entries[3].clone(), entries[1].clone()]);
+        assert_eq!(11, history.entries().unwrap().len());
+
+        let entry = HistoryEntry::new("bind", "argument", "DELETE FROM t", "", "DELETE FROM t");
+        history.add(vec![entry]);
+        let entries = history.entries().unwrap();
+        assert_eq!(10, entries.len());
+        assert_eq!("SELECT * FROM user WHERE id = 2", entries[0].bound);
+        assert!(entries[9].matches("delete ARGUMENT"));
+        assert!(!entries[9].matches("delete user"));
+
+        assert_eq!(8, history.prune(Some(2), None).unwrap());
+        assert_eq!(2, history.entries().unwrap().len());
+    }
+
+    #[test]
+    fn test_replace_entries() {
+        let dir = TestDir::new("history_replace");
+        let path = dir.join("history.jsonl");
+        let mut history = History::new(&HistoryConfig {
+            enabled: true,
+            limit: 0,
+            path: Some(path.clone()),
+        });
+        for sql in ["SELECT 1", "SELECT 2"] {
+            history.add(vec![HistoryEntry::new("bind", "argument", sql, "", sql)]);
+        }
+        history.entries().unwrap();
+        let (entries, read) = read_entries_from(&path, 0).unwrap();
+        assert_eq!(2, entries.len());
+
+        // a tui appends while the history is pruned, and is in the middle of another line
+        let appended = HistoryEntry::new("tui", "mybatis", "SELECT 3", "", "SELECT 3");
+        let line = format!("{}\n", serde_json::to_string(&appended).unwrap());
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{}{{\"time\":", line).unwrap();
+        assert_eq!(
+            (vec![appended], read + line.len() as u64),
+            read_entries_from(&path, read).unwrap()
+        );
+
+        replace_entries(&path, read, entries[1..].to_vec()).unwrap();
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(
+            vec!["SELECT 2", "SELECT 3"],
+            entries
+                .iter()
+                .map(|entry| entry.sql.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!("1970-01-01 00:00:00", format_time(0));
+        assert_eq!("2025-06-28 20:18:06", format_time(1_751_141_886));
+        assert_eq!("2024-02-29 23:59:59", format_time(1_709_251_199));
+    }
+}
@@ -0,0 +1,134 @@
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Opens a log file, or stdin for `-`, decompressing gzip content on the fly.
+pub fn open(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let reader: Box<dyn Read> = if path == "-" {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path).map_err(|e| io::Error::new(e.kind(), format!("{path}: {e}")))?)
+    };
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        return Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))));
+    }
+    Ok(Box::new(reader))
+}
+
+/// Returns the rotated siblings of `path` oldest first, followed by `path` itself.
+///
+/// Both numbered (`app.log.1`, `app.log.2.gz`) and dated (`app.log-20250628.gz`,
+/// `app.log.2025-06-28`) rotations are recognised. Other suffixes, like backups (`app.log.1.bak`)
+/// or other compressions (`app.log.2.xz`), are skipped.
+pub fn with_rotated(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return Ok(vec![path.to_path_buf()]),
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut numbered: Vec<(u64, PathBuf)> = Vec::new();
+    let mut dated: Vec<(String, PathBuf)> = Vec::new();
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let Some(suffix) = file_name
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('.').or_else(|| rest.strip_prefix('-')))
+        else {
+            continue;
+        };
+        let suffix = suffix.strip_suffix(".gz").unwrap_or(suffix);
+        let rotation = suffix.starts_with(|c: char| c.is_ascii_digit())
+            && suffix
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '-' || c == '_');
+        if !rotation {
+            continue;
+        }
+        match suffix.parse::<u64>() {
+            // app.log.1 is newer than app.log.2
+            Ok(number) if suffix.len() < 8 => numbered.push((number, entry.path())),
+            _ => dated.push((suffix.to_string(), entry.path())),
+        }
+    }
+    numbered.sort_by_key(|(number, _)| std::cmp::Reverse(*number));
+    dated.sort();
+
+    let mut paths: Vec<PathBuf> = dated.into_iter().map(|(_, path)| path).collect();
+    paths.extend(numbered.into_iter().map(|(_, path)| path));
+    paths.push(path.to_path_buf());
+    Ok(paths)
+}
+
+/// Calls `f` for every line of `reader`, invalid UTF-8 is replaced instead of failing.
+pub fn for_each_line(
+    mut reader: impl BufRead,
+    mut f: impl FnMut(&str) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&buf);
+        f(line.trim_end_matches(['\n', '\r']))?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::{for_each_line, open, with_rotated};
+    use crate::test_dir::TestDir;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn test_open_gzip_and_rotated() {
+        let dir = TestDir::new("input");
+        fs::write(dir.join("app.log"), "current\n").unwrap();
+        fs::write(dir.join("app.log.1"), "yesterday\n").unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"two days ago\r\n").unwrap();
+        fs::write(dir.join("app.log.2.gz"), encoder.finish().unwrap()).unwrap();
+        fs::write(dir.join("app.log.2025-06-20"), "last week\n").unwrap();
+        fs::write(dir.join("other.log.1"), "unrelated\n").unwrap();
+        // not rotations of app.log, or not readable ones
+        for name in [
+            "app.log.1.bak",
+            "app.log.1.swp",
+            "app.log.2.xz",
+            "app.log.3.zst",
+        ] {
+            fs::write(dir.join(name), "skipped\n").unwrap();
+        }
+
+        let mut lines = Vec::new();
+        for path in with_rotated(&dir.join("app.log")).unwrap() {
+            let reader = open(path.to_str().unwrap()).unwrap();
+            for_each_line(reader, |line| {
+                lines.push(line.to_string());
+                Ok(())
+            })
+            .unwrap();
+        }
+
+        assert_eq!(
+            vec!["last week", "two days ago", "yesterday", "current"],
+            lines
+        );
+    }
+}
@@ -0,0 +1,614 @@
+use crate::app::AreaEnum;
+use crate::core::Dialect;
+use crate::layout::LayoutPreset;
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// What a key does in the Sql and Value panes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    /// To the end of the word, vim's `e`
+    WordEnd,
+    LineStart,
+    LineEnd,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    SelectLeft,
+    SelectRight,
+    SelectUp,
+    SelectDown,
+    SelectWordLeft,
+    SelectWordRight,
+    SelectLineStart,
+    SelectLineEnd,
+    SelectPageUp,
+    SelectPageDown,
+    SelectTop,
+    SelectBottom,
+    SelectAll,
+    Newline,
+    Backspace,
+    Delete,
+    DeleteWordLeft,
+    DeleteWordRight,
+    DeleteLineEnd,
+    Undo,
+    Redo,
+    /// Copy the selection
+    Copy,
+    /// Copy and delete the selection
+    Cut,
+    Paste,
+    /// Start selecting, the moves extend the selection until it is copied, cut or cancelled
+    SetMark,
+    /// Drop the selection
+    Cancel,
+    /// Open the command line
+    Command,
+    /// Search the pane
+    Search,
+}
+
+/// What the keys working in every pane and the entries of the command palette do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Exit,
+    /// Empty the focused pane
+    Clear,
+    /// Copy the result
+    Copy,
+    CopyAll,
+    History,
+    Table,
+    Diagnostics,
+    Zoom,
+    NextLayout,
+    NextArea,
+    PrevArea,
+    Help,
+    Palette,
+    /// Search the focused pane, or the Result
+    Search,
+    SearchNext,
+    SearchPrev,
+    Format,
+    Watch,
+    /// Open the command line to write the result to a file
+    Write,
+    /// Edit the focused input, or the result, in `$VISUAL` or `$EDITOR`
+    Editor,
+    /// Open the dialog writing the result, the statements or the session to a file
+    Export,
+    Layout(LayoutPreset),
+    Dialect(Dialect),
+    Focus(AreaEnum),
+}
+
+impl Command {
+    /// What the command does, as listed in the help and the command palette.
+    pub fn title(&self) -> String {
+        match self {
+            Command::Exit => String::from("Exit"),
+            Command::Clear => String::from("Clear the focused area"),
+            Command::Copy => String::from("Copy the result"),
+            Command::CopyAll => String::from("Copy every listed statement"),
+            Command::History => String::from("Open or close the history"),
+            Command::Table => String::from("Show the parameters as a table or as text"),
+            Command::Diagnostics => String::from("Show or hide the diagnostics"),
+            Command::Zoom => String::from("Show the focused area alone, or all areas"),
+            Command::NextLayout => String::from("Switch to the next layout"),
+            Command::NextArea => String::from("Focus the next area"),
+            Command::PrevArea => String::from("Focus the previous area"),
+            Command::Help => String::from("Show the keys"),
+            Command::Palette => String::from("Open the command palette"),
+            Command::Search => String::from("Search the focused area"),
+            Command::SearchNext => String::from("Go to the next search match"),
+            Command::SearchPrev => String::from("Go to the previous search match"),
+            Command::Format => String::from("Format the sql"),
+            Command::Watch => String::from("Start or stop watching the clipboard"),
+            Command::Write => String::from("Write the result to a file"),
+            Command::Editor => String::from("Edit the focused area in $EDITOR"),
+            Command::Export => String::from("Export or save the session to a file"),
+            Command::Layout(preset) => format!("Layout: {}", preset.name()),
+            Command::Dialect(dialect) => format!(
+                "Dialect: {}",
+                dialect
+                    .to_possible_value()
+                    .map(|value| value.get_name().to_string())
+                    .unwrap_or_default()
+            ),
+            Command::Focus(area) => format!("Go to {}", area.title()),
+        }
+    }
+}
+
+/// Which editor the keys of the Sql and Value panes follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Default,
+    /// Normal, insert and visual modes, the keys of the editor keymap work in insert mode
+    Vim,
+    Emacs,
+}
+
+/// Editing mode of the Sql and Value panes, only the vim profile leaves `Insert` for other
+/// modes than `Command`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Insert,
+    Visual,
+    /// The command line with what was typed so far
+    Command(String),
+}
+
+/// The keys of each editing action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyBinding>>,
+}
+
+impl Keymap {
+    /// The default keys of `profile`, the actions in `overrides` replace their keys and an
+    /// empty list unbinds them.
+    pub fn new(profile: Profile, overrides: &BTreeMap<Action, Keys>) -> Keymap {
+        let mut bindings: BTreeMap<Action, Vec<KeyBinding>> = default_keys(profile)
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys = keys.iter().map(|key| key.parse().unwrap()).collect();
+                (action, keys)
+            })
+            .collect();
+        for (action, keys) in overrides {
+            bindings.insert(*action, keys.0.clone());
+        }
+        Keymap { bindings }
+    }
+
+    /// The keys of every action, in the order of `Action`.
+    pub fn bindings(&self) -> impl Iterator<Item = (Action, &[KeyBinding])> {
+        self.bindings
+            .iter()
+            .map(|(action, keys)| (*action, keys.as_slice()))
+    }
+
+    /// The action bound to `key`, if any.
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|binding| binding.matches(key)))
+            .map(|(action, _)| *action)
+    }
+}
+
+fn default_keys(profile: Profile) -> Vec<(Action, &'static [&'static str])> {
+    use Action::*;
+    let selection: [(Action, &[&str]); 12] = [
+        (SelectLeft, &["shift+left"]),
+        (SelectRight, &["shift+right"]),
+        (SelectUp, &["shift+up"]),
+        (SelectDown, &["shift+down"]),
+        (SelectWordLeft, &["ctrl+shift+left"]),
+        (SelectWordRight, &["ctrl+shift+right"]),
+        (SelectLineStart, &["shift+home"]),
+        (SelectLineEnd, &["shift+end"]),
+        (SelectPageUp, &["shift+pageup"]),
+        (SelectPageDown, &["shift+pagedown"]),
+        (SelectTop, &["ctrl+shift+home"]),
+        (SelectBottom, &["ctrl+shift+end"]),
+    ];
+    let keys: Vec<(Action, &[&str])> = match profile {
+        Profile::Default | Profile::Vim => vec![
+            (Left, &["left"]),
+            (Right, &["right"]),
+            (Up, &["up"]),
+            (Down, &["down"]),
+            (WordLeft, &["ctrl+left", "alt+b"]),
+            (WordRight, &["ctrl+right", "alt+f"]),
+            (LineStart, &["home"]),
+            (LineEnd, &["end", "ctrl+e"]),
+            (PageUp, &["pageup"]),
+            (PageDown, &["pagedown"]),
+            (Top, &["ctrl+home"]),
+            (Bottom, &["ctrl+end"]),
+            (SelectAll, &["ctrl+a"]),
+            (Newline, &["enter"]),
+            (Backspace, &["backspace"]),
+            (Delete, &["delete"]),
+            (
+                DeleteWordLeft,
+                &["ctrl+backspace", "alt+backspace", "ctrl+w"],
+            ),
+            (DeleteWordRight, &["ctrl+delete", "alt+d"]),
+            (DeleteLineEnd, &["ctrl+k"]),
+            (Undo, &["ctrl+z"]),
+            (Redo, &["ctrl+shift+z", "alt+z"]),
+            (Copy, &["ctrl+c"]),
+            (Cut, &["ctrl+x"]),
+            (Paste, &["ctrl+v"]),
+        ],
+        Profile::Emacs => vec![
+            (Left, &["left", "ctrl+b"]),
+            (Right, &["right", "ctrl+f"]),
+            (Up, &["up", "ctrl+p"]),
+            (Down, &["down", "ctrl+n"]),
+            (WordLeft, &["ctrl+left", "alt+b"]),
+            (WordRight, &["ctrl+right", "alt+f"]),
+            (LineStart, &["home", "ctrl+a"]),
+            (LineEnd, &["end", "ctrl+e"]),
+            (PageUp, &["pageup", "alt+v"]),
+            (PageDown, &["pagedown", "ctrl+v"]),
+            (Top, &["ctrl+home", "alt+<"]),
+            (Bottom, &["ctrl+end", "alt+>"]),
+            (SelectAll, &["alt+h"]),
+            (Newline, &["enter", "ctrl+j"]),
+            (Backspace, &["backspace"]),
+            (Delete, &["delete", "ctrl+d"]),
+            (DeleteWordLeft, &["alt+backspace", "ctrl+backspace"]),
+            (DeleteWordRight, &["alt+d", "ctrl+delete"]),
+            (DeleteLineEnd, &["ctrl+k"]),
+            (Undo, &["ctrl+/", "ctrl+_", "ctrl+z"]),
+            (Redo, &["alt+_", "ctrl+?"]),
+            (Copy, &["alt+w"]),
+            (Cut, &["ctrl+w"]),
+            (Paste, &["ctrl+y"]),
+            (SetMark, &["ctrl+space", "ctrl+@"]),
+            (Cancel, &["ctrl+g"]),
+            (Command, &["alt+x"]),
+            (Search, &["ctrl+s"]),
+        ],
+    };
+    keys.into_iter().chain(selection).collect()
+}
+
+/// One key or a list of keys in the config file.
+#[derive(Debug, Clone)]
+pub struct Keys(Vec<KeyBinding>);
+
+impl<'de> Deserialize<'de> for Keys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = Keys;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a key or a list of keys")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Keys, E> {
+                text.parse().map(|key| Keys(vec![key])).map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Keys, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(key) = seq.next_element()? {
+                    keys.push(key);
+                }
+                Ok(Keys(keys))
+            }
+        }
+
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keybindings {
+    pub exit: KeyBinding,
+    pub clear: KeyBinding,
+    /// Copy the result to the clipboard, it is also copied when leaving the Sql or Value pane
+    pub copy: KeyBinding,
+    /// Copy every listed statement of a log
+    pub copy_all: KeyBinding,
+    /// Open or close the history of converted statements
+    pub history: KeyBinding,
+    /// Show the Value pane as a table of its parameters, or as text again
+    pub table: KeyBinding,
+    /// Show or hide the diagnostics panel
+    pub diagnostics: KeyBinding,
+    /// Show the focused pane alone, or all panes again
+    pub zoom: KeyBinding,
+    /// Switch to the next layout preset
+    pub layout: KeyBinding,
+    /// Show every key, `?` also does outside the inputs
+    pub help: KeyBinding,
+    /// Search the commands and run one
+    pub palette: KeyBinding,
+    /// Search the Sql, Value or Result pane, `/` also does outside the inputs and in vim's
+    /// normal mode
+    pub search: KeyBinding,
+    pub search_next: KeyBinding,
+    pub search_prev: KeyBinding,
+    /// Edit the focused Sql or Value pane, or the result, in `$VISUAL` or `$EDITOR`
+    pub external_editor: KeyBinding,
+    /// Write the result, the statements or the session to a file
+    pub export: KeyBinding,
+    pub next_area: KeyBinding,
+    pub prev_area: KeyBinding,
+    /// Which editor the Sql and Value panes follow
+    pub profile: Profile,
+    /// Editing keys of the Sql and Value panes replacing those of the profile, they take
+    /// precedence over the keys above in these panes
+    pub editor: BTreeMap<Action, Keys>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings {
+            exit: KeyBinding::new(KeyCode::Esc, KeyModifiers::NONE),
+            clear: KeyBinding::new(KeyCode::Char('l'), KeyModifiers::CONTROL),
+            copy: KeyBinding::new(KeyCode::Char('y'), KeyModifiers::CONTROL),
+            copy_all: KeyBinding::new(KeyCode::Char('y'), KeyModifiers::ALT),
+            history: KeyBinding::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            table: KeyBinding::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            diagnostics: KeyBinding::new(KeyCode::Char('o'), KeyModifiers::CONTROL),
+            zoom: KeyBinding::new(KeyCode::Char('m'), KeyModifiers::ALT),
+            layout: KeyBinding::new(KeyCode::Char('l'), KeyModifiers::ALT),
+            help: KeyBinding::new(KeyCode::F(1), KeyModifiers::NONE),
+            palette: KeyBinding::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            search: KeyBinding::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
+            search_next: KeyBinding::new(KeyCode::F(3), KeyModifiers::NONE),
+            search_prev: KeyBinding::new(KeyCode::F(3), KeyModifiers::SHIFT),
+            external_editor: KeyBinding::new(KeyCode::Char('e'), KeyModifiers::ALT),
+            export: KeyBinding::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            next_area: KeyBinding::new(KeyCode::Tab, KeyModifiers::NONE),
+            prev_area: KeyBinding::new(KeyCode::BackTab, KeyModifiers::NONE),
+            profile: Profile::default(),
+            editor: BTreeMap::new(),
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn editor_keymap(&self) -> Keymap {
+        Keymap::new(self.profile, &self.editor)
+    }
+
+    /// The keys working in every pane with their command, the first match wins.
+    pub fn commands(&self) -> [(KeyBinding, Command); 18] {
+        [
+            (self.history, Command::History),
+            (self.table, Command::Table),
+            (self.diagnostics, Command::Diagnostics),
+            (self.zoom, Command::Zoom),
+            (self.layout, Command::NextLayout),
+            (self.help, Command::Help),
+            (self.palette, Command::Palette),
+            (self.search, Command::Search),
+            (self.search_next, Command::SearchNext),
+            (self.search_prev, Command::SearchPrev),
+            (self.external_editor, Command::Editor),
+            (self.export, Command::Export),
+            (self.exit, Command::Exit),
+            (self.clear, Command::Clear),
+            (self.copy, Command::Copy),
+            (self.copy_all, Command::CopyAll),
+            (self.next_area, Command::NextArea),
+            (self.prev_area, Command::PrevArea),
+        ]
+    }
+
+    /// The command of `key`, if any.
+    pub fn command(&self, key: &KeyEvent) -> Option<Command> {
+        self.commands()
+            .into_iter()
+            .find(|(binding, _)| binding.matches(key))
+            .map(|(_, command)| command)
+    }
+
+    /// The key running `command`, if any.
+    pub fn key(&self, command: Command) -> Option<KeyBinding> {
+        self.commands()
+            .into_iter()
+            .find(|(_, bound)| *bound == command)
+            .map(|(binding, _)| binding)
+    }
+}
+
+/// A key with its modifiers, written like `esc`, `ctrl+l` or `shift+tab` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { code, modifiers }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        // shift is already part of upper case letters and BackTab
+        let mut modifiers = key.modifiers;
+        if matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // `ctrl++` binds the plus key
+        let key = match parts.pop() {
+            Some("") if parts.last() == Some(&"") => {
+                parts.pop();
+                "+"
+            }
+            Some(key) => key,
+            None => "",
+        };
+        for part in parts {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in key '{}'", part, text)),
+            }
+        }
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let code = match key.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if shift => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "enter" | "return" => KeyCode::Enter,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            name if name.len() > 1 && name.starts_with('f') => match name[1..].parse() {
+                Ok(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key '{}'", text)),
+            },
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key '{}'", text)),
+                }
+            }
+        };
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(KeyBinding { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::BackTab => f.write_str("Shift+Tab"),
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::Config;
+    use crate::keymap::{Action, KeyBinding};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_keymap() {
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        let config = Config::parse(
+            r#"
+            [keybindings.editor]
+            undo = "alt+u"
+            redo = ["ctrl+shift+z", "f5"]
+            paste = []
+            "#,
+        )
+        .unwrap();
+        let editor = &config.keybindings.editor_keymap();
+        assert_eq!(
+            Some(Action::Undo),
+            editor.action(&key(KeyCode::Char('u'), KeyModifiers::ALT))
+        );
+        assert_eq!(
+            None,
+            editor.action(&key(KeyCode::Char('z'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            Some(Action::Redo),
+            editor.action(&key(
+                KeyCode::Char('Z'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ))
+        );
+        assert_eq!(
+            None,
+            editor.action(&key(KeyCode::Char('v'), KeyModifiers::CONTROL))
+        );
+        // the other actions keep their default keys
+        assert_eq!(
+            Some(Action::SelectWordLeft),
+            editor.action(&key(
+                KeyCode::Left,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ))
+        );
+        assert_eq!(
+            Some(Action::SelectAll),
+            editor.action(&key(KeyCode::Char('a'), KeyModifiers::CONTROL))
+        );
+
+        let emacs = Config::parse("[keybindings]\nprofile = \"emacs\"").unwrap();
+        let editor = emacs.keybindings.editor_keymap();
+        assert_eq!(
+            Some(Action::Paste),
+            editor.action(&key(KeyCode::Char('y'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            Some(Action::SetMark),
+            editor.action(&key(KeyCode::Char(' '), KeyModifiers::CONTROL))
+        );
+
+        let error = |text: &str| Config::parse(text).unwrap_err().to_string();
+        assert!(error("[keybindings.editor]\nundo = \"hyper+z\"").contains("unknown modifier"));
+        assert!(error("[keybindings.editor]\nyank = \"ctrl+y\"").contains("unknown variant `yank`"));
+        assert_eq!(
+            "Ctrl+Shift+Left",
+            "ctrl+shift+left".parse::<KeyBinding>().unwrap().to_string()
+        );
+        assert_eq!(
+            "Shift+Tab",
+            "shift+tab".parse::<KeyBinding>().unwrap().to_string()
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::app::AreaEnum;
+use crate::config::data_dir;
+use clap::ValueEnum;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Pane sizes stay within these percentages, so a dragged pane cannot disappear.
+const MIN_SIZE: u16 = 10;
+const MAX_SIZE: u16 = 90;
+
+/// How the Sql, Value and Result panes are arranged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutPreset {
+    /// Sql and Value side by side over the Result
+    #[default]
+    SideBySide,
+    /// Sql, Value and Result on top of each other, for narrow terminals
+    Stacked,
+    /// The Result only, with the statement list and the diagnostics
+    ResultOnly,
+}
+
+impl LayoutPreset {
+    /// The preset after this one, to cycle through them.
+    pub fn next(self) -> LayoutPreset {
+        let all = LayoutPreset::value_variants();
+        let index = all.iter().position(|preset| *preset == self).unwrap_or(0);
+        all[(index + 1) % all.len()]
+    }
+
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// The preset and the sizes of the panes in percent, changed in the TUI by dragging the
+/// borders between panes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaneLayout {
+    pub preset: LayoutPreset,
+    /// Height of the Sql and Value panes over the Result
+    pub inputs: u16,
+    /// Share of the Sql pane next to or over the Value pane
+    pub sql: u16,
+    /// Width of the statement list
+    pub list: u16,
+    /// Keep the layout changed in the TUI for the next session
+    #[serde(skip_serializing)]
+    pub remember: bool,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        PaneLayout {
+            preset: LayoutPreset::default(),
+            inputs: 50,
+            sql: 50,
+            list: 30,
+            remember: true,
+        }
+    }
+}
+
+/// A border between panes that can be dragged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// Between the statement list and the other panes
+    List,
+    /// Between the inputs and the Result
+    Inputs,
+    /// Between the Sql and the Value pane
+    Sql,
+}
+
+/// Where a split is drawn: the area it divides, how, and the first column or row after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitHandle {
+    pub split: Split,
+    pub area: Rect,
+    pub direction: Direction,
+    pub position: u16,
+}
+
+impl SplitHandle {
+    /// Whether (`column`, `row`) is on the borders drawn on both sides of the split.
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        let (along, across, start, length) = match self.direction {
+            Direction::Horizontal => (column, row, self.area.y, self.area.height),
+            Direction::Vertical => (row, column, self.area.x, self.area.width),
+        };
+        (along + 1 == self.position || along == self.position)
+            && across >= start
+            && across < start + length
+    }
+}
+
+/// The areas of the panes shown, `None` for hidden ones.
+#[derive(Debug, Default)]
+pub struct Panes {
+    pub list: Option<Rect>,
+    pub sql: Option<Rect>,
+    pub value: Option<Rect>,
+    pub result: Option<Rect>,
+    pub diagnostics: Option<Rect>,
+    pub footer: Rect,
+    pub handles: Vec<SplitHandle>,
+}
+
+impl PaneLayout {
+    /// Divides `area` among the panes. `list` shows the statement list, `diagnostics` the
+    /// panel of that height under the Result, and `zoomed` gives all the room but the footer
+    /// to one pane.
+    pub fn panes(
+        &self,
+        area: Rect,
+        list: bool,
+        diagnostics: Option<u16>,
+        zoomed: Option<AreaEnum>,
+    ) -> Panes {
+        let [body, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(area);
+        let mut panes = Panes {
+            footer,
+            ..Panes::default()
+        };
+        if let Some(zoomed) = zoomed {
+            *match zoomed {
+                AreaEnum::List => &mut panes.list,
+                AreaEnum::Sql => &mut panes.sql,
+                AreaEnum::Value => &mut panes.value,
+                AreaEnum::Result => &mut panes.result,
+                AreaEnum::Diagnostics => &mut panes.diagnostics,
+            } = Some(body);
+            return panes;
+        }
+
+        let mut rest = body;
+        if list {
+            let [list, other] =
+                Layout::horizontal([Constraint::Percentage(self.list), Constraint::Fill(1)])
+                    .areas(body);
+            panes.list = Some(list);
+            panes
+                .handles
+                .push(handle(Split::List, body, Direction::Horizontal, other.x));
+            rest = other;
+        }
+        let result = match self.preset {
+            LayoutPreset::SideBySide | LayoutPreset::Stacked => {
+                let [inputs, result] =
+                    Layout::vertical([Constraint::Percentage(self.inputs), Constraint::Fill(1)])
+                        .areas(rest);
+                let direction = if self.preset == LayoutPreset::SideBySide {
+                    Direction::Horizontal
+                } else {
+                    Direction::Vertical
+                };
+                let [sql, value] = Layout::default()
+                    .direction(direction)
+                    .constraints([Constraint::Percentage(self.sql), Constraint::Fill(1)])
+                    .areas(inputs);
+                let position = match direction {
+                    Direction::Horizontal => value.x,
+                    Direction::Vertical => value.y,
+                };
+                panes.sql = Some(sql);
+                panes.value = Some(value);
+                panes
+                    .handles
+                    .push(handle(Split::Inputs, rest, Direction::Vertical, result.y));
+                panes
+                    .handles
+                    .push(handle(Split::Sql, inputs, direction, position));
+                result
+            }
+            LayoutPreset::ResultOnly => rest,
+        };
+        // the diagnostics panel takes the bottom of the result
+        match diagnostics {
+            Some(height) => {
+                let [result, diagnostics] = Layout::vertical([
+                    Constraint::Min(3),
+                    Constraint::Length(height.min(result.height / 2)),
+                ])
+                .areas(result);
+                panes.result = Some(result);
+                panes.diagnostics = Some(diagnostics);
+            }
+            None => panes.result = Some(result),
+        }
+        panes
+    }
+
+    /// Moves the border of `handle` to (`column`, `row`).
+    pub fn drag(&mut self, handle: &SplitHandle, column: u16, row: u16) {
+        let (position, start, length) = match handle.direction {
+            Direction::Horizontal => (column, handle.area.x, handle.area.width),
+            Direction::Vertical => (row, handle.area.y, handle.area.height),
+        };
+        if length == 0 {
+            return;
+        }
+        let percent = (u32::from(position.saturating_sub(start)) * 100 / u32::from(length)) as u16;
+        let percent = percent.clamp(MIN_SIZE, MAX_SIZE);
+        match handle.split {
+            Split::List => self.list = percent,
+            Split::Inputs => self.inputs = percent,
+            Split::Sql => self.sql = percent,
+        }
+    }
+
+    /// The layout kept from the last session, `None` when there is none.
+    pub fn load() -> Option<PaneLayout> {
+        let text = fs::read_to_string(layout_path()?).ok()?;
+        toml::from_str(&text).ok()
+    }
+
+    /// Keeps the layout for the next session.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = layout_path().ok_or("no data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+fn handle(split: Split, area: Rect, direction: Direction, position: u16) -> SplitHandle {
+    SplitHandle {
+        split,
+        area,
+        direction,
+        position,
+    }
+}
+
+/// `$XDG_DATA_HOME/sql-param/layout.toml`
+fn layout_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("sql-param").join("layout.toml"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::app::AreaEnum;
+    use crate::layout::{LayoutPreset, PaneLayout, Split};
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_panes() {
+        let area = Rect::new(0, 0, 100, 43);
+        let layout = PaneLayout::default();
+        let panes = layout.panes(area, false, None, None);
+        assert_eq!(Some(Rect::new(0, 0, 50, 20)), panes.sql);
+        assert_eq!(Some(Rect::new(50, 0, 50, 20)), panes.value);
+        assert_eq!(Some(Rect::new(0, 20, 100, 20)), panes.result);
+        assert_eq!(Rect::new(0, 40, 100, 3), panes.footer);
+
+        let stacked = PaneLayout {
+            preset: LayoutPreset::Stacked,
+            ..PaneLayout::default()
+        };
+        let panes = stacked.panes(area, true, Some(4), None);
+        assert_eq!(Some(Rect::new(0, 0, 30, 40)), panes.list);
+        assert_eq!(Some(Rect::new(30, 0, 70, 10)), panes.sql);
+        assert_eq!(Some(Rect::new(30, 10, 70, 10)), panes.value);
+        assert_eq!(Some(Rect::new(30, 36, 70, 4)), panes.diagnostics);
+
+        let result_only = PaneLayout {
+            preset: LayoutPreset::ResultOnly,
+            ..PaneLayout::default()
+        };
+        let panes = result_only.panes(area, false, None, None);
+        assert_eq!(
+            (None, Some(Rect::new(0, 0, 100, 40))),
+            (panes.sql, panes.result)
+        );
+        assert!(panes.handles.is_empty());
+
+        let panes = layout.panes(area, true, None, Some(AreaEnum::Value));
+        assert_eq!(Some(Rect::new(0, 0, 100, 40)), panes.value);
+        assert_eq!((None, None), (panes.list, panes.sql));
+        assert_eq!(LayoutPreset::SideBySide, LayoutPreset::ResultOnly.next());
+        assert_eq!("result-only", LayoutPreset::ResultOnly.name());
+    }
+
+    #[test]
+    fn test_drag() {
+        let area = Rect::new(0, 0, 100, 43);
+        let mut layout = PaneLayout::default();
+        let panes = layout.panes(area, false, None, None);
+        // the borders on both sides of the split
+        let handle = panes.handles.iter().find(|h| h.contains(49, 5)).unwrap();
+        assert_eq!(Split::Sql, handle.split);
+        assert!(handle.contains(50, 5) && !handle.contains(51, 5) && !handle.contains(50, 25));
+        let handle = *handle;
+        layout.drag(&handle, 70, 5);
+        assert_eq!(70, layout.sql);
+        layout.drag(&handle, 99, 5);
+        assert_eq!(90, layout.sql);
+
+        let inputs = *panes.handles.iter().find(|h| h.contains(10, 20)).unwrap();
+        assert_eq!(Split::Inputs, inputs.split);
+        layout.drag(&inputs, 10, 10);
+        assert_eq!(25, layout.inputs);
+
+        let saved = toml::to_string(&layout).unwrap();
+        assert!(!saved.contains("remember"));
+        assert_eq!(layout, toml::from_str(&saved).unwrap());
+    }
+}
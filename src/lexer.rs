@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    String,
+    QuotedIdentifier,
+    Number,
+    Keyword,
+    Identifier,
+    Placeholder(PlaceholderStyle),
+    Punctuation,
+}
+
+/// How a placeholder is written: `?` (JDBC), `$1` (PostgreSQL) or `:name` (named parameters).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderStyle {
+    Question,
+    Numbered,
+    Named,
+}
+
+impl PlaceholderStyle {
+    /// How placeholders of the style look, for messages.
+    pub fn example(&self) -> &'static str {
+        match self {
+            PlaceholderStyle::Question => "?",
+            PlaceholderStyle::Numbered => "$n",
+            PlaceholderStyle::Named => ":name",
+        }
+    }
+}
+
+/// A token of a sql statement, `start..end` is a byte range of the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[rustfmt::skip]
+const KEYWORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CREATE", "CROSS",
+    "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP", "DUPLICATE", "ELSE", "END", "EXISTS",
+    "FALSE", "FETCH", "FOR", "FROM", "FULL", "GROUP", "HAVING", "IF", "IN", "INNER", "INSERT",
+    "INTERVAL", "INTO", "IS", "JOIN", "KEY", "LEFT", "LIKE", "LIMIT", "MERGE", "NOT", "NULL",
+    "OFFSET", "ON", "OR", "ORDER", "OUTER", "OVER", "PARTITION", "REPLACE", "RETURNING", "RIGHT",
+    "ROWS", "SELECT", "SET", "TABLE", "THEN", "TRUE", "TRUNCATE", "UNION", "UPDATE", "USING",
+    "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+/// Splits `sql` into tokens. The tokens cover the whole input, unterminated strings and
+/// comments run to the end.
+pub fn tokenize(sql: &str) -> Vec<Token> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        let kind = match c {
+            c if c.is_ascii_whitespace() => {
+                i = scan_while(bytes, i, |c| c.is_ascii_whitespace());
+                TokenKind::Whitespace
+            }
+            b'-' if next == Some(b'-') => {
+                i = scan_while(bytes, i, |c| c != b'\n');
+                TokenKind::Comment
+            }
+            b'/' if next == Some(b'*') => {
+                i = sql[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                TokenKind::Comment
+            }
+            b'\'' => {
+                i = scan_quoted(bytes, i, b'\'');
+                TokenKind::String
+            }
+            b'"' | b'`' => {
+                i = scan_quoted(bytes, i, c);
+                TokenKind::QuotedIdentifier
+            }
+            b'?' => {
+                i += 1;
+                TokenKind::Placeholder(PlaceholderStyle::Question)
+            }
+            b'$' if next.is_some_and(|c| c.is_ascii_digit()) => {
+                i = scan_while(bytes, i + 1, |c| c.is_ascii_digit());
+                TokenKind::Placeholder(PlaceholderStyle::Numbered)
+            }
+            // `::` is a PostgreSQL cast, not a named parameter
+            b':' if next == Some(b':') => {
+                i += 2;
+                TokenKind::Punctuation
+            }
+            b':' if next.is_some_and(is_word_start) => {
+                i = scan_while(bytes, i + 1, is_word_byte);
+                TokenKind::Placeholder(PlaceholderStyle::Named)
+            }
+            c if c.is_ascii_digit() || (c == b'.' && next.is_some_and(|c| c.is_ascii_digit())) => {
+                i = scan_while(bytes, i, |c| c.is_ascii_alphanumeric() || c == b'.');
+                TokenKind::Number
+            }
+            c if is_word_start(c) => {
+                i = scan_while(bytes, i, is_word_byte);
+                if is_keyword(&sql[start..i]) {
+                    TokenKind::Keyword
+                } else {
+                    TokenKind::Identifier
+                }
+            }
+            _ => {
+                // keep multi-byte characters in one token
+                i += sql[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punctuation
+            }
+        };
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+        });
+    }
+    tokens
+}
+
+/// Byte ranges of the statements of `sql` separated by `;`, with their `;` and without the
+/// whitespace around them.
+pub fn statements(sql: &str) -> Vec<Range<usize>> {
+    let mut statements = Vec::new();
+    let mut start = None;
+    let mut end = 0;
+    for token in tokenize(sql) {
+        if token.kind == TokenKind::Whitespace {
+            continue;
+        }
+        let first = *start.get_or_insert(token.start);
+        end = token.end;
+        if &sql[token.start..token.end] == ";" {
+            statements.push(first..end);
+            start = None;
+        }
+    }
+    if let Some(first) = start {
+        statements.push(first..end);
+    }
+    statements
+}
+
+fn is_word_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c >= 0x80
+}
+
+fn is_word_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80
+}
+
+fn scan_while(bytes: &[u8], mut i: usize, f: impl Fn(u8) -> bool) -> usize {
+    while i < bytes.len() && f(bytes[i]) {
+        i += 1;
+    }
+    i
+}
+
+/// Scans a quoted string starting at `i`, a doubled quote is an escaped quote.
+fn scan_quoted(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    i += 1;
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lexer::{statements, tokenize, PlaceholderStyle, TokenKind};
+
+    fn placeholder_style(sql: &str) -> Option<PlaceholderStyle> {
+        tokenize(sql)
+            .into_iter()
+            .find_map(|token| match token.kind {
+                TokenKind::Placeholder(style) => Some(style),
+                _ => None,
+            })
+    }
+
+    fn kinds(sql: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(sql)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, &sql[token.start..token.end]))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            vec![
+                (TokenKind::Keyword, "select"),
+                (TokenKind::String, "'it''s ?'"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Number, "1.5"),
+                (TokenKind::Keyword, "FROM"),
+                (TokenKind::QuotedIdentifier, "\"user\""),
+                (TokenKind::Comment, "-- id = ?"),
+                (TokenKind::Keyword, "WHERE"),
+                (TokenKind::Identifier, "id"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::Placeholder(PlaceholderStyle::Question), "?"),
+            ],
+            kinds("select 'it''s ?', 1.5 FROM \"user\" -- id = ?\nWHERE id = ?")
+        );
+    }
+
+    #[test]
+    fn test_placeholder_style() {
+        assert_eq!(
+            Some(PlaceholderStyle::Numbered),
+            placeholder_style("SELECT * FROM t WHERE a = $1::int")
+        );
+        assert_eq!(
+            Some(PlaceholderStyle::Named),
+            placeholder_style("SELECT * FROM t WHERE a = :name")
+        );
+        assert_eq!(None, placeholder_style("SELECT 'a = ?' FROM t"));
+    }
+
+    #[test]
+    fn test_statements() {
+        let sql = "SELECT ';' FROM a;\n  UPDATE b SET c = 1; -- done;\nDELETE FROM d";
+        let texts: Vec<&str> = statements(sql)
+            .into_iter()
+            .map(|range| &sql[range])
+            .collect();
+        assert_eq!(
+            vec![
+                "SELECT ';' FROM a;",
+                "UPDATE b SET c = 1;",
+                "-- done;\nDELETE FROM d"
+            ],
+            texts
+        );
+        assert!(statements(" \n").is_empty());
+    }
+}
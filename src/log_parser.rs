@@ -1,66 +1,446 @@
+use lazy_static::lazy_static;
 use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
 
+lazy_static! {
+    static ref SQL_REGEX: Regex = Regex::new(r"Preparing:\s*(.+)").unwrap();
+    static ref PARAMETERS_REGEX: Regex = Regex::new(r"Parameters:(.*)").unwrap();
+    // logged by MyBatis after a statement ran: <==      Total: 3 or <==    Updates: 1
+    static ref ROWS_REGEX: Regex = Regex::new(r"<==\s+(?:Total|Updates):\s*(\d+)").unwrap();
+    // CSI sequences (colours, cursor movement) and OSC sequences (titles, hyperlinks)
+    static ref ANSI_REGEX: Regex =
+        Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-_]")
+            .unwrap();
+    // kubectl logs --prefix: [pod/my-pod-7d9f8b6c4-x2k9p/app] message
+    static ref KUBECTL_REGEX: Regex = Regex::new(r"^\[pod/([^/\]]+)/([^\]]+)\]\s?(.*)$").unwrap();
+    // docker compose logs: app-1  | message
+    static ref COMPOSE_REGEX: Regex = Regex::new(r"^([A-Za-z0-9][\w.-]*[-_][0-9]+)\s+\|\s?(.*)$").unwrap();
+    // stern: my-pod-7d9f8b6c4-x2k9p app message
+    static ref STERN_REGEX: Regex = Regex::new(
+        r"^([a-z0-9][a-z0-9.-]*-(?:[a-z0-9]{5}|[0-9]+))\s+([a-z0-9][a-z0-9-]*)\s+(.*)$"
+    )
+    .unwrap();
+    static ref TIMESTAMP_REGEX: Regex = Regex::new(
+        r"\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:[.,]\d{1,9})?(?:Z|[+-]\d{2}:?\d{2})?"
+    )
+    .unwrap();
+    // Spring Boot: <level> <pid> --- [<thread>]
+    static ref THREAD_REGEX: Regex = Regex::new(r"(?:TRACE|DEBUG|INFO|WARN|ERROR)\s+\d+\s+---\s+\[\s*([^\]]*?)\s*\]").unwrap();
+    // Sleuth/Micrometer: [app,traceId,spanId] or [traceId,spanId], or traceId=...
+    static ref TRACE_REGEX: Regex = Regex::new(
+        r"(?i)\[(?:[\w-]*,)?([0-9a-f]{16,32}),[0-9a-f]{16}(?:,\w*)?\]|trace[_-]?id[=:]\s*([0-9a-f-]{16,36})"
+    )
+    .unwrap();
+}
+
+/// Where a log line came from when it was wrapped by kubectl, stern or docker compose.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LogSource {
+    pub pod: Option<String>,
+    pub container: String,
+}
+
+/// The container tooling that wrapped a log line.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogWrapper {
+    DockerJson,
+    Kubectl,
+    Stern,
+    Compose,
+}
+
+/// A log line with ANSI escape sequences and container log wrappers removed.
 #[derive(Debug, Eq, PartialEq)]
-pub struct LogParser {
-    pub sql: Vec<String>,
-    pub value: Vec<String>,
+pub struct LogLine {
+    pub text: String,
+    pub source: Option<LogSource>,
+    pub wrapper: Option<LogWrapper>,
+}
+
+/// A `Preparing` line paired with the `Parameters` line that followed it.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Statement {
+    pub sql: String,
+    pub value: String,
+    /// Name of the log format the statement was found with
+    pub format: String,
+    pub source: Option<LogSource>,
+    pub wrapper: Option<LogWrapper>,
+    pub timestamp: Option<String>,
+    pub thread: Option<String>,
+    pub trace_id: Option<String>,
+    /// Rows returned or updated, from the `<== Total` or `<== Updates` line logged after it
+    pub rows: Option<u64>,
+}
+
+/// How the sql and the parameters of a statement are logged. The first capture group of
+/// each regex is the sql and the parameter list.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogFormat {
+    pub name: String,
+    #[serde(deserialize_with = "capturing_regex")]
+    pub sql: Regex,
+    #[serde(deserialize_with = "capturing_regex")]
+    pub parameters: Regex,
+}
+
+impl LogFormat {
+    /// `==>  Preparing: ...` and `==> Parameters: ...` as logged by MyBatis.
+    pub fn mybatis() -> LogFormat {
+        LogFormat {
+            name: String::from("mybatis"),
+            sql: SQL_REGEX.clone(),
+            parameters: PARAMETERS_REGEX.clone(),
+        }
+    }
+}
+
+fn capturing_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    let regex =
+        Regex::new(&text).map_err(|e| serde::de::Error::custom(format!("invalid regex: {e}")))?;
+    if regex.captures_len() < 2 {
+        return Err(serde::de::Error::custom(format!(
+            "regex '{}' needs a capture group",
+            text
+        )));
+    }
+    Ok(regex)
+}
+
+/// Pairs `Preparing` and `Parameters` lines one log line at a time.
+///
+/// Statements of different pods/containers and threads may interleave, so a `Parameters`
+/// line is matched with the latest pending `Preparing` line of the same source, thread and
+/// log format.
+pub struct StatementParser {
+    formats: Vec<LogFormat>,
+    pending: Vec<Statement>,
+}
+
+impl Default for StatementParser {
+    fn default() -> Self {
+        StatementParser::new(vec![LogFormat::mybatis()])
+    }
 }
 
-impl LogParser {
-    pub fn parse_lines(lines: Vec<&str>) -> Option<LogParser> {
-        if lines.is_empty() {
+impl StatementParser {
+    /// A parser for logs in any of `formats`, tried in order.
+    pub fn new(formats: Vec<LogFormat>) -> StatementParser {
+        StatementParser {
+            formats,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn push_line(&mut self, line: &str) -> Option<Statement> {
+        let line = unwrap_line(line);
+        let thread = capture(&THREAD_REGEX, &line.text);
+        let sql = self
+            .formats
+            .iter()
+            .find_map(|format| Some((format, format.sql.captures(&line.text)?)));
+        if let Some((format, caps)) = sql {
+            // a statement of the same source and format that never got its parameters is dropped
+            self.pending.retain(|pending| {
+                pending.source != line.source
+                    || pending.thread != thread
+                    || pending.format != format.name
+            });
+            self.pending.push(Statement {
+                sql: group(&caps).trim().to_string(),
+                value: String::new(),
+                format: format.name.clone(),
+                timestamp: capture(&TIMESTAMP_REGEX, &line.text),
+                trace_id: capture(&TRACE_REGEX, &line.text),
+                thread,
+                source: line.source,
+                wrapper: line.wrapper,
+                rows: None,
+            });
             return None;
         }
+        let (format, caps) = self
+            .formats
+            .iter()
+            .find_map(|format| Some((format, format.parameters.captures(&line.text)?)))?;
+        let index = self.pending.iter().rposition(|pending| {
+            pending.source == line.source
+                && pending.thread == thread
+                && pending.format == format.name
+        })?;
+        let mut statement = self.pending.remove(index);
+        statement.value = group(&caps).trim().to_string();
+        Some(statement)
+    }
+}
 
-        let mut sql_lines: Vec<String> = Vec::new();
-        let mut value_lines: Vec<String> = Vec::new();
+/// Parses all statements of a log at once, with the row counts logged after them.
+pub fn parse_statements<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    formats: Vec<LogFormat>,
+) -> Vec<Statement> {
+    let mut parser = StatementParser::new(formats);
+    let mut statements: Vec<Statement> = Vec::new();
+    for line in lines {
+        if let Some(statement) = parser.push_line(line) {
+            statements.push(statement);
+            continue;
+        }
+        let line = unwrap_line(line);
+        let Some(rows) = capture(&ROWS_REGEX, &line.text).and_then(|rows| rows.parse().ok()) else {
+            continue;
+        };
+        let thread = capture(&THREAD_REGEX, &line.text);
+        let statement = statements
+            .iter_mut()
+            .rev()
+            .find(|statement| statement.source == line.source && statement.thread == thread);
+        if let Some(statement) = statement.filter(|statement| statement.rows.is_none()) {
+            statement.rows = Some(rows);
+        }
+    }
+    statements
+}
 
-        let sql_regex = Regex::new(r"Preparing:\s*(.+)").unwrap();
-        let value_regex = Regex::new(r"Parameters:\s*(.+)").unwrap();
+/// The first capture group, empty when it did not take part in the match.
+fn group<'a>(caps: &regex::Captures<'a>) -> &'a str {
+    caps.get(1).map_or("", |m| m.as_str())
+}
 
-        for line in lines {
-            if let Some(caps) = sql_regex.captures(line) {
-                sql_lines.push(caps[1].to_string());
-            }
-            if let Some(caps) = value_regex.captures(line) {
-                value_lines.push(caps[1].to_string() + ", ");
+/// The first non-empty capture group of `regex`, or the whole match when it has none.
+fn capture(regex: &Regex, text: &str) -> Option<String> {
+    let caps = regex.captures(text)?;
+    caps.iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .or_else(|| caps.get(0))
+        .map(|m| m.as_str().to_string())
+}
+
+pub fn strip_ansi(line: &str) -> String {
+    ANSI_REGEX.replace_all(line, "").into_owned()
+}
+
+/// Removes colours and the wrappers added by `docker logs` (json-file driver),
+/// `kubectl logs --prefix`, `stern` and `docker compose logs`.
+pub fn unwrap_line(line: &str) -> LogLine {
+    let line = strip_ansi(line);
+    let mut text = line.trim();
+
+    // docker json-file driver: {"log":"...\n","stream":"stdout","time":"..."}
+    let mut json_source = None;
+    let mut wrapper = None;
+    let json_text;
+    if text.starts_with('{') {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::from_str(text) {
+            if let Some(serde_json::Value::String(log)) = map.get("log") {
+                json_text = strip_ansi(log);
+                text = json_text.trim();
+                wrapper = Some(LogWrapper::DockerJson);
+                json_source = map
+                    .get("attrs")
+                    .and_then(|attrs| attrs.get("tag").or_else(|| attrs.get("name")))
+                    .and_then(|name| name.as_str())
+                    .map(|name| LogSource {
+                        pod: None,
+                        container: name.to_string(),
+                    });
             }
         }
+    }
+
+    if let Some(caps) = KUBECTL_REGEX.captures(text) {
+        return LogLine {
+            text: caps[3].to_string(),
+            source: Some(LogSource {
+                pod: Some(caps[1].to_string()),
+                container: caps[2].to_string(),
+            }),
+            wrapper: Some(LogWrapper::Kubectl),
+        };
+    }
+    if let Some(caps) = COMPOSE_REGEX.captures(text) {
+        return LogLine {
+            text: caps[2].to_string(),
+            source: Some(LogSource {
+                pod: None,
+                container: caps[1].to_string(),
+            }),
+            wrapper: Some(LogWrapper::Compose),
+        };
+    }
+    if let Some(caps) = STERN_REGEX.captures(text) {
+        // a pod name always contains letters, this keeps dates like 2025-06-28 out
+        if caps[1].chars().any(|c| c.is_ascii_lowercase()) {
+            return LogLine {
+                text: caps[3].to_string(),
+                source: Some(LogSource {
+                    pod: Some(caps[1].to_string()),
+                    container: caps[2].to_string(),
+                }),
+                wrapper: Some(LogWrapper::Stern),
+            };
+        }
+    }
 
-        Some(LogParser {
-            sql: sql_lines,
-            value: value_lines,
-        })
+    LogLine {
+        text: text.to_string(),
+        source: json_source,
+        wrapper,
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::log_parser::LogParser;
+    use crate::log_parser::{
+        parse_statements, unwrap_line, LogFormat, LogSource, LogWrapper, Statement, StatementParser,
+    };
+    use regex::Regex;
 
     #[test]
     fn test_parse_lines() {
         let log = "
             iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : ==>  Preparing: SELECT * FROM user WHERE id = ? AND deleted = 0 and sex = ?;
             iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : ==> Parameters: 1(Long), male(String)
+            iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : <==      Total: 2
             iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.business.Test    : ==> Update user info.
             iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : ==>  Preparing: UPDATE user SET name = ?, age = ?, update_time = ?, id_card = ? WHERE id = ? AND deleted = ?;
             iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : ==> Parameters: zhangsan(String), 18(Integer), 2025-06-13 16:44:56.499(Timestamp), 123456789(Long), 1(Integer), 0(Integer)
         ";
-        let log_parser = LogParser::parse_lines(log.lines().collect::<Vec<_>>()).unwrap();
+        let statements = parse_statements(log.lines(), vec![LogFormat::mybatis()]);
         assert_eq!(
             "SELECT * FROM user WHERE id = ? AND deleted = 0 and sex = ?;",
-            log_parser.sql[0]
+            statements[0].sql
+        );
+        assert_eq!("1(Long), male(String)", statements[0].value);
+        assert_eq!(Some(2), statements[0].rows);
+        assert_eq!("UPDATE user SET name = ?, age = ?, update_time = ?, id_card = ? WHERE id = ? AND deleted = ?;", statements[1].sql);
+        assert_eq!("zhangsan(String), 18(Integer), 2025-06-13 16:44:56.499(Timestamp), 123456789(Long), 1(Integer), 0(Integer)", statements[1].value);
+        assert_eq!(None, statements[1].rows);
+        assert_eq!(
+            Some(LogSource {
+                pod: Some(String::from("iov-test-65759f684-pzpws")),
+                container: String::from("iov-test"),
+            }),
+            statements[0].source
         );
-        assert_eq!("1(Long), male(String), ", log_parser.value[0]);
-        assert_eq!("UPDATE user SET name = ?, age = ?, update_time = ?, id_card = ? WHERE id = ? AND deleted = ?;", log_parser.sql[1]);
-        assert_eq!("zhangsan(String), 18(Integer), 2025-06-13 16:44:56.499(Timestamp), 123456789(Long), 1(Integer), 0(Integer), ", log_parser.value[1]);
     }
 
     #[test]
     fn test_empty_lines() {
         let log = "";
-        let log_parser = LogParser::parse_lines(log.lines().collect::<Vec<_>>());
-        assert_eq!(None, log_parser);
+        let statements = parse_statements(log.lines(), vec![LogFormat::mybatis()]);
+        assert_eq!(Vec::<Statement>::new(), statements);
+    }
+
+    #[test]
+    fn test_unwrap_ansi_and_kubectl_prefix() {
+        let line =
+            unwrap_line("\x1b[32m[pod/order-7d9f8b6c4-x2k9p/app]\x1b[0m ==>  Preparing: SELECT 1");
+        assert_eq!("==>  Preparing: SELECT 1", line.text);
+        assert_eq!(
+            Some(LogSource {
+                pod: Some(String::from("order-7d9f8b6c4-x2k9p")),
+                container: String::from("app"),
+            }),
+            line.source
+        );
+    }
+
+    #[test]
+    fn test_unwrap_docker_json() {
+        let line = unwrap_line(
+            r#"{"log":"\u001b[36mDEBUG\u001b[0m ==> Parameters: 1(Long)\n","stream":"stdout","time":"2025-06-28T12:18:06.123Z"}"#,
+        );
+        assert_eq!("DEBUG ==> Parameters: 1(Long)", line.text);
+        assert_eq!(None, line.source);
+    }
+
+    #[test]
+    fn test_unwrap_compose_prefix() {
+        let line = unwrap_line("order-service-1  | ==>  Preparing: SELECT 1");
+        assert_eq!("==>  Preparing: SELECT 1", line.text);
+        assert_eq!(
+            String::from("order-service-1"),
+            line.source.unwrap().container
+        );
+    }
+
+    #[test]
+    fn test_unwrap_plain_line() {
+        let line = unwrap_line("2025-06-28 20:18:06 DEBUG ==>  Preparing: SELECT 1");
+        assert_eq!(
+            "2025-06-28 20:18:06 DEBUG ==>  Preparing: SELECT 1",
+            line.text
+        );
+        assert_eq!(None, line.source);
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let log = "
+            [pod/order-7d9f8b6c4-x2k9p/app] ==>  Preparing: SELECT * FROM user WHERE id = ?
+            [pod/order-7d9f8b6c4-a1b2c/app] ==>  Preparing: SELECT count(*) FROM user
+            [pod/order-7d9f8b6c4-a1b2c/app] ==> Parameters: 
+            [pod/order-7d9f8b6c4-x2k9p/app] ==> Parameters: 1(Long)
+        ";
+        let mut parser = StatementParser::default();
+        let statements: Vec<_> = log.lines().filter_map(|l| parser.push_line(l)).collect();
+        assert_eq!(2, statements.len());
+        assert_eq!("SELECT count(*) FROM user", statements[0].sql);
+        assert_eq!("", statements[0].value);
+        assert_eq!("SELECT * FROM user WHERE id = ?", statements[1].sql);
+        assert_eq!("1(Long)", statements[1].value);
+        assert_eq!(
+            "order-7d9f8b6c4-x2k9p",
+            statements[1].source.as_ref().unwrap().pod.as_ref().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_statement_metadata() {
+        let log = "
+            iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06.123 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : ==>  Preparing: SELECT * FROM user WHERE id = ?
+            iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06.123 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-7] c.a.model.test.yourbatis.Executor        : ==>  Preparing: SELECT * FROM order WHERE id = ?
+            iov-test-65759f684-pzpws iov-test 2025-06-28 20:18:06.124 --- [685fdd7ed0e3ea524bf29a38a05b72dc,0070d58fe48dd8f9] DEBUG 6 --- [  XNIO-1 task-6] c.a.model.test.yourbatis.Executor        : ==> Parameters: 1(Long)
+        ";
+        let mut parser = StatementParser::default();
+        let statements: Vec<_> = log.lines().filter_map(|l| parser.push_line(l)).collect();
+        assert_eq!(1, statements.len());
+        assert_eq!("SELECT * FROM user WHERE id = ?", statements[0].sql);
+        assert_eq!(
+            Some("2025-06-28 20:18:06.123"),
+            statements[0].timestamp.as_deref()
+        );
+        assert_eq!(Some("XNIO-1 task-6"), statements[0].thread.as_deref());
+        assert_eq!(
+            Some("685fdd7ed0e3ea524bf29a38a05b72dc"),
+            statements[0].trace_id.as_deref()
+        );
+        assert_eq!(Some(LogWrapper::Stern), statements[0].wrapper);
+    }
+
+    #[test]
+    fn test_custom_log_format() {
+        let jdbc = LogFormat {
+            name: String::from("jdbc"),
+            sql: Regex::new(r"SQL: (.+)").unwrap(),
+            parameters: Regex::new(r"Params: \[(.*)\]").unwrap(),
+        };
+        let log = "
+            SQL: SELECT * FROM user WHERE id = ?
+            ==>  Preparing: SELECT * FROM order WHERE id = ?
+            Params: [1(Long)]
+        ";
+        let mut parser = StatementParser::new(vec![jdbc, LogFormat::mybatis()]);
+        let statements: Vec<_> = log.lines().filter_map(|l| parser.push_line(l)).collect();
+        assert_eq!(1, statements.len());
+        assert_eq!("SELECT * FROM user WHERE id = ?", statements[0].sql);
+        assert_eq!("1(Long)", statements[0].value);
+        assert_eq!("jdbc", statements[0].format);
     }
 }
@@ -1,74 +1,232 @@
+mod analysis;
 mod app;
 mod argument;
+mod clipboard;
+mod config;
 mod core;
+mod diagnostic;
+mod diff;
+mod editor;
 mod event_handler;
+mod export;
+mod filter;
+mod follow;
+mod formatter;
+mod help;
+mod highlight;
+mod history;
+mod input;
+mod keymap;
+mod layout;
+mod lexer;
 mod log_parser;
+mod output;
+mod palette;
+mod param_table;
+mod result_view;
+mod search;
+mod session;
+mod stats;
+#[cfg(test)]
+mod test_dir;
+mod theme;
 mod ui;
+mod worker;
 
 use crate::app::App;
+use crate::config::Config;
 use crate::event_handler::{handle_key, handle_mouse, handle_paste};
+use crate::layout::PaneLayout;
+use crate::session::Session;
 use crate::ui::ui;
+use crate::worker::Message;
 use crossterm::event::{
     DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
 };
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
-use crossterm::{event, execute};
+use crossterm::{cursor, event, execute};
 use ratatui::backend::{Backend, CrosstermBackend};
 use ratatui::Terminal;
 use std::error::Error;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long the input thread waits for an event before checking whether it should stop
+const INPUT_POLL: Duration = Duration::from_millis(50);
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let arguments = argument::parse_arguments();
+    let mut config = Config::load(arguments.config_path()).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+    if let Some(backend) = arguments.clipboard() {
+        config.clipboard.backend = backend;
+    }
+    if arguments.watch() {
+        config.clipboard.watch = true;
+    }
+
     // Arguments mode
-    if let Some(value) = argument::parse_arguments() {
-        return value;
+    if let Some(value) = arguments.run(&config) {
+        if let Err(e) = value {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // the layout left by the last session replaces the configured one
+    if config.layout.remember {
+        if let Some(saved) = PaneLayout::load() {
+            config.layout = PaneLayout {
+                remember: true,
+                ..saved
+            };
+        }
+    }
+    if let Some(preset) = arguments.layout() {
+        config.layout.preset = preset;
+    }
+    if let Some(theme) = arguments.theme() {
+        config.theme.name = theme;
     }
 
+    // read before the terminal is taken, so that an error is printed as usual
+    let session = arguments.session().map(|path| {
+        Session::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    });
+
     // TUI mode: setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(
-        stdout,
-        EnterAlternateScreen,
-        EnableMouseCapture,
-        EnableBracketedPaste
-    )?;
-    let backend = CrosstermBackend::new(stdout);
+    enter_tui()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let mut app = App::new();
+    let mut app = App::new(config);
+    if let Some(session) = session {
+        app.open_session(session);
+    }
     let _ = run_app(&mut terminal, &mut app);
 
     // restore terminal
+    leave_tui()?;
+
+    Ok(())
+}
+
+/// Raw mode, the alternate screen, mouse capture and bracketed paste for the TUI.
+fn enter_tui() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )
+}
+
+/// The terminal as it was before the TUI.
+fn leave_tui() -> io::Result<()> {
     disable_raw_mode()?;
     execute!(
-        terminal.backend_mut(),
+        io::stdout(),
         LeaveAlternateScreen,
         DisableMouseCapture,
-        DisableBracketedPaste
-    )?;
-    terminal.show_cursor()?;
+        DisableBracketedPaste,
+        cursor::Show
+    )
+}
 
-    Ok(())
+/// Reads the terminal events on a thread of its own, so that the event loop also wakes for the
+/// worker.
+struct InputReader {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl InputReader {
+    fn start(sender: Sender<Message>) -> InputReader {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                match event::poll(INPUT_POLL).and_then(|ready| ready.then(event::read).transpose())
+                {
+                    Ok(None) => {}
+                    Ok(Some(event)) => {
+                        if sender.send(Message::Input(event)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        InputReader { stop, thread }
+    }
+
+    /// Returns once the thread is gone, no event is read after that.
+    fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = self.thread.join();
+    }
 }
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), Box<dyn Error>> {
+    let mut input = InputReader::start(app.worker.sender());
     while !app.should_exit {
-        // parse clipboard first
+        // a log copied for the empty inputs comes back from the worker
         app.parse_clipboard();
         // draw ui
         terminal.draw(|f| ui(f, app))?;
-        if let Ok(event) = event::read() {
-            match event {
-                Event::Key(key) => handle_key(app, key),
-                Event::Paste(data) => handle_paste(app, data),
-                Event::Mouse(mouse) => handle_mouse(app, mouse),
-                _ => {}
+        // wait for a message, or until a recalculation, a clipboard check or a progress is due
+        let timeout = app
+            .next_tick()
+            .map(|at| at.saturating_duration_since(Instant::now()));
+        let mut message = app.worker.recv(timeout);
+        // every message waiting is handled before drawing again
+        while let Some(next) = message {
+            handle_message(app, next);
+            if app.should_exit {
+                break;
             }
-        };
+            message = app.worker.try_recv();
+        }
+        if let Some(area) = app.external_edit.take() {
+            let (text, extension) = app.editor_text(area);
+            // the keys belong to the external editor while it runs
+            input.stop();
+            leave_tui()?;
+            let edited = editor::edit_text(&editor::editor_command(), &text, extension);
+            enter_tui()?;
+            input = InputReader::start(app.worker.sender());
+            terminal.clear()?;
+            app.edited(area, edited);
+        }
+        app.tick();
     }
+    input.stop();
     Ok(())
 }
+
+fn handle_message(app: &mut App, message: Message) {
+    match message {
+        Message::Input(Event::Key(key)) => handle_key(app, key),
+        Message::Input(Event::Paste(data)) => handle_paste(app, data),
+        Message::Input(Event::Mouse(mouse)) => handle_mouse(app, mouse),
+        Message::Input(_) => {}
+        Message::Parsed(id, parsed) => app.parsed(id, parsed),
+        Message::Bound(id, bound) => app.bound(id, bound),
+        Message::Clipboard(content, watched) => app.clipboard_read(content, watched),
+    }
+}
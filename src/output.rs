@@ -0,0 +1,248 @@
+use crate::analysis::{main_table, statement_kind};
+use crate::core::{bind, BindOptions, Value};
+use crate::diagnostic::{self, Diagnostic};
+use crate::lexer::PlaceholderStyle;
+use crate::log_parser::{LogWrapper, Statement};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The replaced SQL, one statement per line
+    Sql,
+    /// A JSON array of statements
+    Json,
+    /// One JSON object per line, suitable for streaming
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// A statement with everything known about it, as written by the json, ndjson and csv formats.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub timestamp: Option<String>,
+    pub thread: Option<String>,
+    pub trace_id: Option<String>,
+    pub pod: Option<String>,
+    pub container: Option<String>,
+    pub format: String,
+    pub wrapper: Option<LogWrapper>,
+    pub placeholder_style: Option<PlaceholderStyle>,
+    pub kind: String,
+    pub table: Option<String>,
+    pub template: String,
+    pub parameters: Vec<Value>,
+    pub bound: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Record {
+    pub fn new(statement: &Statement, options: &BindOptions) -> Record {
+        let binding = bind(&statement.sql, &statement.value, options);
+        let diagnostics = diagnostic::check(&statement.sql, &statement.value, &binding, options);
+        let parameters = if statement.value.trim().is_empty() {
+            Vec::new()
+        } else {
+            binding.values
+        };
+        Record {
+            timestamp: statement.timestamp.clone(),
+            thread: statement.thread.clone(),
+            trace_id: statement.trace_id.clone(),
+            pod: statement
+                .source
+                .as_ref()
+                .and_then(|source| source.pod.clone()),
+            container: statement
+                .source
+                .as_ref()
+                .map(|source| source.container.clone()),
+            format: statement.format.clone(),
+            wrapper: statement.wrapper,
+            placeholder_style: binding.placeholder_style,
+            kind: statement_kind(&statement.sql),
+            table: main_table(&statement.sql),
+            template: statement.sql.clone(),
+            parameters,
+            bound: binding.result,
+            diagnostics,
+        }
+    }
+}
+
+const CSV_HEADER: &[&str] = &[
+    "timestamp",
+    "thread",
+    "trace_id",
+    "pod",
+    "container",
+    "format",
+    "wrapper",
+    "placeholder_style",
+    "kind",
+    "table",
+    "template",
+    "parameters",
+    "bound",
+    "diagnostics",
+];
+
+/// Writes records in one of the output formats. `finish` must be called after the last one.
+pub struct RecordWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    written: usize,
+    // json is written as one pretty array at the end
+    buffered: Vec<Record>,
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(out: W, format: OutputFormat) -> RecordWriter<W> {
+        RecordWriter {
+            out,
+            format,
+            written: 0,
+            buffered: Vec::new(),
+        }
+    }
+
+    pub fn write(&mut self, record: Record) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Sql => writeln!(self.out, "{}", record.bound)?,
+            OutputFormat::Json => self.buffered.push(record),
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut self.out, &record)?;
+                writeln!(self.out)?;
+            }
+            OutputFormat::Csv => {
+                if self.written == 0 {
+                    writeln!(self.out, "{}", CSV_HEADER.join(","))?;
+                }
+                let parameters = record
+                    .parameters
+                    .iter()
+                    .map(|v| match v.field_type.as_str() {
+                        "" => v.field_value.clone(),
+                        field_type => format!("{}({})", v.field_value, field_type),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let diagnostics = record
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                let fields = [
+                    record.timestamp.unwrap_or_default(),
+                    record.thread.unwrap_or_default(),
+                    record.trace_id.unwrap_or_default(),
+                    record.pod.unwrap_or_default(),
+                    record.container.unwrap_or_default(),
+                    record.format,
+                    record.wrapper.map(variant_name).unwrap_or_default(),
+                    record
+                        .placeholder_style
+                        .map(variant_name)
+                        .unwrap_or_default(),
+                    record.kind,
+                    record.table.unwrap_or_default(),
+                    record.template,
+                    parameters,
+                    record.bound,
+                    diagnostics,
+                ];
+                let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                writeln!(self.out, "{}", fields.join(","))?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            serde_json::to_writer_pretty(&mut self.out, &self.buffered)?;
+            writeln!(self.out)?;
+        }
+        self.out.flush()
+    }
+}
+
+/// The serialized name of a unit enum variant, e.g. `docker-json`.
+fn variant_name(variant: impl Serialize) -> String {
+    match serde_json::to_value(variant) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::BindOptions;
+    use crate::log_parser::StatementParser;
+    use crate::output::{OutputFormat, Record, RecordWriter};
+
+    const LOG: &str = "
+        2025-06-28 20:18:06.123 DEBUG 6 --- [  XNIO-1 task-6] c.a.Executor : ==>  Preparing: INSERT INTO user (name, remark) VALUES (?, ?)
+        2025-06-28 20:18:06.123 DEBUG 6 --- [  XNIO-1 task-6] c.a.Executor : ==> Parameters: zhangsan(String), say \"hi\", bye(String)
+    ";
+
+    fn write(format: OutputFormat) -> String {
+        let mut parser = StatementParser::default();
+        let mut writer = RecordWriter::new(Vec::new(), format);
+        for statement in LOG.lines().filter_map(|line| parser.push_line(line)) {
+            writer
+                .write(Record::new(&statement, &BindOptions::default()))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        String::from_utf8(writer.out).unwrap()
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let output = write(OutputFormat::Ndjson);
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!("XNIO-1 task-6", record["thread"]);
+        assert_eq!("question", record["placeholder_style"]);
+        assert_eq!("mybatis", record["format"]);
+        assert_eq!("say \"hi\", bye", record["parameters"][1]["value"]);
+        assert_eq!(
+            "INSERT INTO user (name, remark) VALUES ('zhangsan', 'say \"hi\", bye')",
+            record["bound"]
+        );
+        assert_eq!(0, record["diagnostics"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn test_csv() {
+        let output = write(OutputFormat::Csv);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].starts_with("timestamp,thread,"));
+        assert!(lines[1].starts_with("2025-06-28 20:18:06.123,XNIO-1 task-6,,,,mybatis,,question,INSERT,user,\"INSERT INTO user (name, remark) VALUES (?, ?)\","));
+        assert!(lines[1].contains("\"zhangsan(String), say \"\"hi\"\", bye(String)\""));
+    }
+
+    #[test]
+    fn test_json() {
+        let output = write(OutputFormat::Json);
+        let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(1, records.as_array().unwrap().len());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::keymap::Command;
+use ratatui::widgets::ListState;
+
+/// The command palette: the commands matching what was typed, best match first.
+#[derive(Debug, Default)]
+pub struct Palette {
+    /// Every command that can be run, in the order shown before anything is typed
+    pub commands: Vec<Command>,
+    pub query: String,
+    /// Position of the selected command among the matching ones
+    pub selected: usize,
+    pub list_state: ListState,
+}
+
+impl Palette {
+    pub fn new(commands: Vec<Command>) -> Palette {
+        Palette {
+            commands,
+            ..Palette::default()
+        }
+    }
+
+    /// The commands whose title matches the query, best match first.
+    pub fn matches(&self) -> Vec<Command> {
+        let mut matches: Vec<(i64, Command)> = self
+            .commands
+            .iter()
+            .filter_map(|command| Some((fuzzy_score(&self.query, &command.title())?, *command)))
+            .collect();
+        // the sort is stable, equal scores keep the order of the commands
+        matches.sort_by_key(|(score, _)| -score);
+        matches.into_iter().map(|(_, command)| command).collect()
+    }
+
+    pub fn selected_command(&self) -> Option<Command> {
+        self.matches().get(self.selected).copied()
+    }
+
+    /// Moves the selection by `delta` matching commands.
+    pub fn select_by(&mut self, delta: isize) {
+        let last = self.matches().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn query_char(&mut self, char: char) {
+        self.query.push(char);
+        self.selected = 0;
+    }
+
+    pub fn query_backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+}
+
+/// How well `text` matches `query`, `None` when the characters of `query` are not all in
+/// `text` in that order. Characters following each other or starting a word count more.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for char in query.chars().flat_map(char::to_lowercase) {
+        if char.is_whitespace() {
+            continue;
+        }
+        let found = position + text[position..].iter().position(|c| *c == char)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::Dialect;
+    use crate::keymap::Command;
+    use crate::palette::{fuzzy_score, Palette};
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(Some(0), fuzzy_score("", "Format the sql"));
+        assert_eq!(None, fuzzy_score("fmx", "Format the sql"));
+        assert_eq!(None, fuzzy_score("ts", "Set"));
+        // word starts and runs of characters score higher than scattered ones
+        assert!(fuzzy_score("fs", "Format the sql") > fuzzy_score("fs", "Diff sets"));
+        assert!(fuzzy_score("cop", "Copy the result") > fuzzy_score("cop", "Focus the previous"));
+        assert_eq!(
+            fuzzy_score("DIAL", "dialect"),
+            fuzzy_score("dial", "Dialect")
+        );
+    }
+
+    #[test]
+    fn test_palette() {
+        let mut palette = Palette::new(vec![
+            Command::Exit,
+            Command::Copy,
+            Command::Format,
+            Command::Dialect(Dialect::Mysql),
+            Command::Dialect(Dialect::Oracle),
+        ]);
+        assert_eq!(5, palette.matches().len());
+        "dial ora".chars().for_each(|char| palette.query_char(char));
+        assert_eq!(
+            Some(Command::Dialect(Dialect::Oracle)),
+            palette.selected_command()
+        );
+        palette.query.clear();
+        "co".chars().for_each(|char| palette.query_char(char));
+        assert_eq!(Some(Command::Copy), palette.selected_command());
+        palette.select_by(10);
+        assert_eq!(palette.matches().len() - 1, palette.selected);
+        palette.query_backspace();
+        assert_eq!(0, palette.selected);
+    }
+}
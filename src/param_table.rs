@@ -0,0 +1,254 @@
+use crate::core::{format_values, values, Value};
+use ratatui::widgets::TableState;
+
+/// Types offered by the type picker, the type of a parameter is added when it is not one of them.
+pub const TYPES: [&str; 16] = [
+    "String",
+    "Integer",
+    "Long",
+    "Short",
+    "Byte",
+    "Double",
+    "Float",
+    "BigDecimal",
+    "Boolean",
+    "Timestamp",
+    "Date",
+    "Time",
+    "LocalDate",
+    "LocalDateTime",
+    "LocalTime",
+    "UUID",
+];
+
+/// A parameter as shown in the table, a NULL keeps the value and type it had.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    pub value: String,
+    pub field_type: String,
+    pub null: bool,
+}
+
+impl Param {
+    fn new() -> Param {
+        Param {
+            value: String::new(),
+            field_type: String::from("String"),
+            null: false,
+        }
+    }
+}
+
+impl From<Value> for Param {
+    fn from(value: Value) -> Self {
+        // MyBatis prints a NULL without a type
+        let null = value.field_value == "null" && value.field_type.is_empty();
+        Param {
+            value: if null {
+                String::new()
+            } else {
+                value.field_value
+            },
+            field_type: value.field_type,
+            null,
+        }
+    }
+}
+
+/// The parameters of a parameter list, none for an empty one.
+pub fn params(text: &str) -> Vec<Param> {
+    if text.trim().is_empty() {
+        return Vec::new();
+    }
+    values(text).into_iter().map(Param::from).collect()
+}
+
+/// Writes the parameters back as a parameter list like `values` in core.rs reads it.
+pub fn format_params(params: &[Param]) -> String {
+    let values: Vec<Value> = params
+        .iter()
+        .map(|param| Value {
+            field_value: if param.null {
+                String::from("null")
+            } else {
+                param.value.clone()
+            },
+            field_type: if param.null {
+                String::new()
+            } else {
+                param.field_type.clone()
+            },
+            span: 0..0,
+        })
+        .collect();
+    format_values(&values)
+}
+
+/// Column of the table a cell edit changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamColumn {
+    Value,
+    Type,
+}
+
+/// The Value pane shown as a table of its parameters.
+#[derive(Debug)]
+pub struct ParamTable {
+    /// Row of the selected parameter
+    pub selected: usize,
+    pub column: ParamColumn,
+    /// Text of the cell being edited
+    pub editing: Option<String>,
+    /// Types of the open type picker and the highlighted one
+    pub picker: Option<(Vec<String>, usize)>,
+    pub state: TableState,
+    /// Parameters of the Value pane, read again when it changes rather than on every draw
+    pub params: Vec<Param>,
+}
+
+impl Default for ParamTable {
+    fn default() -> Self {
+        ParamTable {
+            selected: 0,
+            column: ParamColumn::Value,
+            editing: None,
+            picker: None,
+            state: TableState::default(),
+            params: Vec::new(),
+        }
+    }
+}
+
+impl ParamTable {
+    /// Moves the selection by `delta` of `count` rows.
+    pub fn select_by(&mut self, delta: isize, count: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(count.saturating_sub(1));
+    }
+
+    /// Starts editing the selected cell with its text.
+    pub fn start_edit(&mut self, params: &[Param]) {
+        if let Some(param) = params.get(self.selected) {
+            self.editing = Some(match self.column {
+                ParamColumn::Value => param.value.clone(),
+                ParamColumn::Type => param.field_type.clone(),
+            });
+        }
+    }
+
+    /// Writes the edited text into the selected cell, a value is no longer NULL once edited.
+    pub fn commit_edit(&mut self, params: &mut [Param]) -> bool {
+        let (Some(text), Some(param)) = (self.editing.take(), params.get_mut(self.selected)) else {
+            return false;
+        };
+        match self.column {
+            ParamColumn::Value => {
+                param.value = text;
+                param.null = false;
+            }
+            ParamColumn::Type => param.field_type = text.trim().to_string(),
+        }
+        true
+    }
+
+    /// Opens the type picker on the type of the selected parameter.
+    pub fn open_picker(&mut self, params: &[Param]) {
+        let Some(param) = params.get(self.selected) else {
+            return;
+        };
+        let mut types: Vec<String> = TYPES.iter().map(|name| name.to_string()).collect();
+        if !param.field_type.is_empty() && !types.contains(&param.field_type) {
+            types.push(param.field_type.clone());
+        }
+        let index = types
+            .iter()
+            .position(|name| *name == param.field_type)
+            .unwrap_or(0);
+        self.picker = Some((types, index));
+    }
+
+    /// Gives the selected parameter the highlighted type and closes the picker.
+    pub fn pick(&mut self, params: &mut [Param]) -> bool {
+        let (Some((types, index)), Some(param)) =
+            (self.picker.take(), params.get_mut(self.selected))
+        else {
+            return false;
+        };
+        param.field_type = types[index].clone();
+        param.null = false;
+        true
+    }
+
+    pub fn toggle_null(&mut self, params: &mut [Param]) -> bool {
+        let Some(param) = params.get_mut(self.selected) else {
+            return false;
+        };
+        param.null = !param.null;
+        true
+    }
+
+    /// Adds an empty String parameter after the selected one and selects it.
+    pub fn add(&mut self, params: &mut Vec<Param>) {
+        let index = (self.selected + 1).min(params.len());
+        params.insert(index, Param::new());
+        self.selected = index;
+        self.column = ParamColumn::Value;
+    }
+
+    pub fn remove(&mut self, params: &mut Vec<Param>) -> bool {
+        if self.selected >= params.len() {
+            return false;
+        }
+        params.remove(self.selected);
+        self.select_by(0, params.len());
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::param_table::{format_params, params, ParamColumn, ParamTable};
+
+    #[test]
+    fn test_params() {
+        let text = "zhangsan, lisi(String), null, 18(Integer), 2025-06-13(java.time.LocalDate)";
+        let parsed = params(text);
+        assert_eq!(4, parsed.len());
+        assert_eq!("zhangsan, lisi", parsed[0].value);
+        assert!(parsed[1].null);
+        assert_eq!("java.time.LocalDate", parsed[3].field_type);
+        assert_eq!(text, format_params(&parsed));
+        assert!(params(" ").is_empty());
+        assert_eq!("", format_params(&[]));
+    }
+
+    #[test]
+    fn test_edit_params() {
+        let mut parsed = params("1(Long), null");
+        let mut table = ParamTable::default();
+        table.select_by(1, parsed.len());
+        table.toggle_null(&mut parsed);
+        assert_eq!("1(Long), ", format_params(&parsed));
+
+        table.open_picker(&parsed);
+        table.picker.as_mut().unwrap().1 = 0;
+        table.pick(&mut parsed);
+        table.start_edit(&parsed);
+        table.editing = Some(String::from("a, b"));
+        table.commit_edit(&mut parsed);
+        assert_eq!("1(Long), a, b(String)", format_params(&parsed));
+        assert_eq!(parsed, params(&format_params(&parsed)));
+
+        table.column = ParamColumn::Type;
+        table.start_edit(&parsed);
+        assert_eq!(Some("String"), table.editing.as_deref());
+        table.editing = None;
+        table.add(&mut parsed);
+        assert_eq!(2, table.selected);
+        table.select_by(-2, parsed.len());
+        table.remove(&mut parsed);
+        assert_eq!("a, b(String), (String)", format_params(&parsed));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::lexer::statements;
+use std::ops::Range;
+
+/// What is selected in the Result pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResultSelection {
+    /// The lines from this one to the cursor line
+    Lines(usize),
+    /// A statement, as a byte range of the result
+    Statement(Range<usize>),
+}
+
+/// Cursor, selection and scrolling of the Result pane.
+#[derive(Debug)]
+pub struct ResultView {
+    /// Line of the cursor
+    pub cursor: usize,
+    pub selection: Option<ResultSelection>,
+    pub wrap: bool,
+    /// First row shown, a wrapped line takes several rows
+    pub scroll: usize,
+    /// Columns scrolled to the right when the lines are not wrapped
+    pub column: usize,
+    /// Line shown in each row as last drawn, to find the clicked line
+    pub rows: Vec<usize>,
+    /// Generation of the result, width and wrapping the rows were made for
+    pub rows_key: Option<(u64, usize, bool)>,
+}
+
+impl Default for ResultView {
+    fn default() -> Self {
+        ResultView {
+            cursor: 0,
+            selection: None,
+            wrap: true,
+            scroll: 0,
+            column: 0,
+            rows: Vec::new(),
+            rows_key: None,
+        }
+    }
+}
+
+impl ResultView {
+    /// Moves the cursor by `delta` lines of `text`, `select` extends the selected lines.
+    pub fn move_by(&mut self, text: &str, delta: isize, select: bool) {
+        let last = line_ranges(text).len().saturating_sub(1);
+        let cursor = self.cursor.min(last);
+        match (&self.selection, select) {
+            (Some(ResultSelection::Lines(_)), _) => {}
+            (_, true) => self.selection = Some(ResultSelection::Lines(cursor)),
+            (Some(ResultSelection::Statement(_)), false) => self.selection = None,
+            (None, false) => {}
+        }
+        self.cursor = cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Starts or ends selecting lines at the cursor.
+    pub fn toggle_lines(&mut self) {
+        self.selection = match self.selection {
+            Some(ResultSelection::Lines(_)) => None,
+            _ => Some(ResultSelection::Lines(self.cursor)),
+        };
+    }
+
+    /// Selects the statement on the cursor line, or the next one on that line when it is
+    /// already selected.
+    pub fn select_statement(&mut self, text: &str) {
+        let on_line = self.statements_on_cursor_line(text);
+        let next = match &self.selection {
+            Some(ResultSelection::Statement(selected)) => on_line
+                .iter()
+                .position(|range| range == selected)
+                .map_or(0, |position| (position + 1) % on_line.len()),
+            _ => 0,
+        };
+        self.selection = on_line.get(next).cloned().map(ResultSelection::Statement);
+    }
+
+    /// Byte range of the selection in `text`.
+    pub fn selected_range(&self, text: &str) -> Option<Range<usize>> {
+        match self.selection.as_ref()? {
+            ResultSelection::Lines(anchor) => {
+                let lines = line_ranges(text);
+                let last = lines.len().checked_sub(1)?;
+                let (cursor, anchor) = (self.cursor.min(last), (*anchor).min(last));
+                Some(lines[cursor.min(anchor)].start..lines[cursor.max(anchor)].end)
+            }
+            ResultSelection::Statement(range) => (range.end <= text.len()).then(|| range.clone()),
+        }
+    }
+
+    /// The selected text, or the first statement on the cursor line.
+    pub fn selected_text<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let range = self
+            .selected_range(text)
+            .or_else(|| self.statements_on_cursor_line(text).into_iter().next())?;
+        Some(&text[range])
+    }
+
+    fn statements_on_cursor_line(&self, text: &str) -> Vec<Range<usize>> {
+        let lines = line_ranges(text);
+        let Some(line) = lines.get(self.cursor.min(lines.len().saturating_sub(1))) else {
+            return Vec::new();
+        };
+        statements(text)
+            .into_iter()
+            .filter(|range| range.start <= line.end && range.end >= line.start)
+            .collect()
+    }
+}
+
+/// Byte ranges of the lines of `text`, without their line break.
+pub fn line_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut start = 0;
+    text.split('\n')
+        .map(|line| {
+            let range = start..start + line.len();
+            start = range.end + 1;
+            range
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::result_view::{ResultSelection, ResultView};
+
+    const RESULT: &str = "SELECT 1; SELECT 2;\nUPDATE t\nSET a = 1;\nDELETE FROM t;";
+
+    #[test]
+    fn test_select_lines() {
+        let mut view = ResultView::default();
+        view.move_by(RESULT, 1, false);
+        view.move_by(RESULT, 1, true);
+        assert_eq!(Some(ResultSelection::Lines(1)), view.selection);
+        assert_eq!(Some("UPDATE t\nSET a = 1;"), view.selected_text(RESULT));
+        view.move_by(RESULT, isize::MAX, false);
+        assert_eq!(3, view.cursor);
+        assert_eq!(
+            Some("UPDATE t\nSET a = 1;\nDELETE FROM t;"),
+            view.selected_text(RESULT)
+        );
+        view.toggle_lines();
+        assert_eq!(None, view.selection);
+    }
+
+    #[test]
+    fn test_select_statement() {
+        let mut view = ResultView::default();
+        // without a selection the first statement of the line is taken
+        assert_eq!(Some("SELECT 1;"), view.selected_text(RESULT));
+        view.select_statement(RESULT);
+        view.select_statement(RESULT);
+        assert_eq!(Some("SELECT 2;"), view.selected_text(RESULT));
+        view.move_by(RESULT, 2, false);
+        assert_eq!(None, view.selection);
+        view.select_statement(RESULT);
+        assert_eq!(Some("UPDATE t\nSET a = 1;"), view.selected_text(RESULT));
+    }
+}
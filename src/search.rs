@@ -0,0 +1,115 @@
+use crate::app::AreaEnum;
+use regex::RegexBuilder;
+use std::ops::Range;
+
+/// Incremental search in the Sql, Value or Result pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    pub area: AreaEnum,
+    pub query: String,
+    /// Whether the query is still typed, Enter closes the prompt and keeps the matches marked
+    pub typing: bool,
+    /// Byte offset of the cursor when the search started, typing moves to the first match after it
+    pub origin: usize,
+    /// Position of the match moved to among all matches
+    pub current: usize,
+    /// Byte ranges of the query in the searched pane, found again when either changes
+    pub matches: Vec<Range<usize>>,
+}
+
+impl Search {
+    pub fn new(area: AreaEnum, origin: usize) -> Search {
+        Search {
+            area,
+            query: String::new(),
+            typing: true,
+            origin,
+            current: 0,
+            matches: Vec::new(),
+        }
+    }
+
+    /// Finds the query in `text`, ignoring case unless the query has upper case.
+    pub fn find_in(&mut self, text: &str) {
+        self.matches = find(text, &self.query);
+    }
+
+    /// Moves to the first match starting at or after the origin, or to the first match.
+    pub fn first_after_origin(&mut self) {
+        self.current = self
+            .matches
+            .iter()
+            .position(|range| range.start >= self.origin)
+            .unwrap_or(0);
+    }
+
+    /// Moves by `delta` matches, wrapping around at both ends.
+    pub fn move_by(&mut self, delta: isize) {
+        let count = self.matches.len();
+        if count > 0 {
+            let current = self.current.min(count - 1) as isize;
+            self.current = (current + delta).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    /// Where the search is, for the footer: `3/12`, or why nothing is marked.
+    pub fn status(&self) -> String {
+        match self.matches.len() {
+            _ if self.query.is_empty() => String::new(),
+            0 => String::from("no match"),
+            count => format!("{}/{}", self.current.min(count - 1) + 1, count),
+        }
+    }
+}
+
+/// Byte ranges of `query` in `text`, smart case like vim: upper case in the query makes the
+/// search case sensitive.
+pub fn find(text: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let regex = RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(!query.chars().any(char::is_uppercase))
+        .build();
+    match regex {
+        Ok(regex) => regex.find_iter(text).map(|found| found.range()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::app::AreaEnum;
+    use crate::search::{find, Search};
+
+    #[test]
+    fn test_find() {
+        let text = "SELECT * FROM order_item WHERE order_id = (SELECT id FROM `Order`)";
+        assert_eq!(vec![14..19, 31..36, 59..64], find(text, "order"));
+        assert_eq!(vec![59..64], find(text, "Order"));
+        assert_eq!(vec![7..8], find(text, "*"));
+        assert!(find(text, "").is_empty());
+        // the ranges are bytes, also after characters of several bytes
+        assert_eq!(vec![10..15], find("名前 = 'Ünï'", "ünï"));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut search = Search::new(AreaEnum::Result, 5);
+        assert_eq!("", search.status());
+        search.query.push('x');
+        search.find_in("x.........x.........x");
+        assert_eq!(vec![0..1, 10..11, 20..21], search.matches);
+        search.first_after_origin();
+        assert_eq!("2/3", search.status());
+        search.move_by(2);
+        assert_eq!(0, search.current);
+        search.move_by(-1);
+        assert_eq!(2, search.current);
+        search.origin = 30;
+        search.first_after_origin();
+        assert_eq!(0, search.current);
+        search.find_in("");
+        assert_eq!("no match", search.status());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::core::Dialect;
+use crate::lexer::PlaceholderStyle;
+use crate::log_parser::Statement;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Version written into session files, files of a newer version are not opened.
+const SESSION_VERSION: u32 = 1;
+
+/// What the TUI shows, saved to a file so it can be opened again, also by someone else.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub version: u32,
+    pub dialect: Dialect,
+    pub placeholder_style: Option<PlaceholderStyle>,
+    pub sql: String,
+    pub value: String,
+    /// The result as shown, it differs from the bound sql when it was edited
+    pub result: String,
+    /// The statements of a log, empty for a statement typed in
+    pub statements: Vec<Statement>,
+    /// Position of the statement shown in the inputs
+    pub selected: usize,
+    pub filter: String,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            version: SESSION_VERSION,
+            dialect: Dialect::default(),
+            placeholder_style: None,
+            sql: String::new(),
+            value: String::new(),
+            result: String::new(),
+            statements: Vec::new(),
+            selected: 0,
+            filter: String::new(),
+        }
+    }
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Session, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let session: Session = serde_json::from_str(&text)
+            .map_err(|e| format!("{} is not a session: {}", path.display(), e))?;
+        if session.version > SESSION_VERSION {
+            return Err(format!(
+                "{} was saved by a newer sql-param (session version {})",
+                path.display(),
+                session.version
+            ));
+        }
+        Ok(session)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, format!("{}\n", text))
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::Dialect;
+    use crate::log_parser::{parse_statements, LogFormat};
+    use crate::session::Session;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_session_file() {
+        let log = "
+            2025-06-13 16:44:56.499 DEBUG 1 --- [exec-1] ==>  Preparing: SELECT * FROM user WHERE id = ?
+            2025-06-13 16:44:56.500 DEBUG 1 --- [exec-1] ==> Parameters: 1(Long)
+        ";
+        let session = Session {
+            dialect: Dialect::Mysql,
+            sql: String::from("SELECT * FROM user WHERE id = ?"),
+            value: String::from("1(Long)"),
+            result: String::from("SELECT * FROM user WHERE id = 1"),
+            statements: parse_statements(log.lines(), vec![LogFormat::mybatis()]),
+            ..Session::default()
+        };
+        let dir = TestDir::new("session");
+        let path = dir.join("statements.session");
+        session.save(&path).unwrap();
+        assert_eq!(Ok(session), Session::load(&path));
+
+        std::fs::write(&path, "{\"version\": 2}").unwrap();
+        assert!(Session::load(&path)
+            .unwrap_err()
+            .ends_with("was saved by a newer sql-param (session version 2)"));
+        std::fs::write(&path, "SELECT 1").unwrap();
+        assert!(Session::load(&path)
+            .unwrap_err()
+            .contains("is not a session"));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::output::{csv_field, OutputFormat, Record};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+const TOP_TEMPLATES: usize = 10;
+
+/// Statement counts of a log, grouped by statement type, table, thread and template.
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub statements: usize,
+    pub with_diagnostics: usize,
+    pub kinds: BTreeMap<String, usize>,
+    pub tables: BTreeMap<String, usize>,
+    pub threads: BTreeMap<String, usize>,
+    pub templates: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    pub fn add(&mut self, record: &Record) {
+        self.statements += 1;
+        if !record.diagnostics.is_empty() {
+            self.with_diagnostics += 1;
+        }
+        *self.kinds.entry(record.kind.clone()).or_default() += 1;
+        if let Some(table) = &record.table {
+            *self.tables.entry(table.clone()).or_default() += 1;
+        }
+        if let Some(thread) = &record.thread {
+            *self.threads.entry(thread.clone()).or_default() += 1;
+        }
+        *self.templates.entry(record.template.clone()).or_default() += 1;
+    }
+
+    pub fn write(&self, out: &mut impl Write, format: OutputFormat) -> io::Result<()> {
+        let groups = [
+            ("kind", &self.kinds),
+            ("table", &self.tables),
+            ("thread", &self.threads),
+            ("template", &self.templates),
+        ];
+        match format {
+            OutputFormat::Sql => {
+                writeln!(out, "statements: {}", self.statements)?;
+                writeln!(out, "with diagnostics: {}", self.with_diagnostics)?;
+                for (name, counts) in groups {
+                    writeln!(out)?;
+                    writeln!(out, "by {}:", name)?;
+                    let limit = if name == "template" {
+                        TOP_TEMPLATES
+                    } else {
+                        usize::MAX
+                    };
+                    for (key, count) in by_count(counts).into_iter().take(limit) {
+                        writeln!(out, "{:>8}  {}", count, key)?;
+                    }
+                }
+            }
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, self)?;
+                writeln!(out)?;
+            }
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut *out, self)?;
+                writeln!(out)?;
+            }
+            OutputFormat::Csv => {
+                writeln!(out, "group,key,count")?;
+                writeln!(out, "total,statements,{}", self.statements)?;
+                writeln!(out, "total,with_diagnostics,{}", self.with_diagnostics)?;
+                for (name, counts) in groups {
+                    for (key, count) in by_count(counts) {
+                        writeln!(out, "{},{},{}", name, csv_field(key), count)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entries sorted by descending count, then by key.
+fn by_count(counts: &BTreeMap<String, usize>) -> Vec<(&String, usize)> {
+    let mut entries: Vec<_> = counts.iter().map(|(key, count)| (key, *count)).collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    entries
+}
@@ -0,0 +1,439 @@
+use clap::ValueEnum;
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+const BASE03: Color = Color::Rgb(0x00, 0x2b, 0x36);
+const BASE01: Color = Color::Rgb(0x58, 0x6e, 0x75);
+const SOLARIZED_YELLOW: Color = Color::Rgb(0xb5, 0x89, 0x00);
+const SOLARIZED_ORANGE: Color = Color::Rgb(0xcb, 0x4b, 0x16);
+const SOLARIZED_MAGENTA: Color = Color::Rgb(0xd3, 0x36, 0x82);
+const SOLARIZED_BLUE: Color = Color::Rgb(0x26, 0x8b, 0xd2);
+const SOLARIZED_CYAN: Color = Color::Rgb(0x2a, 0xa1, 0x98);
+const SOLARIZED_GREEN: Color = Color::Rgb(0x85, 0x99, 0x00);
+
+/// The built-in themes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Green accent for dark terminals
+    #[default]
+    Dark,
+    /// Blue accent and darker colours for light terminals
+    Light,
+    /// Bright colours with bold and underlined text, not told apart by hue alone
+    HighContrast,
+    /// The Solarized palette, readable on its dark and light backgrounds
+    Solarized,
+}
+
+/// The parts of the TUI a theme styles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeElement {
+    /// Every pane, under the styles below
+    Text,
+    Border,
+    /// Title of the panes without focus
+    Title,
+    FocusedTitle,
+    Cursor,
+    /// Selected text, list items and table rows
+    Selection,
+    /// Cell of the parameter table being edited
+    Editing,
+    LineNumber,
+    /// Number of the line under the cursor in the Result
+    CurrentLine,
+    /// Details like times, tables and help
+    Muted,
+    Warning,
+    /// Background task running
+    Progress,
+    /// Mode shown in the footer, e.g. NORMAL
+    Mode,
+    Keyword,
+    String,
+    Number,
+    Comment,
+    QuotedIdentifier,
+    Placeholder,
+    /// Values written in place of a placeholder
+    Literal,
+    /// The placeholders and the value linked to the one under the cursor
+    Linked,
+    /// Text found by the search
+    SearchMatch,
+    /// The match the search moved to
+    CurrentMatch,
+}
+
+/// A style written as colours and modifiers, e.g. `black on yellow bold` or `#268bd2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ThemeStyle(pub Style);
+
+impl FromStr for ThemeStyle {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::new();
+        let mut words = text.split_whitespace();
+        while let Some(word) = words.next() {
+            let modifier = match word.to_ascii_lowercase().as_str() {
+                "none" | "default" => continue,
+                "on" => {
+                    let color = words.next().ok_or("missing colour after 'on'")?;
+                    style = style.bg(parse_color(color)?);
+                    continue;
+                }
+                "bold" => Modifier::BOLD,
+                "dim" => Modifier::DIM,
+                "italic" => Modifier::ITALIC,
+                "underlined" | "underline" => Modifier::UNDERLINED,
+                "reversed" | "reverse" => Modifier::REVERSED,
+                "crossed_out" => Modifier::CROSSED_OUT,
+                _ => {
+                    style = style.fg(parse_color(word)?);
+                    continue;
+                }
+            };
+            style = style.add_modifier(modifier);
+        }
+        Ok(ThemeStyle(style))
+    }
+}
+
+impl TryFrom<String> for ThemeStyle {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+fn parse_color(text: &str) -> Result<Color, String> {
+    Color::from_str(text).map_err(|_| format!("unknown colour '{}'", text))
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse_color(&text)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// The `[theme]` table of the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub name: ThemeName,
+    /// Colour of the focused pane title, the cursor and the mode instead of the theme one, a
+    /// name like `green`, `#rrggbb` or 0-255
+    #[serde(deserialize_with = "color")]
+    pub accent: Option<Color>,
+    /// Draw with bold, underlined and reversed text only, also set by `NO_COLOR`
+    pub no_color: bool,
+    /// Styles replacing the ones of the theme
+    pub styles: BTreeMap<ThemeElement, ThemeStyle>,
+}
+
+impl ThemeConfig {
+    /// The theme named in the config with the accent and the styles set there.
+    pub fn theme(&self) -> Theme {
+        if self.no_color {
+            let mut theme = Theme::no_color();
+            for (element, style) in &self.styles {
+                let mut style = style.0;
+                style.fg = None;
+                style.bg = None;
+                *theme.style_mut(*element) = style;
+            }
+            return theme;
+        }
+        let mut theme = Theme::named(self.name);
+        if let Some(accent) = self.accent {
+            theme.set_accent(accent);
+        }
+        for (element, style) in &self.styles {
+            *theme.style_mut(*element) = style.0;
+        }
+        theme
+    }
+}
+
+/// The style of every part of the TUI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub text: Style,
+    pub border: Style,
+    pub title: Style,
+    pub focused_title: Style,
+    pub cursor: Style,
+    pub selection: Style,
+    pub editing: Style,
+    pub line_number: Style,
+    pub current_line: Style,
+    pub muted: Style,
+    pub warning: Style,
+    pub progress: Style,
+    pub mode: Style,
+    pub keyword: Style,
+    pub string: Style,
+    pub number: Style,
+    pub comment: Style,
+    pub quoted_identifier: Style,
+    pub placeholder: Style,
+    pub literal: Style,
+    pub linked: Style,
+    pub search_match: Style,
+    pub current_match: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::named(ThemeName::Dark)
+    }
+}
+
+impl Theme {
+    pub fn named(name: ThemeName) -> Theme {
+        match name {
+            ThemeName::Dark => Theme::accented(Color::Green),
+            ThemeName::Light => Theme {
+                keyword: Style::new().fg(Color::Blue).bold(),
+                string: Style::new().fg(Color::Green),
+                number: Style::new().fg(Color::Magenta),
+                comment: Style::new().fg(Color::DarkGray).italic(),
+                quoted_identifier: Style::new().fg(Color::Cyan),
+                placeholder: Style::new().fg(Color::Red).bold(),
+                literal: Style::new().fg(Color::Black).bg(Color::LightYellow),
+                linked: Style::new().fg(Color::Black).bg(Color::LightCyan).bold(),
+                search_match: Style::new().fg(Color::Black).bg(Color::LightMagenta),
+                current_match: Style::new().fg(Color::Black).bg(Color::LightRed).bold(),
+                warning: Style::new().fg(Color::Red),
+                muted: Style::new().fg(Color::DarkGray),
+                line_number: Style::new().fg(Color::DarkGray),
+                ..Theme::accented(Color::Blue)
+            },
+            ThemeName::HighContrast => Theme {
+                border: Style::new().fg(Color::White),
+                title: Style::new().fg(Color::White),
+                focused_title: Style::new().fg(Color::LightYellow).bold().underlined(),
+                selection: Style::new().reversed().bold(),
+                editing: Style::new().fg(Color::LightYellow).bold(),
+                line_number: Style::new().fg(Color::Gray),
+                muted: Style::new().fg(Color::Gray),
+                warning: Style::new().fg(Color::LightYellow).bold(),
+                keyword: Style::new().fg(Color::White).bold(),
+                string: Style::new().fg(Color::LightCyan),
+                number: Style::new().fg(Color::LightMagenta),
+                comment: Style::new().fg(Color::Gray).italic(),
+                quoted_identifier: Style::new().fg(Color::LightCyan).underlined(),
+                placeholder: Style::new().fg(Color::Black).bg(Color::LightYellow).bold(),
+                literal: Style::new().fg(Color::Black).bg(Color::White),
+                linked: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightCyan)
+                    .bold()
+                    .underlined(),
+                search_match: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightMagenta)
+                    .underlined(),
+                current_match: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightRed)
+                    .bold()
+                    .underlined(),
+                ..Theme::accented(Color::LightYellow)
+            },
+            ThemeName::Solarized => Theme {
+                line_number: Style::new().fg(BASE01),
+                muted: Style::new().fg(BASE01),
+                warning: Style::new().fg(SOLARIZED_YELLOW),
+                keyword: Style::new().fg(SOLARIZED_GREEN).bold(),
+                string: Style::new().fg(SOLARIZED_CYAN),
+                number: Style::new().fg(SOLARIZED_MAGENTA),
+                comment: Style::new().fg(BASE01).italic(),
+                quoted_identifier: Style::new().fg(SOLARIZED_BLUE),
+                placeholder: Style::new().fg(SOLARIZED_ORANGE).bold(),
+                literal: Style::new().fg(BASE03).bg(SOLARIZED_YELLOW),
+                linked: Style::new().fg(BASE03).bg(SOLARIZED_CYAN).bold(),
+                search_match: Style::new().fg(BASE03).bg(SOLARIZED_MAGENTA),
+                current_match: Style::new().fg(BASE03).bg(SOLARIZED_ORANGE).bold(),
+                ..Theme::accented(SOLARIZED_BLUE)
+            },
+        }
+    }
+
+    /// Tells things apart with modifiers only, for `NO_COLOR`.
+    pub fn no_color() -> Theme {
+        Theme {
+            text: Style::new(),
+            border: Style::new(),
+            title: Style::new(),
+            focused_title: Style::new().bold().underlined(),
+            cursor: Style::new().reversed(),
+            selection: Style::new().reversed(),
+            editing: Style::new().underlined(),
+            line_number: Style::new().dim(),
+            current_line: Style::new().bold(),
+            muted: Style::new().dim(),
+            warning: Style::new().bold(),
+            progress: Style::new().bold(),
+            mode: Style::new().reversed().bold(),
+            keyword: Style::new().bold(),
+            string: Style::new(),
+            number: Style::new(),
+            comment: Style::new().italic(),
+            quoted_identifier: Style::new(),
+            placeholder: Style::new().bold().underlined(),
+            literal: Style::new().underlined(),
+            linked: Style::new().reversed().bold(),
+            search_match: Style::new().underlined(),
+            current_match: Style::new().reversed().underlined(),
+        }
+    }
+
+    /// The dark theme with `accent`, the other themes start from it.
+    fn accented(accent: Color) -> Theme {
+        let mut theme = Theme {
+            text: Style::new(),
+            border: Style::new(),
+            title: Style::new(),
+            focused_title: Style::new().bold(),
+            cursor: Style::new(),
+            selection: Style::new().reversed(),
+            editing: Style::new(),
+            line_number: Style::new().dim(),
+            current_line: Style::new().bold(),
+            muted: Style::new().dim(),
+            warning: Style::new().fg(Color::Yellow),
+            progress: Style::new(),
+            mode: Style::new().fg(Color::Black).bold(),
+            keyword: Style::new().fg(Color::Blue).bold(),
+            string: Style::new().fg(Color::Green),
+            number: Style::new().fg(Color::Magenta),
+            comment: Style::new().fg(Color::DarkGray).italic(),
+            quoted_identifier: Style::new().fg(Color::Cyan),
+            placeholder: Style::new().fg(Color::Yellow).bold(),
+            literal: Style::new().fg(Color::Black).bg(Color::Yellow),
+            linked: Style::new().fg(Color::Black).bg(Color::Cyan).bold(),
+            search_match: Style::new().fg(Color::Black).bg(Color::Magenta),
+            current_match: Style::new().fg(Color::Black).bg(Color::LightRed).bold(),
+        };
+        theme.set_accent(accent);
+        theme
+    }
+
+    /// Colours the focused pane title, the cursor, the current line, the progress, the edited
+    /// cell and the mode with `accent`.
+    pub fn set_accent(&mut self, accent: Color) {
+        for style in [
+            &mut self.focused_title,
+            &mut self.current_line,
+            &mut self.progress,
+            &mut self.editing,
+        ] {
+            *style = style.fg(accent);
+        }
+        self.cursor = self.cursor.bg(accent);
+        self.mode = self.mode.bg(accent);
+    }
+
+    pub fn style_mut(&mut self, element: ThemeElement) -> &mut Style {
+        match element {
+            ThemeElement::Text => &mut self.text,
+            ThemeElement::Border => &mut self.border,
+            ThemeElement::Title => &mut self.title,
+            ThemeElement::FocusedTitle => &mut self.focused_title,
+            ThemeElement::Cursor => &mut self.cursor,
+            ThemeElement::Selection => &mut self.selection,
+            ThemeElement::Editing => &mut self.editing,
+            ThemeElement::LineNumber => &mut self.line_number,
+            ThemeElement::CurrentLine => &mut self.current_line,
+            ThemeElement::Muted => &mut self.muted,
+            ThemeElement::Warning => &mut self.warning,
+            ThemeElement::Progress => &mut self.progress,
+            ThemeElement::Mode => &mut self.mode,
+            ThemeElement::Keyword => &mut self.keyword,
+            ThemeElement::String => &mut self.string,
+            ThemeElement::Number => &mut self.number,
+            ThemeElement::Comment => &mut self.comment,
+            ThemeElement::QuotedIdentifier => &mut self.quoted_identifier,
+            ThemeElement::Placeholder => &mut self.placeholder,
+            ThemeElement::Literal => &mut self.literal,
+            ThemeElement::Linked => &mut self.linked,
+            ThemeElement::SearchMatch => &mut self.search_match,
+            ThemeElement::CurrentMatch => &mut self.current_match,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::theme::{Theme, ThemeConfig, ThemeElement, ThemeName, ThemeStyle};
+    use ratatui::style::{Color, Modifier, Style, Stylize};
+
+    #[test]
+    fn test_theme_style() {
+        let style = |text: &str| text.parse::<ThemeStyle>().map(|style| style.0);
+        assert_eq!(
+            Ok(Style::new().fg(Color::Black).bg(Color::Yellow).bold()),
+            style("black on yellow bold")
+        );
+        assert_eq!(
+            Ok(Style::new().fg(Color::Rgb(0x26, 0x8b, 0xd2)).underlined()),
+            style("#268bd2 underline")
+        );
+        assert_eq!(Ok(Style::new()), style("none"));
+        assert_eq!(
+            Err(String::from("unknown colour 'blinking'")),
+            style("blinking")
+        );
+        assert_eq!(
+            Err(String::from("missing colour after 'on'")),
+            style("red on")
+        );
+    }
+
+    #[test]
+    fn test_theme_config() {
+        let dark = ThemeConfig::default().theme();
+        assert_eq!(Theme::default(), dark);
+        assert_eq!(Some(Color::Green), dark.focused_title.fg);
+        assert_eq!(Some(Color::Green), dark.cursor.bg);
+
+        let mut config = ThemeConfig {
+            name: ThemeName::Light,
+            accent: Some(Color::Magenta),
+            ..ThemeConfig::default()
+        };
+        config.styles.insert(
+            ThemeElement::Keyword,
+            ThemeStyle(Style::new().fg(Color::Red).italic()),
+        );
+        let light = config.theme();
+        assert_eq!(Some(Color::Magenta), light.mode.bg);
+        assert_eq!(Style::new().fg(Color::Red).italic(), light.keyword);
+        assert_eq!(Theme::named(ThemeName::Light).literal, light.literal);
+
+        // no colour is left, the overrides keep their modifiers
+        config.no_color = true;
+        let plain = config.theme();
+        assert_eq!(Style::new().italic(), plain.keyword);
+        for element in [
+            ThemeElement::Cursor,
+            ThemeElement::Literal,
+            ThemeElement::Linked,
+            ThemeElement::Mode,
+            ThemeElement::FocusedTitle,
+        ] {
+            let style = *plain.clone().style_mut(element);
+            assert_eq!((None, None), (style.fg, style.bg));
+            assert_ne!(Modifier::empty(), style.add_modifier);
+        }
+    }
+}
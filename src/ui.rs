@@ -1,97 +1,956 @@
-use crate::app::{App, AreaEnum};
-use lazy_static::lazy_static;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Style, Stylize};
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
+use crate::app::{App, AreaEnum, Link};
+use crate::export::ExportKind;
+use crate::help::help_sections;
+use crate::highlight::{highlight, mark, text_key};
+use crate::history::format_time;
+use crate::keymap::{Mode, Profile};
+use crate::param_table::ParamColumn;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table,
+};
 use ratatui::Frame;
-use tui_textarea::TextArea;
+use std::ops::Range;
+use strum::IntoEnumIterator;
+use unicode_width::UnicodeWidthChar;
 
-lazy_static! {
-    pub static ref FOCUSED_STYLE: Style = Style::default().fg(Color::Green).bold();
-    pub static ref NORMAL_STYLE: Style = Style::default();
-}
+/// Text marked with a style over its highlighting.
+type Mark = (Range<usize>, Style);
+
+/// Frames of the spinner shown while a background task runs.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
 pub fn ui(frame: &mut Frame, app: &mut App) {
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(vec![
-            Constraint::Percentage(50),
-            Constraint::Percentage(50),
-            Constraint::Min(3),
-        ])
-        .split(frame.area());
-
-    let input_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(layout[0]);
-
-    // recording area coordinate
-    app.set_area_coordinate(AreaEnum::Sql, input_layout[0]);
-    app.set_area_coordinate(AreaEnum::Value, input_layout[1]);
-    app.set_area_coordinate(AreaEnum::Result, layout[1]);
-
-    // render sql textarea
-    render_input(app, frame, input_layout[0], AreaEnum::Sql);
-    // render value textarea
-    render_input(app, frame, input_layout[1], AreaEnum::Value);
-
-    // render result
-    render_result(app, frame, layout[1], AreaEnum::Result);
+    let diagnostics = app
+        .show_diagnostics
+        .then(|| app.diagnostic_items().len().max(1) as u16 + 2);
+    let zoomed = app.zoomed.then_some(app.current_area);
+    let panes = app
+        .config
+        .layout
+        .panes(frame.area(), app.has_list(), diagnostics, zoomed);
+    // recording area coordinate, hidden panes cannot be clicked
+    for (area, rect) in [
+        (AreaEnum::List, panes.list),
+        (AreaEnum::Sql, panes.sql),
+        (AreaEnum::Value, panes.value),
+        (AreaEnum::Result, panes.result),
+        (AreaEnum::Diagnostics, panes.diagnostics),
+    ] {
+        match rect {
+            Some(rect) => app.set_area_coordinate(area, rect),
+            None => {
+                app.area_coordinates.remove(&area);
+            }
+        }
+    }
+    app.split_handles = panes.handles;
+    // the text style of the theme is under every pane
+    frame.render_widget(Block::new().style(app.theme.text), frame.area());
+
+    // the statements of a log are listed on the left
+    if let Some(area) = panes.list {
+        render_list(app, frame, area);
+    }
+    // the placeholder or value under the cursor, marked in both inputs
+    let link = app.link();
+    // render sql with highlighting
+    if let Some(area) = panes.sql {
+        render_input(app, frame, area, AreaEnum::Sql, link.as_ref());
+    }
+    // render value, as text or as a table of the parameters
+    if let Some(area) = panes.value {
+        if app.param_table.is_some() {
+            render_param_table(app, frame, area);
+        } else {
+            render_input(app, frame, area, AreaEnum::Value, link.as_ref());
+        }
+    }
+    if let Some(area) = panes.result {
+        render_result(app, frame, area, AreaEnum::Result);
+    }
+    if let Some(area) = panes.diagnostics {
+        render_diagnostics(app, frame, area);
+    }
 
     // render footer
-    render_footer(frame, layout[2]);
+    render_footer(app, frame, panes.footer, link.as_ref());
+
+    // the history, the help and the command palette are shown over the panes
+    if app.history_view.is_some() {
+        render_history(app, frame, overlay(frame.area(), 90, 80));
+    }
+    if app.help.is_some() {
+        render_help(app, frame, overlay(frame.area(), 90, 80));
+    }
+    if app.palette.is_some() {
+        render_palette(app, frame, overlay(frame.area(), 60, 60));
+    }
+    if app.export.is_some() {
+        render_export(app, frame, overlay(frame.area(), 60, 50));
+    }
 }
 
-fn render_input(app: &App, frame: &mut Frame, area: Rect, area_enum: AreaEnum) {
-    let mut textarea: TextArea;
-    match area_enum {
-        AreaEnum::Sql => {
-            textarea = app.sql_input.clone();
+/// The centre of `area`, `width` and `height` percent of it.
+fn overlay(area: Rect, width: u16, height: u16) -> Rect {
+    let [_, middle, _] = Layout::vertical([
+        Constraint::Percentage((100 - height) / 2),
+        Constraint::Percentage(height),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    let [_, center, _] = Layout::horizontal([
+        Constraint::Percentage((100 - width) / 2),
+        Constraint::Percentage(width),
+        Constraint::Fill(1),
+    ])
+    .areas(middle);
+    center
+}
+
+/// Lists every key of the active keymap by section, then the commands.
+fn render_help(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = &app.theme;
+    let sections = help_sections(&app.config.keybindings, &app.keymap);
+    let width = sections
+        .iter()
+        .flat_map(|section| &section.keys)
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for section in sections {
+        if !lines.is_empty() {
+            lines.push(Line::default());
         }
-        AreaEnum::Value => {
-            textarea = app.value_input.clone();
+        lines.push(Line::styled(section.title, theme.focused_title));
+        for (key, text) in section.keys {
+            lines.push(Line::from(vec![
+                Span::from(format!("  {:<1$}  ", key, width)).bold(),
+                Span::from(text),
+            ]));
         }
-        _ => return,
     }
-    textarea.set_block(new_block(app, area_enum));
 
-    if app.current_area == area_enum {
-        textarea.set_cursor_style(Style::default().bg(Color::Green));
+    let block = Block::new()
+        .title("Keys")
+        .title_style(theme.focused_title)
+        .title_bottom(Line::from("Up/Down: Scroll | Esc: Close").right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border);
+    // the last line stays at the bottom
+    let height = block.inner(area).height;
+    let last = (lines.len() as u16).saturating_sub(height);
+    let scroll = app.help.map_or(0, |scroll| scroll.min(last));
+    app.help = Some(scroll);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)).block(block), area);
+}
+
+/// The commands matching the search, with their keys.
+fn render_palette(app: &mut App, frame: &mut Frame, area: Rect) {
+    let theme = &app.theme;
+    let keybindings = &app.config.keybindings;
+    let Some(palette) = app.palette.as_mut() else {
+        return;
+    };
+    let matches = palette.matches();
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = matches
+        .iter()
+        .map(|command| {
+            let title = command.title();
+            let key = keybindings
+                .key(*command)
+                .map(|key| key.to_string())
+                .unwrap_or_default();
+            let gap = width.saturating_sub(title.len() + key.len()).max(2);
+            ListItem::new(Line::from(vec![
+                Span::from(title),
+                Span::from(" ".repeat(gap)),
+                Span::styled(key, theme.muted),
+            ]))
+        })
+        .collect();
+
+    let block = Block::new()
+        .title("Commands")
+        .title_style(theme.focused_title)
+        .title(Line::from(format!("{}/{}", matches.len(), palette.commands.len())).right_aligned())
+        .title_bottom(format!("> {}", palette.query))
+        .title_bottom(Line::from("Enter: Run | Esc: Close").right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border);
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme.selection);
+    palette
+        .list_state
+        .select((!matches.is_empty()).then_some(palette.selected));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut palette.list_state);
+}
+
+/// What can be exported with the one picked, the path typed and what Tab completes it to.
+fn render_export(app: &mut App, frame: &mut Frame, area: Rect) {
+    let count = if app.statements.is_empty() {
+        usize::from(!app.get_sql_text().trim().is_empty())
     } else {
-        textarea.set_cursor_style(Style::default());
+        app.visible_statements().len()
+    };
+    let statements = match count {
+        1 => String::from("1 statement"),
+        count => format!("{} statements", count),
+    };
+    let theme = &app.theme;
+    let Some(dialog) = app.export.as_ref() else {
+        return;
+    };
+    let mut lines: Vec<Line> = ExportKind::iter()
+        .map(|kind| {
+            let detail = match kind {
+                ExportKind::Result => String::from("the Result area"),
+                ExportKind::Statements => format!("{}, one per line", statements),
+                ExportKind::Json | ExportKind::Csv => format!("{} with details", statements),
+                ExportKind::Session => String::from("to open this view again, --session <file>"),
+            };
+            let line = Line::from(vec![
+                Span::from(format!(" {:<16}", kind.title())),
+                Span::styled(detail, theme.muted),
+            ]);
+            if kind == dialog.kind {
+                line.style(theme.selection)
+            } else {
+                line
+            }
+        })
+        .collect();
+    lines.push(Line::default());
+    lines.push(Line::from(vec![
+        Span::from(" File: ").bold(),
+        Span::from(dialog.path.clone()),
+        Span::styled(" ", theme.cursor.reversed()),
+    ]));
+    lines.extend(
+        dialog
+            .completions
+            .iter()
+            .map(|path| Line::styled(format!("       {}", path), theme.muted)),
+    );
+
+    let block = Block::new()
+        .title("Export")
+        .title_style(theme.focused_title)
+        .title_bottom(
+            Line::from("Up/Down: What | Tab: Complete | Enter: Write | Esc: Close").right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_history(app: &mut App, frame: &mut Frame, area: Rect) {
+    let keybindings = &app.config.keybindings;
+    let help = format!(
+        "Enter: Open | {}: Clear search | {}/{}: Close",
+        keybindings.clear, keybindings.history, keybindings.exit
+    );
+    let theme = &app.theme;
+    let enabled = app.history.is_enabled();
+    let Some(view) = app.history_view.as_mut() else {
+        return;
+    };
+    let visible = view.visible();
+    let items: Vec<ListItem> = visible
+        .iter()
+        .map(|&index| {
+            let entry = &view.entries[index];
+            // one line per entry, the whole statement is in the inputs once opened
+            let bound = entry.bound.split_whitespace().collect::<Vec<_>>().join(" ");
+            ListItem::new(Line::from(vec![
+                Span::styled(format_time(entry.time), theme.muted),
+                Span::styled(format!("  {:<8} ", entry.format), theme.muted),
+                Span::from(bound),
+            ]))
+        })
+        .collect();
+
+    let title = if enabled {
+        "History"
+    } else {
+        "History (turned off)"
+    };
+    let block = Block::new()
+        .title(title)
+        .title_style(theme.focused_title)
+        .title(Line::from(format!("{}/{}", visible.len(), view.entries.len())).right_aligned())
+        .title_bottom(format!("Search: {}", view.query))
+        .title_bottom(Line::from(help).right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border);
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme.selection);
+    view.list_state
+        .select(visible.iter().position(|&index| index == view.selected));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut view.list_state);
+}
+
+/// Lists the diagnostics, with the statement they are about when a log was read.
+fn render_diagnostics(app: &mut App, frame: &mut Frame, area: Rect) {
+    let items = app.diagnostic_items();
+    let list_items: Vec<ListItem> = if items.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "Nothing looks wrong",
+            app.theme.muted,
+        ))]
+    } else {
+        items
+            .iter()
+            .map(|item| {
+                let mut spans = Vec::new();
+                if let Some(index) = item.statement {
+                    let statement = &app.statements[index];
+                    let mut label = format!("#{} {}", index + 1, statement.kind);
+                    if let Some(table) = &statement.table {
+                        label.push_str(&format!(" {}", table));
+                    }
+                    spans.push(Span::styled(format!("{}  ", label), app.theme.muted));
+                }
+                spans.push(Span::from(item.diagnostic.message.clone()));
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
+    let mut block = new_block(app, AreaEnum::Diagnostics);
+    if app.current_area == AreaEnum::Diagnostics {
+        block = block.title_bottom(Line::from("Enter: Jump").right_aligned());
     }
+    let list = List::new(list_items)
+        .block(block)
+        .highlight_style(app.theme.selection);
+    app.diagnostic_selected = app.diagnostic_selected.min(items.len().saturating_sub(1));
+    app.diagnostic_state.select(
+        (!items.is_empty() && app.current_area == AreaEnum::Diagnostics)
+            .then_some(app.diagnostic_selected),
+    );
+    frame.render_stateful_widget(list, area, &mut app.diagnostic_state);
+}
+
+fn render_list(app: &mut App, frame: &mut Frame, area: Rect) {
+    let visible = app.visible_statements();
+    let items: Vec<ListItem> = visible
+        .iter()
+        .map(|&index| {
+            let item = &app.statements[index];
+            let statement = &item.statement;
+            let mut spans = vec![Span::from(item.kind.clone()).bold()];
+            let details = [
+                item.table.clone(),
+                // the time is enough, the date rarely changes within a log
+                statement.timestamp.as_ref().map(|timestamp| {
+                    timestamp
+                        .rsplit([' ', 'T'])
+                        .next()
+                        .unwrap_or(timestamp)
+                        .to_string()
+                }),
+                statement.thread.clone(),
+                statement.rows.map(|rows| format!("{} rows", rows)),
+            ];
+            for detail in details.into_iter().flatten() {
+                spans.push(Span::styled(format!("  {}", detail), app.theme.muted));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
 
-    frame.render_widget(&textarea, area);
+    let mut block = new_block(app, AreaEnum::List)
+        .title(Line::from(format!("{}/{}", visible.len(), app.statements.len())).right_aligned());
+    if !app.filter.is_empty() || app.current_area == AreaEnum::List {
+        let mut title = vec![Span::from(format!("Filter: {}", app.filter))];
+        if let Some(error) = app.filter_error() {
+            title.push(Span::styled(format!("  {}", error), app.theme.warning));
+        }
+        block = block.title_bottom(Line::from(title));
+    }
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(app.theme.selection);
+    let selected = visible.iter().position(|&index| index == app.selected);
+    app.list_state.select(selected);
+    frame.render_stateful_widget(list, area, &mut app.list_state);
 }
 
-fn render_result(app: &App, frame: &mut Frame, area: Rect, area_enum: AreaEnum) {
-    let paragraph = Paragraph::new(app.result.clone())
-        .wrap(Wrap { trim: true })
+/// Renders the Sql or Value input, the sql highlighted, with the placeholders and the value
+/// of `link` marked.
+fn render_input(
+    app: &mut App,
+    frame: &mut Frame,
+    area: Rect,
+    area_enum: AreaEnum,
+    link: Option<&Link>,
+) {
+    let inner = new_block(app, area_enum).inner(area);
+    // the current match is marked over the selection, the selection over the link
+    let (current, matches) = search_marks(app, area_enum);
+    let mut marks: Vec<_> = current
+        .into_iter()
+        .chain(
+            app.selection(area_enum)
+                .map(|range| (range, app.theme.selection)),
+        )
+        .chain(matches)
+        .collect();
+    let linked = app.theme.linked;
+    let (input, cache, scroll) = match area_enum {
+        AreaEnum::Sql => {
+            marks.extend(
+                link.iter()
+                    .flat_map(|link| &link.placeholders)
+                    .map(|span| (span.clone(), linked)),
+            );
+            (&app.sql_input, &mut app.sql_lines, &mut app.sql_scroll)
+        }
+        AreaEnum::Value => {
+            marks.extend(
+                link.and_then(|link| link.value.as_ref())
+                    .map(|value| (value.span.clone(), linked)),
+            );
+            (
+                &app.value_input,
+                &mut app.value_lines,
+                &mut app.value_scroll,
+            )
+        }
+        AreaEnum::List | AreaEnum::Result | AreaEnum::Diagnostics => return,
+    };
+    let (cursor, cell) = cursor_cell(input.lines(), input.cursor());
+    *scroll = scroll_to(*scroll, cursor, cell, inner);
+    let scroll = *scroll;
+    // the text is styled again only when it changed, and only the rows in view are drawn
+    let key = text_key(app.generation, &marks);
+    let theme = &app.theme;
+    let lines: Vec<Line> = cache
+        .get(key, || {
+            let text = input.lines().join("\n");
+            // only the sql is highlighted
+            if area_enum == AreaEnum::Sql {
+                highlight(&text, &marks, theme)
+            } else {
+                mark(&text, &marks)
+            }
+        })
+        .iter()
+        .skip(scroll.0 as usize)
+        .take(inner.height as usize)
+        .cloned()
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .scroll((0, scroll.1))
         .block(new_block(app, area_enum));
     frame.render_widget(paragraph, area);
+    if app.current_area == area_enum {
+        render_cursor(app, frame, inner, cursor, scroll);
+    }
 }
 
-fn render_footer(frame: &mut Frame, area: Rect) {
-    frame.render_widget(
-        Paragraph::new("Tab/Mouse: Switch | Ctrl+l: Clear | Esc: Exit").block(
+/// Renders the parameters of the Value pane as a table, with the type picker over it.
+fn render_param_table(app: &mut App, frame: &mut Frame, area: Rect) {
+    let count = app.params().len();
+    let focused = app.current_area == AreaEnum::Value;
+    let theme = app.theme.clone();
+    let mut block = new_block(app, AreaEnum::Value)
+        .title(Line::from(format!("{} parameters", count)).right_aligned());
+    if focused {
+        block = block.title_bottom(
+            Line::from("Enter: Edit | t: Type | n: NULL | a: Add | d: Delete").right_aligned(),
+        );
+    }
+    let Some(table) = app.param_table.as_mut() else {
+        return;
+    };
+    table.select_by(0, count);
+    let rows: Vec<Row> = table
+        .params
+        .iter()
+        .enumerate()
+        .map(|(index, param)| {
+            let mut value = if param.null {
+                Span::styled("NULL", theme.muted)
+            } else {
+                Span::from(param.value.clone())
+            };
+            let mut field_type = Span::from(param.field_type.clone());
+            // the edited cell shows what was typed so far
+            if let Some(text) = table.editing.as_ref().filter(|_| index == table.selected) {
+                let edited = Span::styled(format!("{}▏", text), theme.editing);
+                match table.column {
+                    ParamColumn::Value => value = edited,
+                    ParamColumn::Type => field_type = edited,
+                }
+            }
+            Row::new([
+                Cell::from(Span::styled((index + 1).to_string(), theme.muted)),
+                Cell::from(value),
+                Cell::from(field_type),
+                Cell::from(if param.null { "✓" } else { "" }),
+            ])
+        })
+        .collect();
+    let widget = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Length(4),
+        ],
+    )
+    .header(Row::new(["#", "Value", "Type", "NULL"]).bold())
+    .block(block)
+    .row_highlight_style(theme.selection)
+    .cell_highlight_style(theme.editing.reversed());
+    table.state.select((count > 0).then_some(table.selected));
+    table
+        .state
+        .select_column(focused.then_some(match table.column {
+            ParamColumn::Value => 1,
+            ParamColumn::Type => 2,
+        }));
+    frame.render_stateful_widget(widget, area, &mut table.state);
+
+    let Some((types, index)) = &table.picker else {
+        return;
+    };
+    // the picker opens below the selected row, or above it near the bottom
+    let height = (types.len() as u16 + 2).min(area.height);
+    let width = types.iter().map(|name| name.len()).max().unwrap_or(0) as u16 + 4;
+    let row = area.y + 2 + table.selected.saturating_sub(table.state.offset()) as u16;
+    let y = if row + 1 + height <= area.bottom() {
+        row + 1
+    } else {
+        area.bottom().saturating_sub(height)
+    };
+    let picker = Rect::new(
+        area.x + area.width / 2,
+        y,
+        width.min(area.width / 2),
+        height,
+    );
+    let items: Vec<ListItem> = types
+        .iter()
+        .map(|name| ListItem::new(name.clone()))
+        .collect();
+    let list = List::new(items)
+        .block(
             Block::new()
-                .title("Help")
+                .title("Type")
                 .borders(Borders::ALL)
-                .border_type(BorderType::Rounded),
-        ),
-        area,
+                .border_type(BorderType::Rounded)
+                .border_style(theme.border),
+        )
+        .highlight_style(theme.selection);
+    frame.render_widget(Clear, picker);
+    frame.render_stateful_widget(
+        list,
+        picker,
+        &mut ListState::default().with_selected(Some(*index)),
+    );
+}
+
+/// The (row, column) of the cell of a (row, character) `cursor` of `lines`, wide characters
+/// take two columns, and the width of the character under it.
+fn cursor_cell(lines: &[String], cursor: (usize, usize)) -> ((usize, usize), u16) {
+    let line = lines.get(cursor.0).map_or("", String::as_str);
+    let column = line
+        .chars()
+        .take(cursor.1)
+        .map(|c| c.width().unwrap_or(0))
+        .sum();
+    let width = line.chars().nth(cursor.1).and_then(|c| c.width());
+    ((cursor.0, column), width.unwrap_or(1).max(1) as u16)
+}
+
+/// Moves the (row, column) `scroll` offset just enough to keep the `cell` columns wide `cursor`
+/// inside `area`.
+fn scroll_to(scroll: (u16, u16), cursor: (usize, usize), cell: u16, area: Rect) -> (u16, u16) {
+    let follow = |offset: u16, position: usize, extent: u16, size: u16| {
+        let position = u16::try_from(position).unwrap_or(u16::MAX);
+        let end = position.saturating_add(extent);
+        if position < offset {
+            position
+        } else if size > 0 && end > offset.saturating_add(size) {
+            end - size.min(end)
+        } else {
+            offset
+        }
+    };
+    (
+        follow(scroll.0, cursor.0, 1, area.height),
+        follow(scroll.1, cursor.1, cell, area.width),
+    )
+}
+
+fn render_cursor(
+    app: &App,
+    frame: &mut Frame,
+    area: Rect,
+    cursor: (usize, usize),
+    scroll: (u16, u16),
+) {
+    let visible = |position: usize, offset: u16, size: u16| {
+        u16::try_from(position)
+            .ok()
+            .and_then(|position| position.checked_sub(offset))
+            .filter(|position| *position < size)
+    };
+    if let (Some(row), Some(column)) = (
+        visible(cursor.0, scroll.0, area.height),
+        visible(cursor.1, scroll.1, area.width),
+    ) {
+        frame.buffer_mut()[(area.x + column, area.y + row)].set_style(app.theme.cursor);
+    }
+}
+
+/// Renders the result with line numbers, the selection and the bound literals marked, and
+/// keeps the cursor line in view.
+fn render_result(app: &mut App, frame: &mut Frame, area: Rect, area_enum: AreaEnum) {
+    let focused = app.current_area == area_enum;
+    let theme = &app.theme;
+    let mut block = new_block(app, area_enum);
+    // the diagnostics of the statement are counted when the panel is hidden
+    if !app.show_diagnostics && !app.diagnostics.is_empty() {
+        let count = app.diagnostics.len();
+        let warnings = format!(
+            "{} warning{} ({})",
+            count,
+            if count == 1 { "" } else { "s" },
+            app.config.keybindings.diagnostics
+        );
+        block = block.title(Line::from(warnings).right_aligned().style(theme.warning));
+    }
+    if let Some(progress) = progress(app) {
+        block = block.title(Line::from(progress).right_aligned().style(theme.progress));
+    }
+    if focused {
+        block = block.title_bottom(
+            Line::from("w: Wrap | v: Select lines | s: Statement | y: Copy").right_aligned(),
+        );
+    }
+    let inner = block.inner(area);
+    // the current match is marked over the selection, the selection over the literals
+    let (current, matches) = search_marks(app, area_enum);
+    let marks: Vec<_> = current
+        .into_iter()
+        .chain(
+            app.result_view
+                .selected_range(&app.result)
+                .map(|range| (range, theme.selection)),
+        )
+        .chain(matches)
+        .chain(
+            app.result_literals
+                .iter()
+                .map(|range| (range.clone(), theme.literal)),
+        )
+        .collect();
+    let key = text_key(app.generation, &marks);
+    let lines = app
+        .result_lines
+        .get(key, || highlight(&app.result, &marks, theme));
+    let view = &mut app.result_view;
+    view.cursor = view.cursor.min(lines.len().saturating_sub(1));
+
+    let gutter = lines.len().to_string().len() + 1;
+    let width = (inner.width as usize).saturating_sub(gutter).max(1);
+    // the line of every row, counted again only when the result, the width or the wrapping
+    // changed, and only the rows in view are made
+    let rows_key = (app.generation, width, view.wrap);
+    if view.rows_key != Some(rows_key) {
+        view.rows.clear();
+        for (number, line) in lines.iter().enumerate() {
+            let count = if view.wrap { row_count(line, width) } else { 1 };
+            view.rows.extend(std::iter::repeat_n(number, count));
+        }
+        view.rows_key = Some(rows_key);
+    }
+    let rows = &view.rows;
+
+    // the rows of the cursor line are kept in view
+    let height = inner.height as usize;
+    let first = rows.partition_point(|&line| line < view.cursor);
+    let last = rows
+        .partition_point(|&line| line <= view.cursor)
+        .saturating_sub(1);
+    if first < view.scroll {
+        view.scroll = first;
+    } else if height > 0 && last >= view.scroll + height {
+        view.scroll = (last + 1 - height).min(first);
+    }
+    view.scroll = view.scroll.min(rows.len().saturating_sub(1));
+
+    let shown = view.scroll..(view.scroll + height).min(rows.len());
+    let mut text = Vec::new();
+    if let (Some(&top), Some(&bottom)) = (rows.get(shown.start), rows.get(shown.end.max(1) - 1)) {
+        for (number, line) in lines.iter().enumerate().take(bottom + 1).skip(top) {
+            let line = line.clone();
+            let parts = if view.wrap {
+                wrap_line(line, width)
+            } else {
+                vec![skip_columns(line, view.column)]
+            };
+            let start = rows.partition_point(|&line| line < number);
+            for (part, mut row) in parts.into_iter().enumerate() {
+                if !shown.contains(&(start + part)) {
+                    continue;
+                }
+                // the number is shown on the first row of a wrapped line
+                let label = if part == 0 {
+                    format!("{:>1$} ", number + 1, gutter - 1)
+                } else {
+                    " ".repeat(gutter)
+                };
+                let style = if focused && number == view.cursor {
+                    theme.current_line
+                } else {
+                    theme.line_number
+                };
+                row.spans.insert(0, Span::styled(label, style));
+                text.push(row);
+            }
+        }
+    }
+
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// The marks of the match moved to and of the other search matches in `area_enum`.
+fn search_marks(app: &App, area_enum: AreaEnum) -> (Option<Mark>, Vec<Mark>) {
+    let Some(search) = app
+        .search
+        .as_ref()
+        .filter(|search| search.area == area_enum)
+    else {
+        return (None, Vec::new());
+    };
+    let current = search
+        .matches
+        .get(search.current)
+        .map(|range| (range.clone(), app.theme.current_match));
+    let matches = search
+        .matches
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != search.current)
+        .map(|(_, range)| (range.clone(), app.theme.search_match))
+        .collect();
+    (current, matches)
+}
+
+/// The background task running, with a spinner and how far it got.
+fn progress(app: &App) -> Option<String> {
+    let task = app.worker.task()?;
+    let step = task.started.elapsed().as_millis() / 100;
+    let mut text = format!("{} {}", SPINNER[step as usize % SPINNER.len()], task.label);
+    if let Some(percent) = task.progress.percent() {
+        text.push_str(&format!(" {}%", percent));
+    }
+    if app.worker.is_parsing() {
+        text.push_str(" (Esc: Cancel)");
+    }
+    Some(text)
+}
+
+/// How many rows `wrap_line` splits `line` into.
+fn row_count(line: &Line, width: usize) -> usize {
+    let mut rows = 1;
+    let mut used = 0;
+    for c in line.spans.iter().flat_map(|span| span.content.chars()) {
+        let cells = c.width().unwrap_or(0);
+        if used + cells > width && used > 0 {
+            rows += 1;
+            used = 0;
+        }
+        used += cells;
+    }
+    rows
+}
+
+/// Splits `line` into rows at most `width` cells wide.
+fn wrap_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let mut rows = vec![Line::default()];
+    let mut used = 0;
+    for span in line.spans {
+        let mut part = String::new();
+        for c in span.content.chars() {
+            let cells = c.width().unwrap_or(0);
+            if used + cells > width && used > 0 {
+                let row = rows.last_mut().expect("one row at least");
+                row.spans
+                    .push(Span::styled(std::mem::take(&mut part), span.style));
+                rows.push(Line::default());
+                used = 0;
+            }
+            part.push(c);
+            used += cells;
+        }
+        if !part.is_empty() {
+            let row = rows.last_mut().expect("one row at least");
+            row.spans.push(Span::styled(part, span.style));
+        }
+    }
+    rows
+}
+
+/// Drops the first `columns` cells of `line`.
+fn skip_columns(line: Line<'static>, columns: usize) -> Line<'static> {
+    let mut skipped = 0;
+    let spans = line
+        .spans
+        .into_iter()
+        .filter_map(|span| {
+            let content: String = span
+                .content
+                .chars()
+                .skip_while(|c| {
+                    let skip = skipped < columns;
+                    if skip {
+                        skipped += c.width().unwrap_or(0);
+                    }
+                    skip
+                })
+                .collect();
+            (!content.is_empty()).then(|| Span::styled(content, span.style))
+        })
+        .collect::<Vec<_>>();
+    Line::from(spans)
+}
+
+fn render_footer(app: &App, frame: &mut Frame, area: Rect, link: Option<&Link>) {
+    let keybindings = &app.config.keybindings;
+    let mut help = format!(
+        "{}: Keys | {}: Commands | {}/Mouse: Switch | {}: Clear | {}: Copy | {}: Exit",
+        keybindings.help,
+        keybindings.palette,
+        keybindings.next_area,
+        keybindings.clear,
+        keybindings.copy,
+        keybindings.exit
     );
+    if app.has_list() {
+        help.push_str(&format!(" | {}: Copy all", keybindings.copy_all));
+    }
+    help.push_str(&format!(
+        " | {}: History | {}: Table | {}: Diagnostics | {}: Zoom | {}: Layout",
+        keybindings.history,
+        keybindings.table,
+        keybindings.diagnostics,
+        keybindings.zoom,
+        keybindings.layout
+    ));
+    if keybindings.profile == Profile::Vim {
+        help.push_str(" | :q: Quit");
+    }
+    // the command line, the search or the outcome of the last command replace the help
+    let search = app.search.as_ref().filter(|search| search.typing);
+    let text = match (search, &app.mode, &app.message) {
+        (Some(search), _, _) => format!("/{}  {}", search.query, search.status()),
+        (_, Mode::Command(line), _) if keybindings.profile == Profile::Emacs => {
+            format!("M-x {}", line)
+        }
+        (_, Mode::Command(line), _) => format!(":{}", line),
+        (_, _, Some(message)) => message.clone(),
+        _ => help,
+    };
+
+    let mut title = vec![Span::from("Help")];
+    if let Some(mode) = mode_name(app) {
+        title.push(Span::from(" "));
+        title.push(Span::styled(format!(" {} ", mode), app.theme.mode));
+    }
+    let mut block = Block::new()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(app.theme.border);
+    // the value of the linked placeholder
+    if let Some(link) = link {
+        block = block.title(
+            Line::from(link.status())
+                .right_aligned()
+                .style(app.theme.linked),
+        );
+    }
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// The mode shown in the footer, the default profile has none.
+fn mode_name(app: &App) -> Option<&'static str> {
+    match (app.config.keybindings.profile, &app.mode) {
+        (_, Mode::Command(_)) => Some("COMMAND"),
+        _ if app.search.as_ref().is_some_and(|search| search.typing) => Some("SEARCH"),
+        (Profile::Vim, Mode::Normal) => Some("NORMAL"),
+        (Profile::Vim, Mode::Visual) => Some("VISUAL"),
+        (Profile::Vim, Mode::Insert) => Some("INSERT"),
+        (Profile::Emacs, _) if app.mark => Some("MARK"),
+        (Profile::Emacs, _) => Some("EMACS"),
+        _ => None,
+    }
 }
 
-fn new_block(app: &App, area_enum: AreaEnum) -> Block {
-    Block::new()
+fn new_block(app: &App, area_enum: AreaEnum) -> Block<'static> {
+    let mut block = Block::new();
+    // the search stays marked after its prompt closed, where it is shows below
+    if let Some(search) = app
+        .search
+        .as_ref()
+        .filter(|s| s.area == area_enum && !s.typing)
+    {
+        let status = search.status();
+        block = block.title_bottom(format!("/{}  {}", search.query, status));
+    }
+    block
         .title(area_enum.title().to_string())
         .title_style(if app.current_area == area_enum {
-            *FOCUSED_STYLE
+            app.theme.focused_title
         } else {
-            *NORMAL_STYLE
+            app.theme.title
         })
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
+        .border_style(app.theme.border)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::{cursor_cell, scroll_to};
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_cursor_cell() {
+        let lines = vec![String::from("张三(String), 18(Integer)")];
+        assert_eq!(((0, 0), 2), cursor_cell(&lines, (0, 0)));
+        assert_eq!(((0, 4), 1), cursor_cell(&lines, (0, 2)));
+        // after the end of the line
+        assert_eq!(((0, 25), 1), cursor_cell(&lines, (0, 23)));
+        assert_eq!(((1, 0), 1), cursor_cell(&lines, (1, 0)));
+
+        let area = Rect::new(0, 0, 10, 5);
+        assert_eq!((0, 0), scroll_to((0, 0), (0, 8), 2, area));
+        // a wide character is kept whole in view
+        assert_eq!((0, 1), scroll_to((0, 0), (0, 9), 2, area));
+        assert_eq!((0, 3), scroll_to((0, 8), (0, 3), 1, area));
+        assert_eq!((5, 0), scroll_to((0, 0), (9, 0), 1, area));
+        assert_eq!((0, u16::MAX - 10), scroll_to((0, 0), (0, 100_000), 1, area));
+    }
 }
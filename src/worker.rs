@@ -0,0 +1,430 @@
+use crate::app::StatementItem;
+use crate::clipboard::Clipboard;
+use crate::config::ClipboardConfig;
+use crate::core::{bind, BindOptions, Binding};
+use crate::diagnostic::{check, Diagnostic};
+use crate::log_parser::{parse_statements, unwrap_line, LogFormat, Statement};
+use crossterm::event::Event;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What wakes the event loop: terminal events and the outcome of background tasks.
+pub enum Message {
+    Input(Event),
+    Parsed(u64, Parsed),
+    Bound(u64, Bound),
+    /// The text of the clipboard, and whether it is watched
+    Clipboard(String, bool),
+}
+
+/// The statements of a log read from the clipboard.
+pub struct Parsed {
+    /// Sql lines of the log, more than statements when some have no parameters line
+    pub prepared: usize,
+    pub statements: Vec<StatementItem>,
+    /// Whether the log was copied while the clipboard is watched
+    pub watched: bool,
+}
+
+/// The binding of the inputs, with what looks wrong with it.
+pub struct Bound {
+    pub sql: String,
+    pub value: String,
+    pub binding: Binding,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// How far a task got, shared with the thread running it.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    done: Arc<AtomicUsize>,
+    /// Zero when the task cannot tell how long it takes
+    total: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Progress {
+    fn add(&self, done: usize) {
+        self.done.fetch_add(done, Ordering::Relaxed);
+    }
+
+    fn add_total(&self, total: usize) {
+        self.total.fetch_add(total, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Percentage done, `None` when it is not known.
+    pub fn percent(&self) -> Option<u16> {
+        let total = self.total.load(Ordering::Relaxed);
+        let done = self.done.load(Ordering::Relaxed);
+        (total > 0).then(|| (done.min(total) * 100 / total).min(99) as u16)
+    }
+}
+
+/// A task running in the background.
+pub struct Task {
+    id: u64,
+    /// What is shown while it runs, e.g. "parsing the clipboard"
+    pub label: &'static str,
+    pub started: Instant,
+    pub progress: Progress,
+}
+
+/// What the background thread is asked to do, a new request of a kind replaces the one waiting.
+#[derive(Default)]
+struct Requests {
+    bind: Option<BindRequest>,
+    /// The clipboard to read and whether it is watched
+    read: Option<(ClipboardConfig, bool)>,
+    stopped: bool,
+}
+
+struct BindRequest {
+    id: u64,
+    sql: String,
+    value: String,
+    options: BindOptions,
+    progress: Progress,
+}
+
+type Queue = Arc<(Mutex<Requests>, Condvar)>;
+
+/// Parses logs on a thread each, binds large inputs and reads the clipboard on a thread living
+/// as long as the worker. A new task of a kind cancels the one running.
+pub struct Worker {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    queue: Queue,
+    next_id: u64,
+    parse: Option<Task>,
+    bind: Option<Task>,
+}
+
+impl Default for Worker {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let queue = Queue::default();
+        let (background, messages) = (Arc::clone(&queue), sender.clone());
+        thread::spawn(move || serve(&background, &messages));
+        Worker {
+            sender,
+            receiver,
+            queue,
+            next_id: 0,
+            parse: None,
+            bind: None,
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.request(|requests| requests.stopped = true);
+    }
+}
+
+impl Worker {
+    /// Where terminal events are sent to reach the event loop.
+    pub fn sender(&self) -> Sender<Message> {
+        self.sender.clone()
+    }
+
+    /// Waits for the next message, at most `timeout` when given.
+    pub fn recv(&self, timeout: Option<Duration>) -> Option<Message> {
+        match timeout {
+            Some(timeout) => self.receiver.recv_timeout(timeout).ok(),
+            None => self.receiver.recv().ok(),
+        }
+    }
+
+    /// The next message when one is waiting.
+    pub fn try_recv(&self) -> Option<Message> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// The running task shown in the TUI, the parsing first.
+    pub fn task(&self) -> Option<&Task> {
+        self.parse.as_ref().or(self.bind.as_ref())
+    }
+
+    pub fn is_parsing(&self) -> bool {
+        self.parse.is_some()
+    }
+
+    pub fn is_binding(&self) -> bool {
+        self.bind.is_some()
+    }
+
+    /// Parses `content` in the background, see `parse_log`.
+    pub fn parse(
+        &mut self,
+        content: String,
+        formats: Vec<LogFormat>,
+        options: BindOptions,
+        watched: bool,
+    ) {
+        let task = self.start("parsing the clipboard");
+        let (id, progress, sender) = (task.id, task.progress.clone(), self.sender.clone());
+        cancel(self.parse.replace(task));
+        thread::spawn(move || {
+            if let Some(parsed) = parse_log(&content, formats, &options, watched, &progress) {
+                let _ = sender.send(Message::Parsed(id, parsed));
+            }
+        });
+    }
+
+    /// Binds `sql` with `value` in the background.
+    pub fn bind(&mut self, sql: String, value: String, options: BindOptions) {
+        let task = self.start("binding");
+        let request = BindRequest {
+            id: task.id,
+            sql,
+            value,
+            options,
+            progress: task.progress.clone(),
+        };
+        cancel(self.bind.replace(task));
+        self.request(|requests| requests.bind = Some(request));
+    }
+
+    /// Reads the clipboard of `config` in the background, its text comes as
+    /// `Message::Clipboard`. Failing to read sends nothing.
+    pub fn read_clipboard(&mut self, config: ClipboardConfig, watched: bool) {
+        self.request(|requests| {
+            let watched = watched || requests.read.as_ref().is_some_and(|(_, watched)| *watched);
+            requests.read = Some((config, watched));
+        });
+    }
+
+    fn request(&self, add: impl FnOnce(&mut Requests)) {
+        let (requests, ready) = &*self.queue;
+        add(&mut requests.lock().unwrap());
+        ready.notify_one();
+    }
+
+    /// Whether `id` is the task running, which is then done. Outcomes of cancelled tasks may
+    /// still arrive and are dropped.
+    pub fn finish(&mut self, id: u64) -> bool {
+        for slot in [&mut self.parse, &mut self.bind] {
+            if slot.as_ref().is_some_and(|task| task.id == id) {
+                *slot = None;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Cancels the parsing of a log, returns whether one was running. A binding is cancelled
+    /// by the next one.
+    pub fn cancel_parse(&mut self) -> bool {
+        let running = self.parse.is_some();
+        cancel(self.parse.take());
+        running
+    }
+
+    fn start(&mut self, label: &'static str) -> Task {
+        self.next_id += 1;
+        Task {
+            id: self.next_id,
+            label,
+            started: Instant::now(),
+            progress: Progress::default(),
+        }
+    }
+}
+
+/// Runs the requests of the queue until the worker is dropped.
+fn serve(queue: &Queue, sender: &Sender<Message>) {
+    // opened again when the configuration changes
+    let mut clipboard: Option<(Clipboard, ClipboardConfig)> = None;
+    loop {
+        let (bind, read) = {
+            let (requests, ready) = &**queue;
+            let waiting = |requests: &mut Requests| {
+                !requests.stopped && requests.bind.is_none() && requests.read.is_none()
+            };
+            let mut requests = ready.wait_while(requests.lock().unwrap(), waiting).unwrap();
+            if requests.stopped {
+                return;
+            }
+            (requests.bind.take(), requests.read.take())
+        };
+        if let Some((config, watched)) = read {
+            if clipboard
+                .as_ref()
+                .is_none_or(|(_, opened)| *opened != config)
+            {
+                clipboard = Some((Clipboard::new(&config), config));
+            }
+            let text = clipboard
+                .as_mut()
+                .map(|(clipboard, _)| clipboard.get_text());
+            if let Some(Ok(text)) = text {
+                if sender.send(Message::Clipboard(text, watched)).is_err() {
+                    return;
+                }
+            }
+        }
+        if let Some(request) = bind.filter(|request| !request.progress.is_cancelled()) {
+            let bound = bind_inputs(request.sql, request.value, &request.options);
+            // the binding cannot be interrupted, it is dropped instead
+            if !request.progress.is_cancelled()
+                && sender.send(Message::Bound(request.id, bound)).is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+fn cancel(task: Option<Task>) {
+    if let Some(task) = task {
+        task.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Reads the statements of the log `content` and binds them, `None` once cancelled.
+pub fn parse_log(
+    content: &str,
+    formats: Vec<LogFormat>,
+    options: &BindOptions,
+    watched: bool,
+    progress: &Progress,
+) -> Option<Parsed> {
+    // counting the sql lines and parsing read the log once each
+    progress.add_total(content.len() * 2);
+    let lines = || {
+        content
+            .lines()
+            .take_while(|_| !progress.is_cancelled())
+            .inspect(|line| progress.add(line.len() + 1))
+    };
+    // the sql lines tell how many statements there should be
+    let prepared = lines()
+        .filter(|line| {
+            let line = unwrap_line(line);
+            formats.iter().any(|format| format.sql.is_match(&line.text))
+        })
+        .count();
+    let statements = parse_statements(lines(), formats);
+    progress.add_total(statements.iter().map(size).sum());
+    let mut items = Vec::with_capacity(statements.len());
+    for statement in statements {
+        if progress.is_cancelled() {
+            return None;
+        }
+        progress.add(size(&statement));
+        items.push(StatementItem::new(statement, options));
+    }
+    (!progress.is_cancelled()).then_some(Parsed {
+        prepared,
+        statements: items,
+        watched,
+    })
+}
+
+fn size(statement: &Statement) -> usize {
+    statement.sql.len() + statement.value.len()
+}
+
+/// Binds the inputs and checks the binding.
+pub fn bind_inputs(sql: String, value: String, options: &BindOptions) -> Bound {
+    let binding = bind(&sql, &value, options);
+    let diagnostics = check(&sql, &value, &binding, options);
+    Bound {
+        sql,
+        value,
+        binding,
+        diagnostics,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clipboard::ClipboardBackend;
+    use crate::config::ClipboardConfig;
+    use crate::core::BindOptions;
+    use crate::log_parser::LogFormat;
+    use crate::test_dir::TestDir;
+    use crate::worker::{parse_log, Message, Progress, Worker};
+    use std::time::Duration;
+
+    const LOG: &str = "==>  Preparing: SELECT * FROM user WHERE id = ?\n==> Parameters: 1(Long)\n";
+
+    #[test]
+    fn test_parse_log() {
+        let options = BindOptions::default();
+        let progress = Progress::default();
+        let parsed = parse_log(LOG, vec![LogFormat::mybatis()], &options, false, &progress);
+        let parsed = parsed.unwrap();
+        assert_eq!(1, parsed.prepared);
+        assert_eq!("SELECT", parsed.statements[0].kind);
+        assert_eq!(Some(99), progress.percent());
+
+        let mut worker = Worker::default();
+        worker.parse(String::from(LOG), vec![LogFormat::mybatis()], options, true);
+        assert!(worker.is_parsing());
+        let Some(Message::Parsed(id, parsed)) = worker.recv(Some(Duration::from_secs(5))) else {
+            panic!("the log is parsed");
+        };
+        assert!(parsed.watched);
+        assert!(worker.finish(id));
+        assert!(!worker.finish(id));
+        assert!(worker.task().is_none());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let progress = Progress::default();
+        progress
+            .cancelled
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        let options = BindOptions::default();
+        assert!(parse_log(LOG, vec![LogFormat::mybatis()], &options, false, &progress).is_none());
+
+        let mut worker = Worker::default();
+        let options = BindOptions::default();
+        worker.bind(String::from("SELECT ?"), String::from("1"), options.clone());
+        let first = worker.task().unwrap().id;
+        worker.bind(String::from("SELECT ?"), String::from("2"), options.clone());
+        // the outcome of the first binding no longer counts
+        assert!(!worker.finish(first));
+        assert!(!worker.cancel_parse());
+        worker.parse(
+            String::from(LOG),
+            vec![LogFormat::mybatis()],
+            options,
+            false,
+        );
+        assert!(worker.cancel_parse());
+        assert!(worker.is_binding() && !worker.is_parsing());
+    }
+
+    #[test]
+    fn test_read_clipboard() {
+        let dir = TestDir::new("worker");
+        let config = ClipboardConfig {
+            backend: ClipboardBackend::File,
+            path: Some(dir.join("clipboard.txt")),
+            ..ClipboardConfig::default()
+        };
+        std::fs::write(dir.join("clipboard.txt"), LOG).unwrap();
+        let mut worker = Worker::default();
+        // a read waiting is done once, watched when either asked for it
+        worker.read_clipboard(config.clone(), true);
+        worker.read_clipboard(config, false);
+        let Some(Message::Clipboard(text, watched)) = worker.recv(Some(Duration::from_secs(5)))
+        else {
+            panic!("the clipboard is read");
+        };
+        assert_eq!(LOG, text);
+        assert!(watched);
+        assert!(worker.task().is_none());
+    }
+}
@@ -0,0 +1,63 @@
+#[path = "../src/test_dir.rs"]
+mod test_dir;
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use test_dir::TestDir;
+
+const LOG: &str = "
+2025-06-13 16:44:56.499 DEBUG 1 --- [exec-1] ==>  Preparing: SELECT * FROM user WHERE id = ?
+2025-06-13 16:44:56.500 DEBUG 1 --- [exec-1] ==> Parameters: 1(Long)
+";
+
+/// Runs sql-param in `dir` with `args` and `stdin`, with the config and the history of `dir`.
+fn run(dir: &TestDir, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sql-param"))
+        .args(args)
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env_remove("SQL_PARAM_CONFIG")
+        .env_remove("SQL_PARAM_DIALECT")
+        .env_remove("SQL_PARAM_PLACEHOLDER_STYLE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_parse_without_command() {
+    let dir = TestDir::new("cli");
+    std::fs::write(dir.join("app.log"), LOG).unwrap();
+    let log = "app.log";
+    let bound = "SELECT * FROM user WHERE id = 1\n";
+
+    assert_eq!(bound, stdout(run(&dir, &["--file", log], "")));
+    assert_eq!(bound, stdout(run(&dir, &["-f", log], "")));
+    assert_eq!(bound, stdout(run(&dir, &["-"], LOG)));
+    assert_eq!(bound, stdout(run(&dir, &[log], "")));
+    // the options of `parse` work without the command too
+    assert_eq!("", stdout(run(&dir, &[log, "--grep", "orders"], "")));
+    assert_eq!(bound, stdout(run(&dir, &["parse", log], "")));
+
+    let output = run(&dir, &["--file", "no-such.log"], "");
+    assert_eq!(Some(1), output.status.code());
+}